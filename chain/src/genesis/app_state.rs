@@ -31,7 +31,10 @@ impl TryFrom<pb::GenesisAppState> for AppState {
 
     fn try_from(msg: pb::GenesisAppState) -> Result<Self, Self::Error> {
         Ok(AppState {
-            chain_params: msg.chain_params.unwrap().into(),
+            chain_params: msg
+                .chain_params
+                .ok_or_else(|| anyhow::anyhow!("genesis app state is missing chain params"))?
+                .try_into()?,
             validators: msg
                 .validators
                 .into_iter()
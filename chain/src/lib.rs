@@ -1,11 +1,11 @@
 mod epoch;
 mod known_assets;
 mod note_source;
+mod transaction_location;
 mod view;
 
 pub mod genesis;
 pub mod params;
-pub mod quarantined;
 pub(crate) mod state_key;
 pub mod sync;
 
@@ -13,4 +13,5 @@ pub use epoch::Epoch;
 pub use known_assets::KnownAssets;
 pub use note_source::NoteSource;
 pub use sync::CompactBlock;
+pub use transaction_location::TransactionLocation;
 pub use view::View;
@@ -1,4 +1,4 @@
-use penumbra_crypto::asset;
+use penumbra_crypto::{asset, proofs::groth16};
 use penumbra_proto::{chain as pb, crypto as pbc, Protobuf};
 use serde::{Deserialize, Serialize};
 
@@ -55,6 +55,20 @@ pub struct ChainParams {
     pub signed_blocks_window_len: u64,
     /// The maximum number of blocks in the window each validator can miss signing without slashing.
     pub missed_blocks_maximum: u64,
+    /// The maximum total commission a validator may charge, in basis points.
+    pub max_commission_bps: u64,
+    /// The maximum increase in total commission a validator may make per epoch, in basis points.
+    pub max_commission_increase_bps_per_epoch: u64,
+    /// The minimum amount of stake a new validator must self-delegate.
+    pub min_self_delegation: u64,
+    /// The maximum age, in blocks, of the note commitment tree anchor a transaction may use.
+    pub max_anchor_age: u64,
+    /// The encoded Groth16 verifying key for the spend circuit, or empty if
+    /// Groth16 spend proofs are not accepted.
+    pub spend_verifying_key: Vec<u8>,
+    /// The encoded Groth16 verifying key for the output circuit, or empty if
+    /// Groth16 output proofs are not accepted.
+    pub output_verifying_key: Vec<u8>,
 
     /// Whether IBC (forming connections, processing IBC packets) is enabled.
     pub ibc_enabled: bool,
@@ -64,11 +78,42 @@ pub struct ChainParams {
     pub outbound_ics20_transfers_enabled: bool,
 }
 
+impl ChainParams {
+    /// Decode the Groth16 verifying keys recorded in these parameters, if any.
+    ///
+    /// Every node verifies Groth16 proofs against these keys, so that they all
+    /// reach the same verdict; if there are none, every node rejects them.
+    pub fn groth16_verifying_keys(&self) -> anyhow::Result<Option<groth16::VerifyingKeys>> {
+        match (
+            self.spend_verifying_key.is_empty(),
+            self.output_verifying_key.is_empty(),
+        ) {
+            (true, true) => Ok(None),
+            (false, false) => Ok(Some(groth16::VerifyingKeys::decode(
+                &self.spend_verifying_key,
+                &self.output_verifying_key,
+            )?)),
+            _ => Err(anyhow::anyhow!(
+                "chain parameters must have both Groth16 verifying keys or neither"
+            )),
+        }
+    }
+}
+
 impl Protobuf<pb::ChainParams> for ChainParams {}
 
-impl From<pb::ChainParams> for ChainParams {
-    fn from(msg: pb::ChainParams) -> Self {
-        ChainParams {
+impl TryFrom<pb::ChainParams> for ChainParams {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::ChainParams) -> Result<Self, Self::Error> {
+        // An anchor has to remain usable for at least the block after it's
+        // recorded, or no spend could ever be valid.
+        anyhow::ensure!(
+            msg.max_anchor_age > 0,
+            "the maximum anchor age must be at least one block"
+        );
+
+        Ok(ChainParams {
             chain_id: msg.chain_id,
             epoch_duration: msg.epoch_duration,
             unbonding_epochs: msg.unbonding_epochs,
@@ -78,10 +123,16 @@ impl From<pb::ChainParams> for ChainParams {
             base_reward_rate: msg.base_reward_rate,
             missed_blocks_maximum: msg.missed_blocks_maximum,
             signed_blocks_window_len: msg.signed_blocks_window_len,
+            max_commission_bps: msg.max_commission_bps,
+            max_commission_increase_bps_per_epoch: msg.max_commission_increase_bps_per_epoch,
+            min_self_delegation: msg.min_self_delegation,
+            max_anchor_age: msg.max_anchor_age,
+            spend_verifying_key: msg.spend_verifying_key,
+            output_verifying_key: msg.output_verifying_key,
             ibc_enabled: msg.ibc_enabled,
             inbound_ics20_transfers_enabled: msg.inbound_ics20_transfers_enabled,
             outbound_ics20_transfers_enabled: msg.outbound_ics20_transfers_enabled,
-        }
+        })
     }
 }
 
@@ -97,6 +148,12 @@ impl From<ChainParams> for pb::ChainParams {
             slashing_penalty_downtime_bps: params.slashing_penalty_downtime_bps,
             slashing_penalty_misbehavior_bps: params.slashing_penalty_misbehavior_bps,
             base_reward_rate: params.base_reward_rate,
+            max_commission_bps: params.max_commission_bps,
+            max_commission_increase_bps_per_epoch: params.max_commission_increase_bps_per_epoch,
+            min_self_delegation: params.min_self_delegation,
+            max_anchor_age: params.max_anchor_age,
+            spend_verifying_key: params.spend_verifying_key,
+            output_verifying_key: params.output_verifying_key,
             ibc_enabled: params.ibc_enabled,
             inbound_ics20_transfers_enabled: params.inbound_ics20_transfers_enabled,
             outbound_ics20_transfers_enabled: params.outbound_ics20_transfers_enabled,
@@ -124,9 +181,36 @@ impl Default for ChainParams {
             slashing_penalty_downtime_bps: 1,
             // 3bps -> 11% return over 365 epochs
             base_reward_rate: 3_0000,
+            // 5000 basis points = 50%
+            max_commission_bps: 5000,
+            // 100 basis points = 1% per epoch
+            max_commission_increase_bps_per_epoch: 100,
+            // 1 penumbra
+            min_self_delegation: 1_000_000,
+            // about an hour and a half, at 5 second blocks
+            max_anchor_age: 1000,
+            spend_verifying_key: Vec::new(),
+            output_verifying_key: Vec::new(),
             ibc_enabled: true,
             inbound_ics20_transfers_enabled: false,
             outbound_ics20_transfers_enabled: false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_max_anchor_age_is_rejected() {
+        let params = pb::ChainParams::from(ChainParams::default());
+        assert!(ChainParams::try_from(params.clone()).is_ok());
+
+        let params = pb::ChainParams {
+            max_anchor_age: 0,
+            ..params
+        };
+        assert!(ChainParams::try_from(params).is_err());
+    }
+}
@@ -1,13 +1,11 @@
 use std::convert::TryFrom;
 
 use anyhow::Result;
-use penumbra_crypto::{IdentityKey, NotePayload, Nullifier};
+use penumbra_crypto::{NotePayload, Nullifier};
 use penumbra_proto::{chain as pb, Protobuf};
 use penumbra_tct::builder::{block, epoch};
 use serde::{Deserialize, Serialize};
 
-use crate::quarantined::Quarantined;
-
 /// A compressed delta update with the minimal data from a block required to
 /// synchronize private client state.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -22,10 +20,6 @@ pub struct CompactBlock {
     pub block_root: block::Root,
     // The epoch root of this epoch, if this block ends an epoch (`None` otherwise).
     pub epoch_root: Option<epoch::Root>,
-    // Newly quarantined things in this block.
-    pub quarantined: Quarantined,
-    // Newly slashed validators in this block.
-    pub slashed: Vec<IdentityKey>,
     // **IMPORTANT NOTE FOR FUTURE HUMANS**: if you want to add new fields to the `CompactBlock`,
     // you must update `CompactBlock::requires_scanning` to check for the emptiness of those fields, because
     // the client will skip processing any compact block that is marked as not requiring scanning.
@@ -39,8 +33,6 @@ impl Default for CompactBlock {
             nullifiers: Vec::new(),
             block_root: block::Finalized::default().root(),
             epoch_root: None,
-            quarantined: Quarantined::default(),
-            slashed: Vec::new(),
         }
     }
 }
@@ -50,8 +42,6 @@ impl CompactBlock {
     pub fn requires_scanning(&self) -> bool {
         !self.note_payloads.is_empty() // need to scan notes
             || !self.nullifiers.is_empty() // need to collect nullifiers
-            || !self.quarantined.is_empty() // need to scan quarantined notes
-            || !self.slashed.is_empty() // need to process slashing
     }
 }
 
@@ -70,12 +60,6 @@ impl From<CompactBlock> for pb::CompactBlock {
                 Some(cb.block_root.into())
             },
             epoch_root: cb.epoch_root.map(Into::into),
-            quarantined: if cb.quarantined.is_empty() {
-                None
-            } else {
-                Some(cb.quarantined.into())
-            },
-            slashed: cb.slashed.into_iter().map(Into::into).collect(),
         }
     }
 }
@@ -103,18 +87,6 @@ impl TryFrom<pb::CompactBlock> for CompactBlock {
                 // If the block root wasn't present, that means it's the default finalized block root
                 .unwrap_or_else(|| block::Finalized::default().root()),
             epoch_root: value.epoch_root.map(TryInto::try_into).transpose()?,
-            quarantined: value
-                .quarantined
-                .map(TryInto::try_into)
-                .transpose()?
-                // If the quarantined set wasn't present, that means it contained nothing, so make
-                // it the default, empty set
-                .unwrap_or_default(),
-            slashed: value
-                .slashed
-                .into_iter()
-                .map(IdentityKey::try_from)
-                .collect::<Result<Vec<_>>>()?,
         })
     }
 }
@@ -0,0 +1,50 @@
+use anyhow::{anyhow, Result};
+use penumbra_proto::{chain as pb, Protobuf};
+
+/// The location of a transaction in the chain.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct TransactionLocation {
+    /// The transaction's hash.
+    pub id: [u8; 32],
+    /// The height of the block containing the transaction.
+    pub height: u64,
+    /// The position of the transaction among the transactions executed in the
+    /// block.
+    pub index: u64,
+}
+
+impl Protobuf<pb::TransactionLocation> for TransactionLocation {}
+
+impl TryFrom<pb::TransactionLocation> for TransactionLocation {
+    type Error = anyhow::Error;
+    fn try_from(msg: pb::TransactionLocation) -> Result<Self> {
+        Ok(TransactionLocation {
+            id: msg
+                .id
+                .try_into()
+                .map_err(|_| anyhow!("expected 32-byte transaction hash"))?,
+            height: msg.height,
+            index: msg.index,
+        })
+    }
+}
+
+impl From<TransactionLocation> for pb::TransactionLocation {
+    fn from(location: TransactionLocation) -> Self {
+        pb::TransactionLocation {
+            id: location.id.to_vec(),
+            height: location.height,
+            index: location.index,
+        }
+    }
+}
+
+impl std::fmt::Debug for TransactionLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransactionLocation")
+            .field("id", &hex::encode(self.id))
+            .field("height", &self.height)
+            .field("index", &self.index)
+            .finish()
+    }
+}
@@ -40,7 +40,8 @@ impl App {
 
         let staking = Staking::new(state.clone()).await;
         let ibc = IBCComponent::new(state.clone()).await;
-        let shielded_pool = ShieldedPool::new(state.clone(), nct).await;
+        let shielded_pool =
+            ShieldedPool::new(state.clone(), storage.state().await.unwrap(), nct).await;
 
         Self {
             state,
@@ -60,9 +61,10 @@ impl App {
         // We want to store the latest NCT in a sidecar part of the storage,
         // rather than the Penumbra state, because the serialization format for
         // the NCT should not be consensus-critical.  We need to grab a copy of
-        // the entire NCT, so we can use it to re-instantiate the ShieldedPool.
-        let nct = self.shielded_pool.note_commitment_tree();
-        storage.put_nct(nct).await?;
+        // the entire NCT, so we can use it to re-instantiate the ShieldedPool,
+        // and only the updates made to it during this block are written out.
+        let mut nct = self.shielded_pool.note_commitment_tree().clone();
+        storage.put_nct(&mut nct).await?;
         // Commit the pending writes, clearing the state.
         let (root_hash, version) = self.state.write().await.commit(storage.clone()).await?;
         tracing::debug!(?root_hash, version, "finished committing state");
@@ -73,15 +75,40 @@ impl App {
         // Now re-instantiate all of the components so they all have the same shared state.
         self.staking = Staking::new(self.state.clone()).await;
         self.ibc = IBCComponent::new(self.state.clone()).await;
-        self.shielded_pool = ShieldedPool::new(self.state.clone(), nct.clone()).await;
+        self.shielded_pool =
+            ShieldedPool::new(self.state.clone(), storage.state().await?, nct).await;
 
         Ok((root_hash, version))
     }
 
+    /// Checks that the supply of every asset changed by the current block
+    /// moved by exactly the amount implied by the block's contents.
+    ///
+    /// This should be called after [`Component::init_chain`] or
+    /// [`Component::end_block`], and before [`App::commit`]: if it fails, the
+    /// state is inconsistent and must not be committed.
+    pub async fn check_supply_changes(&mut self, ctx: Context) -> Result<()> {
+        self.shielded_pool.check_supply_changes(ctx).await
+    }
+
     // TODO: should this just be returned by `commit`? both are called during every `EndBlock`
     pub async fn tm_validator_updates(&self) -> Result<Vec<ValidatorUpdate>> {
         self.staking.tm_validator_updates().await
     }
+
+    /// Performs all stateless checks on the given [`Transaction`] except for
+    /// signature verification.
+    ///
+    /// This should only be used when the transaction's signatures have already
+    /// been verified, e.g., in a batch with other transactions using
+    /// [`Transaction::verify_signatures_batch`].
+    #[instrument(skip(ctx, tx))]
+    pub fn check_tx_stateless_except_signatures(ctx: Context, tx: &Transaction) -> Result<()> {
+        Staking::check_tx_stateless(ctx.clone(), tx)?;
+        IBCComponent::check_tx_stateless(ctx.clone(), tx)?;
+        ShieldedPool::check_tx_stateless(ctx, tx)?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -126,10 +153,8 @@ impl Component for App {
 
     #[instrument(skip(ctx, tx))]
     fn check_tx_stateless(ctx: Context, tx: &Transaction) -> Result<()> {
-        Staking::check_tx_stateless(ctx.clone(), tx)?;
-        IBCComponent::check_tx_stateless(ctx.clone(), tx)?;
-        ShieldedPool::check_tx_stateless(ctx, tx)?;
-        Ok(())
+        tx.verify_signatures()?;
+        Self::check_tx_stateless_except_signatures(ctx, tx)
     }
 
     #[instrument(skip(self, ctx, tx))]
@@ -1,47 +1,56 @@
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, ops::RangeInclusive};
 
-use crate::{
-    stake::{validator, View as _},
-    Component, Context,
-};
+use crate::{Component, Context};
 use anyhow::{anyhow, Context as _, Result};
 use ark_ff::PrimeField;
 use async_trait::async_trait;
 use decaf377::{Fq, Fr};
 use penumbra_chain::{
-    genesis,
-    quarantined::{self, Slashed},
-    sync::CompactBlock,
-    Epoch, KnownAssets, NoteSource, View as _,
+    genesis, sync::CompactBlock, Epoch, KnownAssets, NoteSource, TransactionLocation, View as _,
 };
 use penumbra_crypto::{
     asset::{self, Asset, Denom},
-    ka, note, Address, IdentityKey, Note, NotePayload, Nullifier, One, Value,
+    ka, note, Address, DelegationToken, Note, NotePayload, Nullifier, One, Value,
     STAKING_TOKEN_ASSET_ID,
 };
 use penumbra_storage::{State, StateExt};
 use penumbra_tct as tct;
-use penumbra_transaction::{action::Undelegate, Action, Transaction};
+use penumbra_transaction::{Action, Transaction};
 use tendermint::abci;
 use tracing::instrument;
 
-use crate::shielded_pool::{event, state_key, CommissionAmounts};
+use crate::shielded_pool::{event, state_key, CommissionAmounts, SupplyChanges};
+use crate::stake::View as _;
 
 use super::Delible;
 
 pub struct ShieldedPool {
     state: State,
+    /// A read-only copy of the state as of the start of the block, used to find the supply of
+    /// each asset before the block changed it
+    block_start: State,
     note_commitment_tree: tct::Tree,
+    /// The note commitments added so far in the current block, which are inserted into the NCT
+    /// all at once when the block ends, so that they can be hashed in parallel
+    block_commitments: Vec<(tct::Witness, tct::Commitment)>,
     /// The in-progress CompactBlock representation of the ShieldedPool changes
     compact_block: CompactBlock,
+    /// The number of transactions executed so far in the current block
+    tx_index: u64,
+    /// The changes to the supply of each asset implied by the contents of the current block
+    supply_changes: SupplyChanges,
 }
 
 impl ShieldedPool {
-    #[instrument(name = "shielded_pool", skip(state, note_commitment_tree))]
-    pub async fn new(state: State, note_commitment_tree: tct::Tree) -> Self {
+    #[instrument(name = "shielded_pool", skip(state, block_start, note_commitment_tree))]
+    pub async fn new(state: State, block_start: State, note_commitment_tree: tct::Tree) -> Self {
         Self {
+            block_start,
             note_commitment_tree,
+            block_commitments: Vec::new(),
             compact_block: CompactBlock::default(),
+            tx_index: 0,
+            supply_changes: SupplyChanges::default(),
             state,
         }
     }
@@ -103,16 +112,10 @@ impl Component for ShieldedPool {
     #[instrument(name = "shielded_pool", skip(_ctx, tx))]
     fn check_tx_stateless(_ctx: Context, tx: &Transaction) -> Result<()> {
         // TODO: add a check that ephemeral_key is not identity to prevent scanning dos attack ?
-        let auth_hash = tx.transaction_body().auth_hash();
-
-        // 1. Check binding signature.
-        tx.binding_verification_key()
-            .verify(auth_hash.as_ref(), tx.binding_sig())
-            .context("binding signature failed to verify")?;
 
-        // 2. Check all spend auth signatures using provided spend auth keys
-        // and check all proofs verify. If any action does not verify, the entire
-        // transaction has failed.
+        // Spend authorization and binding signatures are verified by the
+        // `App`, in a batch, so we only check proofs here.  If any action does
+        // not verify, the entire transaction has failed.
         let mut spent_nullifiers = BTreeSet::<Nullifier>::new();
 
         for action in tx.transaction_body().actions {
@@ -132,12 +135,6 @@ impl Component for ShieldedPool {
                     }
                 }
                 Action::Spend(spend) => {
-                    spend
-                        .body
-                        .rk
-                        .verify(auth_hash.as_ref(), &spend.auth_sig)
-                        .context("spend auth signature failed to verify")?;
-
                     spend
                         .proof
                         .verify(
@@ -172,7 +169,6 @@ impl Component for ShieldedPool {
             self.state.check_nullifier_unspent(spent_nullifier).await?;
         }
 
-        // TODO: handle quarantine
         Ok(())
     }
 
@@ -180,38 +176,49 @@ impl Component for ShieldedPool {
     async fn execute_tx(&mut self, ctx: Context, tx: &Transaction) {
         let source = NoteSource::Transaction { id: tx.id() };
 
-        if let Some((epoch, identity_key)) = self.should_quarantine(tx).await {
-            for quarantined_output in tx.note_payloads() {
-                // Queue up scheduling this note to be unquarantined: the actual state-writing for
-                // all quarantined notes happens during end_block, to avoid state churn
-                self.schedule_note(epoch, identity_key, quarantined_output, source)
-                    .await;
-            }
-            for quarantined_spent_nullifier in tx.spent_nullifiers() {
-                self.quarantined_spend_nullifier(
-                    epoch,
-                    identity_key,
-                    quarantined_spent_nullifier,
-                    source,
-                )
+        // The transaction fee is burned.
+        let fee = tx.transaction_body.fee.0;
+        self.state
+            .update_token_supply(&STAKING_TOKEN_ASSET_ID, -(fee as i64))
+            .await
+            .unwrap();
+        self.supply_changes.burn(*STAKING_TOKEN_ASSET_ID, fee);
+
+        // Undelegation claims are processed by the staking component, but we
+        // record the supply changes they imply here, to check its work.
+        for claim in tx.undelegate_claims() {
+            self.supply_changes
+                .burn(claim.unbonding_token().id(), claim.unbonding_amount);
+            self.supply_changes
+                .issue(*STAKING_TOKEN_ASSET_ID, claim.unbonded_amount());
+        }
+
+        // Index the transaction, so that clients can find it later.
+        let location = TransactionLocation {
+            id: tx.id(),
+            height: self.height().await,
+            index: self.tx_index,
+        };
+        self.tx_index += 1;
+        self.state.set_transaction_location(location).await;
+
+        for compact_output in tx.note_payloads() {
+            self.add_note(compact_output, source).await;
+        }
+        for spent_nullifier in tx.spent_nullifiers() {
+            self.spend_nullifier(spent_nullifier, source).await;
+            self.state
+                .set_transaction_by_nullifier(&spent_nullifier, location)
                 .await;
-                ctx.record(event::quarantine_spend(quarantined_spent_nullifier));
-            }
-        } else {
-            for compact_output in tx.note_payloads() {
-                self.add_note(compact_output, source).await;
-            }
-            for spent_nullifier in tx.spent_nullifiers() {
-                self.spend_nullifier(spent_nullifier, source).await;
-                ctx.record(event::spend(spent_nullifier));
-            }
+            ctx.record(event::spend(spent_nullifier));
         }
     }
 
     #[instrument(name = "shielded_pool", skip(self, _ctx, _end_block))]
     async fn end_block(&mut self, _ctx: Context, _end_block: &abci::request::EndBlock) {
-        // Get the current block height
+        // Get the current block height and epoch
         let height = self.height().await;
+        let epoch = Epoch::from_height(height, self.state.get_epoch_duration().await.unwrap());
 
         // Set the height of the compact block
         self.compact_block.height = height;
@@ -227,8 +234,7 @@ impl Component for ShieldedPool {
         // TODO: should we calculate this here or include it directly within the PendingRewardNote
         // to prevent a potential mismatch between Staking and ShieldedPool?
         let source = NoteSource::FundingStreamReward {
-            epoch_index: Epoch::from_height(height, self.state.get_epoch_duration().await.unwrap())
-                .index,
+            epoch_index: epoch.index,
         };
 
         for note in notes.notes {
@@ -244,15 +250,11 @@ impl Component for ShieldedPool {
             .unwrap();
         }
 
-        // Schedule all unquarantining that was set up in this block
-        self.schedule_unquarantine().await;
-
-        // Handle any slashing that occurred in this block, unscheduling all affected notes and
-        // nullifiers from future unbonding
-        self.process_slashing().await;
-
-        // Process all unquarantining scheduled for this block
-        self.process_unquarantine().await;
+        // The staking component processes an epoch's delegation changes at its
+        // end, so record the supply changes they imply.
+        if epoch.is_epoch_end(height) {
+            self.record_delegation_supply_changes(epoch).await.unwrap();
+        }
 
         // Close the block in the NCT
         self.finish_nct_block().await;
@@ -265,10 +267,7 @@ impl ShieldedPool {
     #[instrument(
         skip(self, value, address, source),
         fields(
-            position = u64::from(self
-                .note_commitment_tree
-                .position()
-                .unwrap_or_else(|| u64::MAX.into())),
+            position = self.next_position().unwrap_or(u64::MAX),
         )
     )]
     async fn mint_note(
@@ -299,11 +298,9 @@ impl ShieldedPool {
         */
 
         // ... so just hash the current position instead.
-        let position: u64 = self
-            .note_commitment_tree
-            .position()
-            .expect("note commitment tree is not full")
-            .into();
+        let position = self
+            .next_position()
+            .expect("note commitment tree is not full");
 
         let blinding_factor = Fq::from_le_bytes_mod_order(
             blake2b_simd::Params::default()
@@ -333,6 +330,7 @@ impl ShieldedPool {
         self.state
             .update_token_supply(&value.asset_id, value.amount as i64)
             .await?;
+        self.supply_changes.issue(value.asset_id, value.amount);
         self.add_note(
             NotePayload {
                 note_commitment,
@@ -350,10 +348,9 @@ impl ShieldedPool {
     async fn add_note(&mut self, note_payload: NotePayload, source: NoteSource) {
         tracing::debug!("adding note");
 
-        // 1. Insert it into the NCT
-        self.note_commitment_tree
-            .insert(tct::Witness::Forget, note_payload.note_commitment)
-            .expect("inserting into the note commitment tree never fails");
+        // 1. Queue it for insertion into the NCT at the end of the block
+        self.block_commitments
+            .push((tct::Witness::Forget, note_payload.note_commitment));
 
         // 2. Record its source in the JMT
         self.state
@@ -364,27 +361,6 @@ impl ShieldedPool {
         self.compact_block.note_payloads.push(note_payload);
     }
 
-    #[instrument(skip(self, source, note_payload), fields(note_commitment = ?note_payload.note_commitment))]
-    async fn schedule_note(
-        &mut self,
-        epoch: u64,
-        identity_key: IdentityKey,
-        note_payload: NotePayload,
-        source: NoteSource,
-    ) {
-        tracing::debug!("scheduling note");
-
-        // 1. Record its source in the JMT
-        self.state
-            .set_note_source(&note_payload.note_commitment, source)
-            .await;
-
-        // 2. Schedule it in the compact block
-        self.compact_block
-            .quarantined
-            .schedule_note(epoch, identity_key, note_payload);
-    }
-
     #[instrument(skip(self, source))]
     async fn spend_nullifier(&mut self, nullifier: Nullifier, source: NoteSource) {
         tracing::debug!("marking as spent");
@@ -404,37 +380,11 @@ impl ShieldedPool {
         self.compact_block.nullifiers.push(nullifier);
     }
 
-    #[instrument(skip(self, source))]
-    async fn quarantined_spend_nullifier(
-        &mut self,
-        epoch: u64,
-        identity_key: IdentityKey,
-        nullifier: Nullifier,
-        source: NoteSource,
-    ) {
-        // We need to record the nullifier as spent under quarantine in the JMT (to prevent
-        // double spends), as well as in the CompactBlock (so clients can learn their note
-        // was provisionally spent, pending quarantine period).
-        tracing::debug!("marking as spent (currently quarantined)");
-        self.state
-            .put_domain(
-                state_key::quarantined_spent_nullifier_lookup(&nullifier),
-                // We don't use the value for validity checks, but writing the source
-                // here lets us find out what transaction spent the nullifier.
-                Delible::Present(source),
-            )
-            .await;
-        // Queue up scheduling this nullifier to be unquarantined: the actual state-writing
-        // for all quarantined nullifiers happens during end_block, to avoid state churn
-        self.compact_block
-            .quarantined
-            .schedule_nullifier(epoch, identity_key, nullifier);
-    }
-
     #[instrument(skip(self))]
     async fn write_compactblock_and_nct(&mut self) -> Result<()> {
         // Extract the compact block, resetting it
         let compact_block = std::mem::take(&mut self.compact_block);
+        self.tx_index = 0;
         let height = self.height().await;
 
         // Write the CompactBlock:
@@ -443,21 +393,35 @@ impl ShieldedPool {
         self.state
             .set_nct_anchor(height, self.note_commitment_tree.root())
             .await;
+        // and forget the anchor that just became too old to use.
+        self.state.prune_nct_anchor(height).await?;
 
         Ok(())
     }
 
+    /// The position in the NCT at which the next note of this block will be inserted, once the
+    /// block ends, or `None` if the NCT is full.
+    fn next_position(&self) -> Option<u64> {
+        self.note_commitment_tree
+            .position()
+            .map(|position| u64::from(position) + self.block_commitments.len() as u64)
+    }
+
     /// Finish the block in the NCT.
     #[instrument(skip(self))]
     async fn finish_nct_block(&mut self) {
         // Get the current block height
         let height = self.height().await;
 
-        // Close the block in the TCT
+        // Close the block in the TCT, hashing all of its commitments at once
+        let block = tct::builder::block::Finalized::from_commitments(std::mem::take(
+            &mut self.block_commitments,
+        ))
+        .expect("a block never contains more than 65,536 notes");
         let block_root = self
             .note_commitment_tree
-            .end_block()
-            .expect("ending a block in the note commitment tree can never fail");
+            .insert_block(block)
+            .expect("inserting a block into the note commitment tree can never fail");
 
         // Put the block root in the compact block
         self.compact_block.block_root = block_root;
@@ -485,144 +449,74 @@ impl ShieldedPool {
         }
     }
 
-    /// Get the current block height.
-    async fn height(&self) -> u64 {
-        self.state
-            .get_block_height()
-            .await
-            .expect("block height must be set")
-    }
-
-    /// Get the current epoch.
-    async fn epoch(&self) -> Epoch {
-        // Get the height
-        let height = self.height().await;
-
-        // Get the epoch duration
-        let epoch_duration = self.epoch_duration().await;
-
-        // The current epoch
-        Epoch::from_height(height, epoch_duration)
-    }
-
-    /// Get the epoch duration.
-    async fn epoch_duration(&self) -> u64 {
-        self.state
-            .get_epoch_duration()
-            .await
-            .expect("can get epoch duration")
-    }
-
-    /// Returns the epoch and identity key for quarantining a transaction, if it should be
-    /// quarantined, otherwise `None`.
-    async fn should_quarantine(&self, transaction: &Transaction) -> Option<(u64, IdentityKey)> {
-        let validator_identity =
-            transaction
-                .transaction_body
-                .actions
-                .iter()
-                .find_map(|action| {
-                    if let Action::Undelegate(Undelegate {
-                        validator_identity, ..
-                    }) = action
-                    {
-                        Some(validator_identity)
-                    } else {
-                        None
-                    }
-                })?;
-
-        let validator_bonding_state = self
-            .state
-            .validator_bonding_state(validator_identity)
-            .await
-            .expect("validator lookup in state succeeds")
-            .expect("validator is present in state");
-
-        let should_quarantine = match validator_bonding_state {
-            validator::BondingState::Unbonded => None,
-            validator::BondingState::Unbonding { unbonding_epoch } => {
-                Some((unbonding_epoch, *validator_identity))
+    /// Record the supply changes implied by the delegations and undelegations
+    /// processed at the end of `epoch`.
+    async fn record_delegation_supply_changes(&mut self, epoch: Epoch) -> Result<()> {
+        for height in epoch.start_height().value()..=epoch.end_height().value() {
+            let changes = self.state.delegation_changes(height.try_into()?).await?;
+            // Delegations burn staking tokens and mint delegation tokens...
+            for d in changes.delegations {
+                self.supply_changes
+                    .burn(*STAKING_TOKEN_ASSET_ID, d.unbonded_amount);
+                self.supply_changes.issue(
+                    DelegationToken::from(&d.validator_identity).id(),
+                    d.delegation_amount,
+                );
             }
-            validator::BondingState::Bonded => {
-                let unbonding_epochs = self
-                    .state
-                    .get_chain_params()
-                    .await
-                    .expect("can get chain params")
-                    .unbonding_epochs;
-                Some((
-                    self.epoch().await.index + unbonding_epochs,
-                    *validator_identity,
-                ))
+            // ... while undelegations burn delegation tokens and mint unbonding
+            // tokens.
+            for u in changes.undelegations {
+                self.supply_changes.burn(
+                    DelegationToken::from(&u.validator_identity).id(),
+                    u.delegation_amount,
+                );
+                self.supply_changes
+                    .issue(u.unbonding_token().id(), u.unbonded_amount);
             }
-        };
-
-        tracing::debug!(?should_quarantine, "should quarantine");
-
-        should_quarantine
+        }
+        Ok(())
     }
 
-    async fn schedule_unquarantine(&mut self) {
-        // First, we group all the scheduled quarantined notes by unquarantine epoch, in the process
-        // resetting the quarantine field of the component
-        for (&unbonding_epoch, scheduled) in self.compact_block.quarantined.iter() {
-            self.state
-                .schedule_unquarantine(unbonding_epoch, scheduled.clone())
-                .await
-                .expect("scheduling unquarantine must succeed");
+    /// Check that the supply of every asset changed during this block moved by
+    /// exactly the amount implied by the contents of the block, recording an
+    /// event for each change.
+    ///
+    /// This must be called after the block has ended, since the supply changes
+    /// implied by an epoch's delegations are only known then.
+    #[instrument(skip(self, ctx))]
+    pub async fn check_supply_changes(&mut self, ctx: Context) -> Result<()> {
+        for (asset_id, change) in std::mem::take(&mut self.supply_changes).0 {
+            let initial_supply = match self.block_start.token_supply(&asset_id).await {
+                Ok(supply) => supply.unwrap_or(0),
+                // The tree is empty before genesis, so every supply starts at 0.
+                Err(e) if e.downcast_ref::<jmt::MissingRootError>().is_some() => 0,
+                Err(e) => return Err(e),
+            };
+            let expected_supply = change.expected_supply(initial_supply)?;
+            let actual_supply = self.state.token_supply(&asset_id).await?.unwrap_or(0);
+            if expected_supply != actual_supply {
+                return Err(anyhow!(
+                    "supply of {} is {}, but the block changed it by {:?} from {}, which implies {}",
+                    asset_id,
+                    actual_supply,
+                    change,
+                    initial_supply,
+                    expected_supply
+                ));
+            }
+            tracing::debug!(?asset_id, ?change, "checked supply change");
+            ctx.record(event::supply_change(asset_id, change.issued, change.burned));
         }
-    }
 
-    // Process any slashing that occrred in this block.
-    async fn process_slashing(&mut self) {
-        self.compact_block.slashed.extend(
-            self.state
-                .unschedule_all_slashed()
-                .await
-                .expect("can unschedule slashed"),
-        );
+        Ok(())
     }
 
-    // Process any notes/nullifiers due to be unquarantined in this block, if it's an
-    // epoch-ending block
-    #[instrument(skip(self))]
-    async fn process_unquarantine(&mut self) {
-        let this_epoch = self.epoch().await;
-
-        if this_epoch.is_epoch_end(self.height().await) {
-            for (_, per_validator) in self
-                .state
-                .scheduled_to_apply(this_epoch.index)
-                .await
-                .expect("can look up quarantined for this epoch")
-            {
-                // For all the note payloads scheduled for unquarantine now, remove them from
-                // quarantine and add them to the proper notes for this block
-                for note_payload in per_validator.note_payloads {
-                    let note_source = self
-                        .state
-                        .note_source(&note_payload.note_commitment)
-                        .await
-                        .expect("can try to unquarantine note")
-                        .expect("note payload to unquarantine has source");
-                    tracing::debug!(?note_payload, "unquarantining note");
-                    self.add_note(note_payload, note_source).await;
-                }
-                // For all the nullifiers scheduled for unquarantine now, remove them from
-                // quarantine and add them to the proper nullifiers for this block
-                for nullifier in per_validator.nullifiers {
-                    let note_source = self
-                        .state
-                        .unquarantine_nullifier(nullifier)
-                        .await
-                        .expect("can try to unquarantine nullifier")
-                        .expect("nullifier to unquarantine has source");
-                    tracing::debug!(?nullifier, "unquarantining nullifier");
-                    self.spend_nullifier(nullifier, note_source).await;
-                }
-            }
-        }
+    /// Get the current block height.
+    async fn height(&self) -> u64 {
+        self.state
+            .get_block_height()
+            .await
+            .expect("block height must be set")
     }
 }
 
@@ -635,9 +529,10 @@ pub trait View: StateExt {
         self.get_proto(state_key::token_supply(asset_id)).await
     }
 
+    /// Changes the supply of the given asset.
     #[instrument(skip(self, change))]
     async fn update_token_supply(&self, asset_id: &asset::Id, change: i64) -> Result<()> {
-        let key = format!("shielded_pool/assets/{}/token_supply", asset_id).into();
+        let key = state_key::token_supply(asset_id);
         let current_supply = match self.get_proto(key).await {
             Ok(Some(value)) => value,
             Ok(None) => 0u64,
@@ -717,22 +612,6 @@ pub trait View: StateExt {
         .await
     }
 
-    // Returns whether the note was presently quarantined.
-    async fn roll_back_note(&self, commitment: &note::Commitment) -> Result<Option<NoteSource>> {
-        // Get the note source of the note (or empty vec if already applied or rolled back)
-        let source = self
-            .get_domain::<Delible<NoteSource>, _>(state_key::note_source(commitment))
-            .await?
-            .expect("can't roll back note that was never created")
-            .into();
-
-        // Delete the note from the set of all notes
-        self.put_domain(state_key::note_source(commitment), Delible::Deleted)
-            .await;
-
-        Ok(source)
-    }
-
     async fn note_source(&self, note_commitment: &note::Commitment) -> Result<Option<NoteSource>> {
         Ok(self
             .get_domain::<Delible<NoteSource>, _>(state_key::note_source(note_commitment))
@@ -741,6 +620,35 @@ pub trait View: StateExt {
             .into())
     }
 
+    async fn set_transaction_location(&self, location: TransactionLocation) {
+        self.put_domain(state_key::transaction_by_hash(&location.id), location)
+            .await
+    }
+
+    /// The location of the transaction with the given hash, if it was executed.
+    async fn transaction_by_hash(&self, id: &[u8; 32]) -> Result<Option<TransactionLocation>> {
+        self.get_domain(state_key::transaction_by_hash(id)).await
+    }
+
+    async fn set_transaction_by_nullifier(
+        &self,
+        nullifier: &Nullifier,
+        location: TransactionLocation,
+    ) {
+        self.put_domain(state_key::transaction_by_nullifier(nullifier), location)
+            .await
+    }
+
+    /// The location of the transaction that revealed the given nullifier, if
+    /// it has been spent.
+    async fn transaction_by_nullifier(
+        &self,
+        nullifier: &Nullifier,
+    ) -> Result<Option<TransactionLocation>> {
+        self.get_domain(state_key::transaction_by_nullifier(nullifier))
+            .await
+    }
+
     async fn set_compact_block(&self, compact_block: CompactBlock) {
         let height = compact_block.height;
         self.put_domain(state_key::compact_block(height), compact_block)
@@ -758,53 +666,81 @@ pub trait View: StateExt {
         self.put_domain(state_key::anchor_by_height(&height), nct_anchor)
             .await;
         // and as a key, so we can query for it.
-        self.put_proto(
+        self.put_domain(
             state_key::anchor_lookup(&nct_anchor),
-            // We don't use the value for validity checks, but writing the height
-            // here lets us find out what height the anchor was for.
-            height,
+            // Writing the height here lets us check the age of the anchor.
+            Delible::Present(height),
         )
         .await;
     }
 
-    /// Checks whether a claimed NCT anchor is a previous valid state root.
-    async fn check_claimed_anchor(&self, anchor: &tct::Root) -> Result<()> {
-        if let Some(anchor_height) = self
-            .get_proto::<u64>(state_key::anchor_lookup(anchor))
+    /// Prunes the anchor that falls out of the valid anchor window once the
+    /// block at `height` is finished.
+    ///
+    /// The JMT does not support deletion, so the anchor's lookup entry is
+    /// marked as deleted.  The anchor itself is kept in `anchor_by_height`, as
+    /// a record of the NCT's history.
+    async fn prune_nct_anchor(&self, height: u64) -> Result<()> {
+        let max_anchor_age = self.get_chain_params().await?.max_anchor_age;
+        let pruned_height = match height.checked_sub(max_anchor_age) {
+            Some(pruned_height) => pruned_height,
+            None => return Ok(()),
+        };
+        let anchor: tct::Root = self
+            .get_domain(state_key::anchor_by_height(&pruned_height))
             .await?
-        {
-            tracing::debug!(?anchor, ?anchor_height, "anchor is valid");
-            Ok(())
-        } else {
-            Err(anyhow!(
-                "provided anchor {} is not a valid NCT root",
-                anchor
-            ))
+            .ok_or_else(|| anyhow!("missing anchor for height {}", pruned_height))?;
+
+        // The same root may have been recorded again at a later height, in
+        // which case it's still valid.
+        let lookup: Option<u64> = self
+            .get_domain::<Delible<u64>, _>(state_key::anchor_lookup(&anchor))
+            .await?
+            .unwrap_or_default()
+            .into();
+        if lookup == Some(pruned_height) {
+            tracing::debug!(?pruned_height, ?anchor, "pruning anchor");
+            self.put_domain(state_key::anchor_lookup(&anchor), Delible::<u64>::Deleted)
+                .await;
         }
+
+        Ok(())
     }
 
-    // Returns the source if the nullifier was in quarantine already
-    #[instrument(skip(self))]
-    async fn unquarantine_nullifier(&self, nullifier: Nullifier) -> Result<Option<NoteSource>> {
-        tracing::debug!("removing quarantined nullifier");
-
-        // Get the note source of the nullifier (or empty vec if already applied or rolled back)
-        let source = self
-            .get_domain::<Delible<NoteSource>, _>(state_key::quarantined_spent_nullifier_lookup(
-                &nullifier,
-            ))
+    /// The range of heights whose anchors a transaction may use, if it is
+    /// included in the block following the current one.
+    async fn valid_anchor_heights(&self) -> Result<RangeInclusive<u64>> {
+        let height = self.get_block_height().await?;
+        let max_anchor_age = self.get_chain_params().await?.max_anchor_age;
+        Ok((height + 1).saturating_sub(max_anchor_age)..=height)
+    }
+
+    /// Checks whether a claimed NCT anchor is a previous valid state root,
+    /// recent enough to be used in the current block.
+    async fn check_claimed_anchor(&self, anchor: &tct::Root) -> Result<()> {
+        let anchor_height: Option<u64> = self
+            .get_domain::<Delible<u64>, _>(state_key::anchor_lookup(anchor))
             .await?
-            .expect("can't unquarantine nullifier that was never quarantined")
+            .unwrap_or_default()
             .into();
+        let anchor_height = anchor_height
+            .ok_or_else(|| anyhow!("provided anchor {} is not a valid NCT root", anchor))?;
 
-        // Delete the nullifier from the quarantine set
-        self.put_domain(
-            state_key::quarantined_spent_nullifier_lookup(&nullifier),
-            Delible::Deleted,
-        )
-        .await;
+        let height = self.get_block_height().await?;
+        let max_anchor_age = self.get_chain_params().await?.max_anchor_age;
+        let anchor_age = height.saturating_sub(anchor_height);
+        if anchor_age > max_anchor_age {
+            return Err(anyhow!(
+                "provided anchor {} is from height {}, {} blocks ago, but anchors may be at most {} blocks old",
+                anchor,
+                anchor_height,
+                anchor_age,
+                max_anchor_age
+            ));
+        }
 
-        Ok(source)
+        tracing::debug!(?anchor, ?anchor_height, "anchor is valid");
+        Ok(())
     }
 
     #[instrument(skip(self))]
@@ -820,87 +756,172 @@ pub trait View: StateExt {
             ));
         }
 
-        if let Some(source) = self
-            .get_domain::<Delible<NoteSource>, _>(state_key::quarantined_spent_nullifier_lookup(
-                &nullifier,
-            ))
-            .await?
-            .and_then(<Option<NoteSource>>::from)
-        {
-            return Err(anyhow!(
-                "nullifier {} was already spent in {:?} (currently quarantined)",
-                nullifier,
-                source,
-            ));
-        }
-
         Ok(())
     }
 
-    async fn scheduled_to_apply(&self, epoch: u64) -> Result<quarantined::Scheduled> {
-        Ok(self
-            .get_domain(state_key::scheduled_to_apply(epoch))
-            .await?
-            .unwrap_or_default())
+    // TODO: rename to something more generic ("minted notes"?) that can
+    // be used with IBC transfers, and fix up the path and proto
+
+    async fn commission_amounts(&self, height: u64) -> Result<Option<CommissionAmounts>> {
+        self.get_domain(state_key::commission_amounts(height)).await
     }
 
-    async fn schedule_unquarantine(
-        &self,
-        epoch: u64,
-        scheduled: quarantined::Scheduled,
-    ) -> Result<()> {
-        let mut updated_quarantined = self.scheduled_to_apply(epoch).await?;
-        updated_quarantined.extend(scheduled);
-        self.put_domain(state_key::scheduled_to_apply(epoch), updated_quarantined)
-            .await;
-        Ok(())
+    async fn set_commission_amounts(&self, height: u64, notes: CommissionAmounts) {
+        self.put_domain(state_key::commission_amounts(height), notes)
+            .await
     }
+}
 
-    // Unschedule the unquarantining of all notes and nullifiers for the given validator, in any
-    // epoch which could possibly still be unbonding
-    async fn unschedule_all_slashed(&self) -> Result<Vec<IdentityKey>> {
-        let height = self.get_block_height().await?;
-        let epoch_duration = self.get_epoch_duration().await?;
-        let this_epoch = Epoch::from_height(height, epoch_duration);
-        let unbonding_epochs = self.get_chain_params().await?.unbonding_epochs;
+impl<T: StateExt> View for T {}
 
-        let slashed: Slashed = self
-            .get_domain(state_key::slashed_validators(height))
-            .await?
-            .unwrap_or_default();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use penumbra_chain::params::ChainParams;
+    use penumbra_storage::Storage;
+    use tempfile::tempdir;
+
+    // After each block, a transaction may use an anchor from any of the last
+    // `max_anchor_age` blocks, and older anchors have been pruned.
+    #[tokio::test]
+    async fn anchors_are_valid_for_max_anchor_age_blocks() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("anchor-testing.db"))
+            .await
+            .unwrap();
+        let state = storage.state().await.unwrap();
+        state
+            .put_chain_params(ChainParams {
+                max_anchor_age: 3,
+                ..Default::default()
+            })
+            .await;
 
-        for epoch in this_epoch.index.saturating_sub(unbonding_epochs)..=this_epoch.index {
-            let mut updated_scheduled = self.scheduled_to_apply(epoch).await?;
-            for &identity_key in &slashed.validators {
-                let unbonding = updated_scheduled.unschedule_validator(identity_key);
-                // Now we also ought to remove these nullifiers and notes from quarantine without
-                // applying them:
-                for &nullifier in unbonding.nullifiers.iter() {
-                    self.unquarantine_nullifier(nullifier).await?;
-                }
-                for note_payload in unbonding.note_payloads.iter() {
-                    self.roll_back_note(&note_payload.note_commitment).await?;
-                }
+        let mut tree = tct::Tree::new();
+        let mut anchors = Vec::new();
+        for height in 0..10u64 {
+            tree.insert(tct::Witness::Forget, tct::Commitment(Fq::from(height + 1)))
+                .unwrap();
+            tree.end_block().unwrap();
+            state.put_block_height(height).await;
+            state.set_nct_anchor(height, tree.root()).await;
+            state.prune_nct_anchor(height).await.unwrap();
+            anchors.push(tree.root());
+
+            // The oldest valid anchor is `max_anchor_age - 1` blocks old...
+            let oldest = height.saturating_sub(2);
+            assert_eq!(state.valid_anchor_heights().await.unwrap(), oldest..=height);
+            for anchor_height in oldest..=height {
+                state
+                    .check_claimed_anchor(&anchors[anchor_height as usize])
+                    .await
+                    .unwrap();
+            }
+            // ... and every older one is rejected.
+            for anchor_height in 0..oldest {
+                assert!(state
+                    .check_claimed_anchor(&anchors[anchor_height as usize])
+                    .await
+                    .is_err());
             }
-            // We're removed all the scheduled notes and nullifiers for this epoch and identity key:
-            self.put_domain(state_key::scheduled_to_apply(epoch), updated_scheduled)
-                .await;
         }
 
-        Ok(slashed.validators)
+        // Pruned anchors are still recorded by height, as the NCT's history.
+        for (height, anchor) in anchors.iter().enumerate() {
+            let recorded: Option<tct::Root> = state
+                .get_domain(state_key::anchor_by_height(&(height as u64)))
+                .await
+                .unwrap();
+            assert_eq!(recorded, Some(*anchor));
+        }
     }
 
-    // TODO: rename to something more generic ("minted notes"?) that can
-    // be used with IBC transfers, and fix up the path and proto
+    #[tokio::test]
+    async fn supply_check_rejects_unexplained_changes() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("shielded-pool-testing.db"))
+            .await
+            .unwrap();
+        let mut pool = ShieldedPool::new(
+            storage.state().await.unwrap(),
+            storage.state().await.unwrap(),
+            tct::Tree::new(),
+        )
+        .await;
 
-    async fn commission_amounts(&self, height: u64) -> Result<Option<CommissionAmounts>> {
-        self.get_domain(state_key::commission_amounts(height)).await
+        // A change to the supply that the block accounts for is accepted...
+        pool.state
+            .update_token_supply(&STAKING_TOKEN_ASSET_ID, 100)
+            .await
+            .unwrap();
+        pool.supply_changes.issue(*STAKING_TOKEN_ASSET_ID, 100);
+        pool.check_supply_changes(Context::new()).await.unwrap();
+
+        // ... but one that it doesn't is not.
+        pool.state
+            .update_token_supply(&STAKING_TOKEN_ASSET_ID, 50)
+            .await
+            .unwrap();
+        pool.supply_changes.issue(*STAKING_TOKEN_ASSET_ID, 140);
+        assert!(pool.check_supply_changes(Context::new()).await.is_err());
     }
 
-    async fn set_commission_amounts(&self, height: u64, notes: CommissionAmounts) {
-        self.put_domain(state_key::commission_amounts(height), notes)
+    // Executed transactions can be looked up by their hash.
+    #[tokio::test]
+    async fn transactions_are_indexed_by_hash() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("tx-hash-testing.db"))
             .await
+            .unwrap();
+        let state = storage.state().await.unwrap();
+
+        let location = TransactionLocation {
+            id: [1; 32],
+            height: 5,
+            index: 2,
+        };
+        state.set_transaction_location(location).await;
+
+        assert_eq!(
+            state.transaction_by_hash(&[1; 32]).await.unwrap(),
+            Some(location)
+        );
+        assert_eq!(state.transaction_by_hash(&[2; 32]).await.unwrap(), None);
     }
-}
 
-impl<T: StateExt> View for T {}
+    // Spent nullifiers can be looked up to find the transaction that spent them.
+    #[tokio::test]
+    async fn transactions_are_indexed_by_nullifier() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("tx-nullifier-testing.db"))
+            .await
+            .unwrap();
+        let state = storage.state().await.unwrap();
+
+        let location = TransactionLocation {
+            id: [1; 32],
+            height: 5,
+            index: 2,
+        };
+        let spent = [Nullifier(Fq::from(1u64)), Nullifier(Fq::from(2u64))];
+        for nullifier in &spent {
+            state
+                .set_transaction_by_nullifier(nullifier, location)
+                .await;
+        }
+
+        for nullifier in &spent {
+            assert_eq!(
+                state.transaction_by_nullifier(nullifier).await.unwrap(),
+                Some(location)
+            );
+        }
+        assert_eq!(
+            state
+                .transaction_by_nullifier(&Nullifier(Fq::from(3u64)))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
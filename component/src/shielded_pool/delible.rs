@@ -58,3 +58,19 @@ impl From<Delible<NoteSource>> for pb::DelibleNoteSource {
         }
     }
 }
+
+impl Protobuf<pb::DelibleHeight> for Delible<u64> {}
+
+impl TryFrom<pb::DelibleHeight> for Delible<u64> {
+    type Error = anyhow::Error;
+
+    fn try_from(v: pb::DelibleHeight) -> Result<Self, Self::Error> {
+        Ok(v.height.into())
+    }
+}
+
+impl From<Delible<u64>> for pb::DelibleHeight {
+    fn from(v: Delible<u64>) -> Self {
+        pb::DelibleHeight { height: v.into() }
+    }
+}
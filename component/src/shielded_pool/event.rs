@@ -1,13 +1,17 @@
-use penumbra_crypto::Nullifier;
+use penumbra_crypto::{asset, Nullifier};
 use tendermint::abci::{Event, EventAttributeIndexExt};
 
 pub fn spend(nullifier: Nullifier) -> Event {
     Event::new("spend", vec![("nullifier", nullifier.to_string()).index()])
 }
 
-pub fn quarantine_spend(nullifier: Nullifier) -> Event {
+pub fn supply_change(asset_id: asset::Id, issued: u64, burned: u64) -> Event {
     Event::new(
-        "quarantine_spend",
-        vec![("nullifier", nullifier.to_string()).index()],
+        "supply_change",
+        vec![
+            ("asset_id", asset_id.to_string()).index(),
+            ("issued", issued.to_string()).no_index(),
+            ("burned", burned.to_string()).no_index(),
+        ],
     )
 }
@@ -3,6 +3,7 @@ mod component;
 mod delible;
 pub(crate) mod event;
 mod metrics;
+mod supply;
 
 pub mod state_key;
 
@@ -10,3 +11,4 @@ pub use self::metrics::register_metrics;
 pub use commission::{CommissionAmount, CommissionAmounts};
 pub use component::{ShieldedPool, View};
 pub use delible::Delible;
+pub use supply::{SupplyChange, SupplyChanges};
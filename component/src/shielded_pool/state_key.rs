@@ -34,16 +34,14 @@ pub fn spent_nullifier_lookup(nullifier: &Nullifier) -> KeyHash {
     format!("shielded_pool/spent_nullifiers/{}", nullifier).into()
 }
 
-pub fn commission_amounts(height: u64) -> KeyHash {
-    format!("staking/commission_amounts/{}", height).into()
+pub fn transaction_by_nullifier(nullifier: &Nullifier) -> KeyHash {
+    format!("shielded_pool/transaction_by_nullifier/{}", nullifier).into()
 }
 
-pub fn scheduled_to_apply(epoch: u64) -> KeyHash {
-    format!("shielded_pool/quarantined_to_apply_in_epoch/{}", epoch).into()
+pub fn transaction_by_hash(id: &[u8; 32]) -> KeyHash {
+    format!("shielded_pool/transaction_by_hash/{}", hex::encode(id)).into()
 }
 
-pub fn quarantined_spent_nullifier_lookup(nullifier: &Nullifier) -> KeyHash {
-    format!("shielded_pool/quarantined_spent_nullifiers/{}", nullifier).into()
+pub fn commission_amounts(height: u64) -> KeyHash {
+    format!("staking/commission_amounts/{}", height).into()
 }
-
-pub use crate::stake::state_key::slashed_validators;
@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use penumbra_crypto::asset;
+
+/// The changes made to the supply of each asset during a block.
+///
+/// These are derived from the contents of the block itself -- the notes it
+/// mints, the fees and undelegation claims in its transactions, and the
+/// delegation changes processed at the end of an epoch -- independently of the
+/// supply counters, so that at the end of the block we can check that the
+/// counters changed by exactly as much as the block implies.
+#[derive(Debug, Clone, Default)]
+pub struct SupplyChanges(pub BTreeMap<asset::Id, SupplyChange>);
+
+/// The change made to the supply of a single asset during a block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SupplyChange {
+    /// The total amount of the asset minted during the block.
+    pub issued: u64,
+    /// The total amount of the asset burned during the block.
+    pub burned: u64,
+}
+
+impl SupplyChanges {
+    /// Record that `amount` of `asset_id` was minted.
+    pub fn issue(&mut self, asset_id: asset::Id, amount: u64) {
+        self.0.entry(asset_id).or_default().issued += amount;
+    }
+
+    /// Record that `amount` of `asset_id` was burned.
+    pub fn burn(&mut self, asset_id: asset::Id, amount: u64) {
+        self.0.entry(asset_id).or_default().burned += amount;
+    }
+}
+
+impl SupplyChange {
+    /// The supply implied by this change to an asset whose supply at the start
+    /// of the block was `initial_supply`.
+    pub fn expected_supply(&self, initial_supply: u64) -> Result<u64> {
+        initial_supply
+            .checked_add(self.issued)
+            .and_then(|supply| supply.checked_sub(self.burned))
+            .ok_or_else(|| {
+                anyhow!(
+                    "change {:?} to initial supply {} is out of range",
+                    self,
+                    initial_supply
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::STAKING_TOKEN_ASSET_ID;
+
+    use super::*;
+
+    #[test]
+    fn accumulates_issuance_and_burns() {
+        let mut changes = SupplyChanges::default();
+        changes.issue(*STAKING_TOKEN_ASSET_ID, 50);
+        changes.burn(*STAKING_TOKEN_ASSET_ID, 30);
+        changes.issue(*STAKING_TOKEN_ASSET_ID, 10);
+
+        let change = changes.0[&*STAKING_TOKEN_ASSET_ID];
+        assert_eq!(
+            change,
+            SupplyChange {
+                issued: 60,
+                burned: 30,
+            }
+        );
+        assert_eq!(change.expected_supply(100).unwrap(), 130);
+        assert!(change.expected_supply(10).is_err());
+    }
+}
@@ -6,7 +6,6 @@ use crate::{Component, Context};
 use ::metrics::{decrement_gauge, gauge, increment_gauge};
 use anyhow::{anyhow, Context as _, Result};
 use async_trait::async_trait;
-use penumbra_chain::quarantined::Slashed;
 use penumbra_chain::{genesis, Epoch, View as _};
 use penumbra_crypto::{DelegationToken, IdentityKey, STAKING_TOKEN_ASSET_ID};
 use penumbra_proto::Protobuf;
@@ -28,6 +27,7 @@ use tracing::{instrument, Instrument};
 use crate::stake::{
     metrics,
     rate::{BaseRateData, RateData},
+    state_key,
     validator::{self, Validator},
     DelegationChanges, Uptime,
 };
@@ -58,6 +58,86 @@ impl Staking {
         }
     }
 
+    /// Checks that withdrawing `withdrawn` delegation tokens from the
+    /// validator's self-bond is covered by it, and leaves at least the minimum
+    /// self-delegation bonded, unless the validator will never be part of the
+    /// consensus set again.
+    async fn check_self_bond_withdrawal(
+        &self,
+        identity_key: &IdentityKey,
+        withdrawn: u64,
+        min_self_delegation: u64,
+    ) -> Result<()> {
+        let self_bond = self.state.self_bond(identity_key).await?;
+        let remaining = self_bond.checked_sub(withdrawn).ok_or_else(|| {
+            anyhow::anyhow!(
+                "cannot undelegate {} delegation tokens from the self-bond of {}, which holds only {}",
+                withdrawn,
+                identity_key,
+                self_bond,
+            )
+        })?;
+
+        let validator_state = self
+            .state
+            .validator_state(identity_key)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("missing state for validator"))?;
+        if matches!(
+            validator_state,
+            validator::State::Disabled | validator::State::Tombstoned
+        ) {
+            return Ok(());
+        }
+
+        let rate_data = self
+            .state
+            .next_validator_rate(identity_key)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("unknown validator identity {}", identity_key))?;
+        let remaining_unbonded = rate_data.unbonded_amount(remaining);
+        if remaining_unbonded < min_self_delegation {
+            return Err(anyhow::anyhow!(
+                "undelegation would leave {} self-delegated to {}, below the minimum of {}; disable the validator first to withdraw its self-bond",
+                remaining_unbonded,
+                identity_key,
+                min_self_delegation,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Add `amount` delegation tokens to the self-bond of the validator with the given identity key.
+    async fn add_to_self_bond(&mut self, identity_key: &IdentityKey, amount: u64) -> Result<()> {
+        let self_bond = self.state.self_bond(identity_key).await?;
+        let self_bond = self_bond
+            .checked_add(amount)
+            .ok_or_else(|| anyhow::anyhow!("self-bond of {} would overflow", identity_key))?;
+        self.state.set_self_bond(identity_key, self_bond).await;
+        Ok(())
+    }
+
+    /// Remove `amount` delegation tokens from the self-bond of the validator with the given
+    /// identity key.
+    async fn withdraw_from_self_bond(
+        &mut self,
+        identity_key: &IdentityKey,
+        amount: u64,
+    ) -> Result<()> {
+        let self_bond = self.state.self_bond(identity_key).await?;
+        let remaining = self_bond.checked_sub(amount).ok_or_else(|| {
+            anyhow::anyhow!(
+                "cannot withdraw {} delegation tokens from the self-bond of {}, which holds only {}",
+                amount,
+                identity_key,
+                self_bond,
+            )
+        })?;
+        self.state.set_self_bond(identity_key, remaining).await;
+        Ok(())
+    }
+
     /// Updates the state of the given validator, performing all necessary state transitions.
     ///
     /// This method errors on illegal state transitions; since execution must be infallible,
@@ -367,6 +447,12 @@ impl Staking {
 
             let funding_streams = validator.funding_streams;
 
+            // Record the commission in effect at the start of the new epoch,
+            // which bounds how far the validator can raise it during that epoch.
+            self.state
+                .set_epoch_start_commission(v, funding_streams.total_rate_bps())
+                .await;
+
             let next_rate =
                 current_rate.next(&next_base_rate, funding_streams.as_ref(), &validator_state);
             assert!(next_rate.epoch_index == epoch_to_end.index + 2);
@@ -390,15 +476,28 @@ impl Staking {
                 delegation_delta
             );
 
-            let abs_unbonded_amount =
-                current_rate.unbonded_amount(delegation_delta.abs() as u64) as i64;
-            let staking_delta = if delegation_delta >= 0 {
-                // Net delegation: subtract the unbonded amount from the staking token supply
-                -abs_unbonded_amount
-            } else {
-                // Net undelegation: add the unbonded amount to the staking token supply
-                abs_unbonded_amount
-            };
+            // Delegations subtract their unbonded amount from the staking
+            // token supply, while undelegations produce unbonding tokens,
+            // which are only converted back to staking tokens when claimed.
+            //
+            // This is the sum of the unbonded amounts the delegations actually
+            // spent, rather than the unbonded amount of the total delegation,
+            // which can differ from it by rounding.
+            let total_unbonded = delegations_by_validator
+                .get(&validator.identity_key)
+                .into_iter()
+                .flat_map(|ds| ds.iter().map(|d| d.unbonded_amount))
+                .sum::<u64>();
+            let staking_delta = -(total_unbonded as i64);
+            for u in undelegations_by_validator
+                .get(&validator.identity_key)
+                .into_iter()
+                .flatten()
+            {
+                self.state
+                    .update_token_supply(&u.unbonding_token().id(), u.unbonded_amount as i64)
+                    .await?;
+            }
 
             // update the delegation token supply in the JMT
             self.state
@@ -845,27 +944,13 @@ impl Component for Staking {
 
     #[instrument(name = "staking", skip(_ctx, tx))]
     fn check_tx_stateless(_ctx: Context, tx: &Transaction) -> Result<()> {
-        // Check that the transaction undelegates from at most one validator.
-        let undelegation_identities = tx
-            .undelegations()
-            .map(|u| u.validator_identity.clone())
-            .collect::<BTreeSet<_>>();
-
-        if undelegation_identities.len() > 1 {
-            return Err(anyhow!(
-                "transaction contains undelegations from multiple validators: {:?}",
-                undelegation_identities
-            ));
-        }
-
-        // We prohibit actions other than `Spend`, `Delegate`, `Output` and `Undelegate` in
-        // transactions that contain `Undelegate`, to avoid having to quarantine them.
-        if undelegation_identities.len() == 1 {
-            use Action::*;
-            for action in tx.transaction_body().actions {
-                if !matches!(action, Undelegate(_) | Delegate(_) | Spend(_) | Output(_)) {
-                    return Err(anyhow::anyhow!("transaction contains an undelegation, but also contains an action other than Spend, Delegate, Output or Undelegate"));
-                }
+        // Check that undelegation claims don't apply a penalty of more than 100%.
+        for claim in tx.undelegate_claims() {
+            if claim.penalty > 1_0000_0000 {
+                return Err(anyhow!(
+                    "undelegation claim has penalty {}, greater than 1_0000_0000 = 100%",
+                    claim.penalty
+                ));
             }
         }
 
@@ -904,17 +989,65 @@ impl Component for Staking {
 
     #[instrument(name = "staking", skip(self, _ctx, tx))]
     async fn check_tx_stateful(&self, _ctx: Context, tx: &Transaction) -> Result<()> {
+        let chain_params = self.state.get_chain_params().await?;
+        let cur_epoch = self.state.get_current_epoch().await?;
+
+        // Parse the validator definitions up front, so that delegations can
+        // reference a validator defined in the same transaction.
+        let mut definitions = Vec::new();
+        let mut new_validators = BTreeSet::new();
+        for v in tx.validator_definitions() {
+            let v = validator::Definition::try_from(v.clone())
+                .context("supplied proto is not a valid definition")?;
+            if self
+                .state
+                .validator(&v.validator.identity_key)
+                .await?
+                .is_none()
+            {
+                new_validators.insert(v.validator.identity_key.clone());
+            }
+            definitions.push(v);
+        }
+
         // Tally the delegations and undelegations
         let mut delegation_changes = BTreeMap::new();
+        let mut self_delegations = BTreeMap::<IdentityKey, u64>::new();
         for d in tx.delegations() {
-            let next_rate_data = self
-                .state
-                .next_validator_rate(&d.validator_identity)
-                .await?
-                .ok_or_else(|| {
-                    anyhow::anyhow!("unknown validator identity {}", d.validator_identity)
-                })?
-                .clone();
+            if d.self_bond && !authorized_by(tx, &d.validator_identity) {
+                return Err(anyhow::anyhow!(
+                    "self-bond delegation to {} must only spend notes controlled by the validator's identity key",
+                    d.validator_identity,
+                ));
+            }
+
+            let (next_rate_data, validator_state) =
+                if new_validators.contains(&d.validator_identity) {
+                    // A validator defined in this transaction will be added
+                    // with these initial rates, in the Inactive state.
+                    let next_rate_data = RateData {
+                        identity_key: d.validator_identity.clone(),
+                        epoch_index: cur_epoch.index + 1,
+                        validator_reward_rate: 0,
+                        validator_exchange_rate: 1_0000_0000, // 1 represented as 1e8
+                    };
+                    (next_rate_data, validator::State::Inactive)
+                } else {
+                    let next_rate_data = self
+                        .state
+                        .next_validator_rate(&d.validator_identity)
+                        .await?
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("unknown validator identity {}", d.validator_identity)
+                        })?
+                        .clone();
+                    let validator_state = self
+                        .state
+                        .validator_state(&d.validator_identity)
+                        .await?
+                        .ok_or_else(|| anyhow::anyhow!("missing state for validator"))?;
+                    (next_rate_data, validator_state)
+                };
 
             // Check whether the epoch is correct first, to give a more helpful
             // error message if it's wrong.
@@ -927,12 +1060,6 @@ impl Component for Staking {
             }
 
             // Check whether the delegation is allowed
-            let validator_state = self
-                .state
-                .validator_state(&d.validator_identity)
-                .await?
-                .ok_or_else(|| anyhow::anyhow!("missing state for validator"))?;
-
             use validator::State::*;
             if !matches!(validator_state, Inactive | Active) {
                 return Err(anyhow::anyhow!(
@@ -963,6 +1090,11 @@ impl Component for Staking {
                 *delegation_changes
                     .entry(d.validator_identity.clone())
                     .or_insert(0) += i64::try_from(d.delegation_amount).unwrap();
+                if d.self_bond {
+                    *self_delegations
+                        .entry(d.validator_identity.clone())
+                        .or_insert(0) += d.unbonded_amount;
+                }
             } else {
                 return Err(anyhow::anyhow!(
                     "given {} unbonded stake, expected {} delegation tokens but description produces {}",
@@ -972,7 +1104,15 @@ impl Component for Staking {
                 ));
             }
         }
+        let mut self_undelegations = BTreeMap::<IdentityKey, u64>::new();
         for u in tx.undelegations() {
+            if u.self_bond && !authorized_by(tx, &u.validator_identity) {
+                return Err(anyhow::anyhow!(
+                    "self-bond undelegation from {} must only spend notes controlled by the validator's identity key",
+                    u.validator_identity,
+                ));
+            }
+
             let rate_data = self
                 .state
                 .next_validator_rate(&u.validator_identity)
@@ -1016,6 +1156,11 @@ impl Component for Staking {
                 *delegation_changes
                     .entry(u.validator_identity.clone())
                     .or_insert(0) -= i64::try_from(u.delegation_amount).unwrap();
+                if u.self_bond {
+                    *self_undelegations
+                        .entry(u.validator_identity.clone())
+                        .or_insert(0) += u.delegation_amount;
+                }
             } else {
                 return Err(anyhow::anyhow!(
                     "given {} delegation tokens, expected {} unbonded stake but description produces {}",
@@ -1026,11 +1171,61 @@ impl Component for Staking {
             }
         }
 
-        // Check that the sequence numbers of updated validators are correct.
-        for v in tx.validator_definitions() {
-            let v = validator::Definition::try_from(v.clone())
-                .context("supplied proto is not a valid definition")?;
-            let existing_v = self.state.validator(&v.validator.identity_key).await?;
+        // Check that withdrawals from self-bonds leave them sufficiently bonded.
+        for (identity_key, withdrawn) in self_undelegations {
+            self.check_self_bond_withdrawal(
+                &identity_key,
+                withdrawn,
+                chain_params.min_self_delegation,
+            )
+            .await?;
+        }
+
+        // Check that undelegation claims are for completed unbonding periods,
+        // and apply the correct slashing penalty.
+        for claim in tx.undelegate_claims() {
+            let unbonding_end_epoch = self
+                .state
+                .unbonding_end_epoch_for(&claim.validator_identity, claim.start_epoch_index)
+                .await?;
+            if cur_epoch.index < unbonding_end_epoch {
+                return Err(anyhow::anyhow!(
+                    "undelegation claim for unbonding that started in epoch {} is not claimable until epoch {}, but the current epoch is {}",
+                    claim.start_epoch_index,
+                    unbonding_end_epoch,
+                    cur_epoch.index,
+                ));
+            }
+
+            let expected_penalty = self
+                .state
+                .unbonding_penalty(&claim.validator_identity, claim.start_epoch_index)
+                .await?;
+            if claim.penalty != expected_penalty {
+                return Err(anyhow::anyhow!(
+                    "undelegation claim applies penalty {} but the penalty for validator {} since epoch {} is {}",
+                    claim.penalty,
+                    claim.validator_identity,
+                    claim.start_epoch_index,
+                    expected_penalty,
+                ));
+            }
+        }
+
+        // Check that the sequence numbers and commission of updated validators
+        // are correct, and that new validators are sufficiently self-bonded.
+        for v in definitions {
+            let identity_key = &v.validator.identity_key;
+            let commission_bps = v.validator.funding_streams.total_rate_bps();
+            if commission_bps > chain_params.max_commission_bps {
+                return Err(anyhow::anyhow!(
+                    "validator commission of {}bps exceeds the maximum of {}bps",
+                    commission_bps,
+                    chain_params.max_commission_bps
+                ));
+            }
+
+            let existing_v = self.state.validator(identity_key).await?;
 
             if let Some(existing_v) = existing_v {
                 // This is an existing validator definition. Ensure that the highest
@@ -1042,9 +1237,36 @@ impl Component for Staking {
                         current_seq
                     ));
                 }
+
+                // Limit how far the commission can rise within a single epoch,
+                // relative to the commission in effect when the epoch started,
+                // so that repeated updates can't be used to exceed the limit.
+                let epoch_start_commission =
+                    match self.state.epoch_start_commission(identity_key).await? {
+                        Some(commission) => commission,
+                        None => existing_v.funding_streams.total_rate_bps(),
+                    };
+                let max_commission =
+                    epoch_start_commission + chain_params.max_commission_increase_bps_per_epoch;
+                if commission_bps > max_commission {
+                    return Err(anyhow::anyhow!(
+                        "validator commission of {}bps exceeds the maximum of {}bps for this epoch (started at {}bps)",
+                        commission_bps,
+                        max_commission,
+                        epoch_start_commission,
+                    ));
+                }
             } else {
-                // This is a new validator definition.
-                continue;
+                // This is a new validator definition, which must be bonded
+                // with a self-delegation made in the same transaction.
+                let self_delegation = self_delegations.get(identity_key).copied().unwrap_or(0);
+                if self_delegation < chain_params.min_self_delegation {
+                    return Err(anyhow::anyhow!(
+                        "new validator must self-bond at least {} in the defining transaction, but bonded {}",
+                        chain_params.min_self_delegation,
+                        self_delegation,
+                    ));
+                }
             }
 
             // the validator definition has now passed all verification checks
@@ -1061,10 +1283,43 @@ impl Component for Staking {
                 Action::Delegate(d) => {
                     tracing::debug!(?d, "queuing delegation for next epoch");
                     self.delegation_changes.delegations.push(d.clone());
+                    if d.self_bond {
+                        self.add_to_self_bond(&d.validator_identity, d.delegation_amount)
+                            .await
+                            .expect("can update self-bond");
+                    }
                 }
                 Action::Undelegate(u) => {
                     tracing::debug!(?u, "queuing undelegation for next epoch");
                     self.delegation_changes.undelegations.push(u.clone());
+                    if u.self_bond {
+                        // check_tx already rejected withdrawals exceeding the self-bond
+                        self.withdraw_from_self_bond(&u.validator_identity, u.delegation_amount)
+                            .await
+                            .expect("self-bond withdrawal was checked in check_tx");
+                    }
+                    // Register the unbonding token produced by the undelegation,
+                    // so that clients can learn its denomination.
+                    self.state
+                        .register_denom(&u.unbonding_token().denom())
+                        .await
+                        .unwrap();
+                }
+                Action::UndelegateClaim(c) => {
+                    tracing::debug!(?c, "processing undelegation claim");
+                    // The unbonding tokens are consumed, and the penalized
+                    // amount of staking tokens is produced.
+                    self.state
+                        .update_token_supply(
+                            &c.unbonding_token().id(),
+                            -(c.unbonding_amount as i64),
+                        )
+                        .await
+                        .unwrap();
+                    self.state
+                        .update_token_supply(&STAKING_TOKEN_ASSET_ID, c.unbonded_amount() as i64)
+                        .await
+                        .unwrap();
                 }
                 _ => {}
             }
@@ -1134,6 +1389,18 @@ impl Component for Staking {
     }
 }
 
+/// Whether every note spent by `tx` is controlled by the validator's identity
+/// key, signed without randomization, so that (un)delegations to a self-bond
+/// are funded and authorized by the validator itself.
+///
+/// Since the unrandomized `rk` of each such spend is the identity key itself,
+/// these spends are publicly linkable to the validator, unlike every other
+/// spend.
+fn authorized_by(tx: &Transaction, identity_key: &IdentityKey) -> bool {
+    let mut spends = tx.spends().peekable();
+    spends.peek().is_some() && spends.all(|spend| spend.body.rk == identity_key.0)
+}
+
 /// Extension trait providing read/write access to staking data.
 ///
 /// TODO: should this be split into Read and Write traits?
@@ -1154,11 +1421,24 @@ pub trait View: StateExt {
     #[instrument(skip(self))]
     async fn set_base_rates(&self, current: BaseRateData, next: BaseRateData) {
         tracing::debug!("setting base rates");
+        // Also record the current base rate under its epoch index, so that the
+        // history of base rates remains queryable after it's overwritten.
+        self.put_domain(
+            state_key::base_rate_by_epoch(current.epoch_index),
+            current.clone(),
+        )
+        .await;
         self.put_domain("staking/base_rate/current".into(), current)
             .await;
         self.put_domain("staking/base_rate/next".into(), next).await;
     }
 
+    /// Returns the base rate data that was in effect during the given epoch.
+    async fn base_rate_by_epoch(&self, epoch_index: u64) -> Result<Option<BaseRateData>> {
+        self.get_domain(state_key::base_rate_by_epoch(epoch_index))
+            .await
+    }
+
     async fn current_validator_rate(&self, identity_key: &IdentityKey) -> Result<Option<RateData>> {
         self.get_domain(format!("staking/validators/{}/rate/current", identity_key).into())
             .await
@@ -1169,6 +1449,20 @@ pub trait View: StateExt {
             .await
     }
 
+    /// Returns the rate data that was in effect for the given validator during
+    /// the given epoch.
+    async fn validator_rate_by_epoch(
+        &self,
+        identity_key: &IdentityKey,
+        epoch_index: u64,
+    ) -> Result<Option<RateData>> {
+        self.get_domain(state_key::validator_rate_by_epoch(
+            identity_key,
+            epoch_index,
+        ))
+        .await
+    }
+
     #[instrument(skip(self))]
     async fn set_validator_power(
         &self,
@@ -1203,6 +1497,15 @@ pub trait View: StateExt {
         next_rates: RateData,
     ) {
         tracing::debug!("setting validator rates");
+        // Also record the current rate under its epoch index, so that the
+        // history of exchange rates remains queryable after it's overwritten.
+        // If a slashing penalty is applied mid-epoch, this overwrites the
+        // recorded rate for that epoch with the penalized one.
+        self.put_domain(
+            state_key::validator_rate_by_epoch(identity_key, current_rates.epoch_index),
+            current_rates.clone(),
+        )
+        .await;
         self.put_domain(
             format!("staking/validators/{}/rate/current", identity_key).into(),
             current_rates,
@@ -1215,6 +1518,39 @@ pub trait View: StateExt {
         .await;
     }
 
+    /// Returns the validator's total commission, in basis points, as of the
+    /// start of the current epoch.
+    async fn epoch_start_commission(&self, identity_key: &IdentityKey) -> Result<Option<u64>> {
+        self.get_proto(state_key::epoch_start_commission(identity_key))
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn set_epoch_start_commission(&self, identity_key: &IdentityKey, commission_bps: u64) {
+        tracing::debug!("setting epoch start commission");
+        self.put_proto(
+            state_key::epoch_start_commission(identity_key),
+            commission_bps,
+        )
+        .await
+    }
+
+    /// The number of delegation tokens held by the chain as the validator's
+    /// self-bond.
+    async fn self_bond(&self, identity_key: &IdentityKey) -> Result<u64> {
+        Ok(self
+            .get_proto(state_key::self_bond(identity_key))
+            .await?
+            .unwrap_or(0))
+    }
+
+    #[instrument(skip(self))]
+    async fn set_self_bond(&self, identity_key: &IdentityKey, delegation_amount: u64) {
+        tracing::debug!("setting self-bond");
+        self.put_proto(state_key::self_bond(identity_key), delegation_amount)
+            .await
+    }
+
     async fn validator(&self, identity_key: &IdentityKey) -> Result<Option<Validator>> {
         self.get_domain(format!("staking/validators/{}", identity_key).into())
             .await
@@ -1262,22 +1598,100 @@ pub trait View: StateExt {
         self.set_validator_rates(identity_key, cur_rate, next_rate)
             .await;
 
-        // Whenever a slashing penalty is applied, we need to record that the validator was slashedt
-        // in this block so that the shielded pool can unschedule unquarantines
-        self.record_slashing(identity_key.clone()).await?;
+        // Whenever a slashing penalty is applied, we need to record it for
+        // the current epoch, so that it can be applied to undelegation claims
+        // for unbonding periods that include this epoch.
+        self.record_slashing_penalty(identity_key, slashing_penalty_bps)
+            .await?;
 
         Ok(())
     }
 
-    async fn record_slashing(&self, identity_key: IdentityKey) -> Result<()> {
-        let height = self.get_block_height().await?;
-        let key = super::state_key::slashed_validators(height);
-        let mut slashed: Slashed = self.get_domain(key).await?.unwrap_or_default();
-        slashed.validators.push(identity_key);
-        self.put_domain(key, slashed).await;
+    async fn record_slashing_penalty(
+        &self,
+        identity_key: &IdentityKey,
+        slashing_penalty_bps: u64,
+    ) -> Result<()> {
+        let epoch_index = self.get_current_epoch().await?.index;
+        let current_penalty = self
+            .penalty_in_epoch(identity_key, epoch_index)
+            .await?
+            .unwrap_or(0);
+        // The penalty is recorded in bps of bps, so convert from bps.
+        let penalty = compound_penalties(current_penalty, slashing_penalty_bps * 1_0000);
+        self.put_proto(
+            state_key::penalty_in_epoch(identity_key, epoch_index),
+            penalty,
+        )
+        .await;
         Ok(())
     }
 
+    /// Returns the slashing penalty applied to the validator in the given
+    /// epoch, expressed in basis points of basis points.
+    async fn penalty_in_epoch(
+        &self,
+        identity_key: &IdentityKey,
+        epoch_index: u64,
+    ) -> Result<Option<u64>> {
+        self.get_proto(state_key::penalty_in_epoch(identity_key, epoch_index))
+            .await
+    }
+
+    /// Returns the index of the epoch at which unbonding tokens for the given
+    /// validator and unbonding start epoch become claimable.
+    ///
+    /// This is the end of the unbonding period, unless the validator finishes
+    /// unbonding sooner, in which case the tokens are no longer at risk and
+    /// can be claimed as soon as the validator is unbonded.
+    async fn unbonding_end_epoch_for(
+        &self,
+        identity_key: &IdentityKey,
+        start_epoch_index: u64,
+    ) -> Result<u64> {
+        let unbonding_epochs = self.get_chain_params().await?.unbonding_epochs;
+        let default_end_epoch = start_epoch_index + unbonding_epochs;
+
+        let end_epoch = match self.validator_bonding_state(identity_key).await? {
+            Some(validator::BondingState::Unbonded) => {
+                std::cmp::min(default_end_epoch, self.get_current_epoch().await?.index)
+            }
+            Some(validator::BondingState::Unbonding { unbonding_epoch }) => {
+                std::cmp::min(default_end_epoch, unbonding_epoch)
+            }
+            Some(validator::BondingState::Bonded) | None => default_end_epoch,
+        };
+
+        Ok(end_epoch)
+    }
+
+    /// Returns the compounded slashing penalty applied to the validator during
+    /// the unbonding period that began in the given epoch, up to the current
+    /// epoch, expressed in basis points of basis points.
+    async fn unbonding_penalty(
+        &self,
+        identity_key: &IdentityKey,
+        start_epoch_index: u64,
+    ) -> Result<u64> {
+        let current_epoch_index = self.get_current_epoch().await?.index;
+        let end_epoch_index = self
+            .unbonding_end_epoch_for(identity_key, start_epoch_index)
+            .await?;
+
+        // Penalties recorded in the epoch in which the tokens become claimable
+        // (or later) no longer apply.
+        let mut penalty = 0;
+        for epoch_index in
+            start_epoch_index..std::cmp::min(end_epoch_index, current_epoch_index + 1)
+        {
+            if let Some(epoch_penalty) = self.penalty_in_epoch(identity_key, epoch_index).await? {
+                penalty = compound_penalties(penalty, epoch_penalty);
+            }
+        }
+
+        Ok(penalty)
+    }
+
     // Used for adding a new validator to the JMT. May be either
     // Active (a genesis validator) on Inactive (a validator added
     // post-genesis).
@@ -1293,6 +1707,8 @@ pub trait View: StateExt {
         tracing::debug!(?validator);
         let id = validator.identity_key.clone();
 
+        self.set_epoch_start_commission(&id, validator.funding_streams.total_rate_bps())
+            .await;
         self.put_domain(format!("staking/validators/{}", id).into(), validator)
             .await;
         self.register_denom(&DelegationToken::from(&id).denom())
@@ -1452,3 +1868,154 @@ pub trait View: StateExt {
 }
 
 impl<T: StateExt + Send + Sync> View for T {}
+
+/// Compounds two penalties expressed in basis points of basis points, so that
+/// applying the result is equivalent to applying each penalty in turn.
+fn compound_penalties(a: u64, b: u64) -> u64 {
+    const ONE: u128 = 1_0000_0000;
+    let remaining = (ONE - a as u128) * (ONE - b as u128) / ONE;
+    (ONE - remaining) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use penumbra_chain::params::ChainParams;
+    use penumbra_crypto::rdsa::{SigningKey, SpendAuth};
+    use penumbra_storage::Storage;
+    use penumbra_transaction::action::UndelegateClaim;
+    use rand_core::OsRng;
+    use tempfile::tempdir;
+
+    // A validator can't withdraw its self-bond below the minimum while it can
+    // still rejoin the consensus set.
+    #[tokio::test]
+    async fn self_bond_withdrawal_respects_minimum() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("stake-testing.db"))
+            .await
+            .unwrap();
+        let state = storage.state().await.unwrap();
+
+        let identity_key = IdentityKey(SigningKey::<SpendAuth>::new(OsRng).into());
+        let rate = |epoch_index| RateData {
+            identity_key: identity_key.clone(),
+            epoch_index,
+            validator_reward_rate: 0,
+            validator_exchange_rate: 1_0000_0000,
+        };
+        state
+            .set_validator_rates(&identity_key, rate(0), rate(1))
+            .await;
+        state
+            .put_domain(
+                format!("staking/validators/{}/state", identity_key).into(),
+                validator::State::Active,
+            )
+            .await;
+        state.set_self_bond(&identity_key, 1500).await;
+
+        let staking = Staking::new(state.clone()).await;
+        staking
+            .check_self_bond_withdrawal(&identity_key, 500, 1000)
+            .await
+            .unwrap();
+        assert!(staking
+            .check_self_bond_withdrawal(&identity_key, 501, 1000)
+            .await
+            .is_err());
+        assert!(staking
+            .check_self_bond_withdrawal(&identity_key, 1501, 1000)
+            .await
+            .is_err());
+
+        // Once the validator is disabled, the whole self-bond can be withdrawn.
+        state
+            .put_domain(
+                format!("staking/validators/{}/state", identity_key).into(),
+                validator::State::Disabled,
+            )
+            .await;
+        staking
+            .check_self_bond_withdrawal(&identity_key, 1500, 1000)
+            .await
+            .unwrap();
+    }
+
+    // Undelegated stake is at risk until the end of the epoch the undelegation
+    // is included in, so a slash later in that epoch must apply to the claim.
+    #[tokio::test]
+    async fn slash_in_undelegation_epoch_applies_to_claim() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("stake-testing.db"))
+            .await
+            .unwrap();
+        let state = storage.state().await.unwrap();
+
+        state
+            .put_chain_params(ChainParams {
+                epoch_duration: 10,
+                unbonding_epochs: 2,
+                ..Default::default()
+            })
+            .await;
+        // Block 15 is in epoch 1
+        state.put_block_height(15).await;
+
+        let identity_key = IdentityKey(SigningKey::<SpendAuth>::new(OsRng).into());
+        let rate = |epoch_index| RateData {
+            identity_key: identity_key.clone(),
+            epoch_index,
+            validator_reward_rate: 0,
+            validator_exchange_rate: 1_0000_0000,
+        };
+        state
+            .set_validator_rates(&identity_key, rate(1), rate(2))
+            .await;
+
+        // Undelegations are prepared against the next epoch's rate
+        let next_rate = state
+            .next_validator_rate(&identity_key)
+            .await
+            .unwrap()
+            .unwrap();
+        let undelegate = Undelegate {
+            validator_identity: identity_key.clone(),
+            epoch_index: next_rate.epoch_index,
+            unbonded_amount: next_rate.unbonded_amount(1000),
+            delegation_amount: 1000,
+            self_bond: false,
+        };
+
+        // Slash the validator by 10% later in the same epoch
+        state.put_block_height(18).await;
+        state
+            .apply_slashing_penalty(&identity_key, 1000)
+            .await
+            .unwrap();
+
+        // Once the unbonding period is over, the claim must carry the penalty
+        let start_epoch_index = undelegate.unbonding_token().start_epoch_index();
+        let end_epoch_index = state
+            .unbonding_end_epoch_for(&identity_key, start_epoch_index)
+            .await
+            .unwrap();
+        state.put_block_height(end_epoch_index * 10).await;
+        let penalty = state
+            .unbonding_penalty(&identity_key, start_epoch_index)
+            .await
+            .unwrap();
+        let claim = UndelegateClaim {
+            validator_identity: identity_key,
+            start_epoch_index,
+            penalty,
+            unbonding_amount: undelegate.unbonded_amount,
+        };
+
+        assert_eq!(
+            claim.unbonding_token().id(),
+            undelegate.unbonding_token().id()
+        );
+        assert_eq!(claim.unbonded_amount(), 900);
+    }
+}
@@ -145,6 +145,7 @@ impl RateData {
             epoch_index: self.epoch_index,
             unbonded_amount,
             validator_identity: self.identity_key.clone(),
+            self_bond: false,
         }
     }
 
@@ -156,6 +157,7 @@ impl RateData {
             delegation_amount,
             unbonded_amount: self.unbonded_amount(delegation_amount),
             validator_identity: self.identity_key.clone(),
+            self_bond: false,
         }
     }
 }
@@ -1,5 +1,26 @@
 use jmt::KeyHash;
+use penumbra_crypto::IdentityKey;
 
-pub fn slashed_validators(height: u64) -> KeyHash {
-    format!("staking/slashed_validators/{}", height).into()
+pub fn penalty_in_epoch(identity_key: &IdentityKey, epoch_index: u64) -> KeyHash {
+    format!("staking/penalty/{}/{}", identity_key, epoch_index).into()
+}
+
+pub fn validator_rate_by_epoch(identity_key: &IdentityKey, epoch_index: u64) -> KeyHash {
+    format!(
+        "staking/validators/{}/rate/epoch/{}",
+        identity_key, epoch_index
+    )
+    .into()
+}
+
+pub fn base_rate_by_epoch(epoch_index: u64) -> KeyHash {
+    format!("staking/base_rate/epoch/{}", epoch_index).into()
+}
+
+pub fn self_bond(identity_key: &IdentityKey) -> KeyHash {
+    format!("staking/validators/{}/self_bond", identity_key).into()
+}
+
+pub fn epoch_start_commission(identity_key: &IdentityKey) -> KeyHash {
+    format!("staking/validators/{}/epoch_start_commission", identity_key).into()
 }
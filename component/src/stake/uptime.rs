@@ -81,6 +81,25 @@ impl Uptime {
     pub fn num_missed_blocks(&self) -> usize {
         self.signatures.iter_zeros().len()
     }
+
+    /// The height of the most recent block recorded in the window.
+    pub fn as_of_block_height(&self) -> u64 {
+        self.as_of_block_height
+    }
+
+    /// The number of blocks in the window.
+    pub fn window_len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Returns the heights of the blocks in the window that the validator
+    /// missed signing, in ascending order.
+    pub fn missed_block_heights(&self) -> impl Iterator<Item = u64> + '_ {
+        let len = self.signatures.len() as u64;
+        let start = (self.as_of_block_height + 1).saturating_sub(len);
+        (start..=self.as_of_block_height)
+            .filter(move |height| !self.signatures[(height % len) as usize])
+    }
 }
 
 impl Protobuf<pb::Uptime> for Uptime {}
@@ -135,6 +154,18 @@ mod tests {
         }
         assert_eq!(uptime.num_missed_blocks(), 0);
 
+        // Check that the missed blocks are reported at the right heights
+        uptime
+            .mark_height_as_signed(2 * window as u64 + 1, false)
+            .unwrap();
+        uptime
+            .mark_height_as_signed(2 * window as u64 + 2, true)
+            .unwrap();
+        assert_eq!(
+            uptime.missed_block_heights().collect::<Vec<_>>(),
+            vec![2 * window as u64 + 1]
+        );
+
         // Finally, check that the sanity-checking works
         assert!(uptime.mark_height_as_signed(0, true).is_err());
     }
@@ -11,6 +11,7 @@ mod registry;
 pub use cache::Cache;
 pub use denom::{Denom, Unit};
 pub use id::Id;
+pub(crate) use id::VALUE_GENERATOR_DOMAIN_SEP;
 pub use registry::{Registry, REGISTRY};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
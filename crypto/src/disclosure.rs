@@ -0,0 +1,143 @@
+use std::convert::{TryFrom, TryInto};
+
+use anyhow::anyhow;
+use penumbra_proto::{crypto as pb, Protobuf};
+use serde::{Deserialize, Serialize};
+
+use crate::{memo::MemoCiphertext, memo::MemoPlaintext, Note, NotePayload, PayloadKey};
+
+/// A disclosure of a single output of a transaction, which lets a third party
+/// decrypt that output and nothing else.
+///
+/// The disclosure names the output by the hash of its transaction and its index
+/// among that transaction's outputs, and carries the output's [`PayloadKey`],
+/// which either the sender (with their outgoing viewing key) or the recipient
+/// (with their incoming viewing key) can recover.  Since the payload key is
+/// derived from a fresh ephemeral key for each output, it can't be used to
+/// decrypt any other output, and it reveals nothing about either party's keys.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "pb::PaymentDisclosure", into = "pb::PaymentDisclosure")]
+pub struct PaymentDisclosure {
+    /// The hash of the transaction containing the disclosed output.
+    pub tx_hash: [u8; 32],
+    /// The index of the disclosed output among the transaction's outputs.
+    pub output_index: u64,
+    /// The payload key for the disclosed output.
+    pub payload_key: PayloadKey,
+}
+
+impl PaymentDisclosure {
+    /// Verify the disclosure against the disclosed output's note payload,
+    /// returning the disclosed note.
+    ///
+    /// This checks that the payload key decrypts the note, and that the note
+    /// matches the payload's note commitment, so a disclosure can't be used to
+    /// claim that an output contains some other note.  The caller is
+    /// responsible for checking that `note_payload` belongs to the output
+    /// named by the disclosure.
+    pub fn verify(&self, note_payload: &NotePayload) -> anyhow::Result<Note> {
+        let note = self
+            .payload_key
+            .decrypt_note(&note_payload.encrypted_note)
+            .map_err(|_| anyhow!("payload key does not decrypt the disclosed note"))?;
+
+        if note.commit() != note_payload.note_commitment {
+            return Err(anyhow!(
+                "disclosed note does not match the output's note commitment"
+            ));
+        }
+
+        Ok(note)
+    }
+
+    /// Decrypt the disclosed output's memo.
+    pub fn decrypt_memo(&self, ciphertext: &MemoCiphertext) -> anyhow::Result<MemoPlaintext> {
+        self.payload_key.decrypt_memo(ciphertext)
+    }
+}
+
+impl Protobuf<pb::PaymentDisclosure> for PaymentDisclosure {}
+
+impl From<PaymentDisclosure> for pb::PaymentDisclosure {
+    fn from(msg: PaymentDisclosure) -> Self {
+        pb::PaymentDisclosure {
+            tx_hash: msg.tx_hash.to_vec(),
+            output_index: msg.output_index,
+            payload_key: Some(msg.payload_key.into()),
+        }
+    }
+}
+
+impl TryFrom<pb::PaymentDisclosure> for PaymentDisclosure {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::PaymentDisclosure) -> Result<Self, Self::Error> {
+        Ok(PaymentDisclosure {
+            tx_hash: msg
+                .tx_hash
+                .as_slice()
+                .try_into()
+                .map_err(|_| anyhow!("transaction hash must be 32 bytes"))?,
+            output_index: msg.output_index,
+            payload_key: msg
+                .payload_key
+                .ok_or_else(|| anyhow!("missing payload key"))?
+                .try_into()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        ka,
+        keys::{SeedPhrase, SpendKey},
+        Value, STAKING_TOKEN_ASSET_ID,
+    };
+
+    fn note_payload(note: &Note) -> (NotePayload, ka::Public) {
+        let esk = ka::Secret::new(&mut OsRng);
+        let epk = esk.diversified_public(&note.diversified_generator());
+        let payload = NotePayload {
+            note_commitment: note.commit(),
+            ephemeral_key: epk,
+            encrypted_note: note.encrypt(&esk),
+        };
+        (payload, epk)
+    }
+
+    #[test]
+    fn disclosure_decrypts_exactly_one_note() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(&mut OsRng), 0);
+        let ivk = sk.full_viewing_key().incoming();
+        let (dest, _dtk) = ivk.payment_address(0u64.into());
+        let value = Value {
+            amount: 10,
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        };
+
+        let note = Note::generate(&mut OsRng, &dest, value);
+        let (payload, epk) = note_payload(&note);
+        let other_note = Note::generate(&mut OsRng, &dest, value);
+        let (other_payload, _) = note_payload(&other_note);
+
+        let disclosure = PaymentDisclosure {
+            tx_hash: [0; 32],
+            output_index: 0,
+            payload_key: PayloadKey::from_incoming(ivk, &epk).unwrap(),
+        };
+
+        assert_eq!(disclosure.verify(&payload).unwrap(), note);
+        assert!(disclosure.verify(&other_payload).is_err());
+
+        // A payload whose commitment doesn't match its ciphertext is rejected.
+        let mismatched = NotePayload {
+            note_commitment: other_note.commit(),
+            ..payload
+        };
+        assert!(disclosure.verify(&mismatched).is_err());
+    }
+}
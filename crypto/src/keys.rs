@@ -15,5 +15,6 @@ mod ivk;
 mod ovk;
 
 pub use fvk::{FullViewingKey, FullViewingKeyHash};
+pub(crate) use fvk::IVK_DOMAIN_SEP;
 pub use ivk::{IncomingViewingKey, IVK_LEN_BYTES};
 pub use ovk::{OutgoingViewingKey, OVK_LEN_BYTES};
@@ -3,6 +3,7 @@ use std::convert::TryFrom;
 use hmac::Hmac;
 use pbkdf2::pbkdf2;
 use penumbra_proto::{crypto as pb, Protobuf};
+use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 
 use super::{
@@ -91,6 +92,16 @@ impl SpendKey {
         SpendKeyBytes(spend_seed_bytes).into()
     }
 
+    /// Generate a new, random [`SpendKey`].
+    ///
+    /// This is used for one-off keys, such as those authorizing dummy spends;
+    /// long-lived keys should be derived [from a seed phrase](Self::from_seed_phrase).
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut spend_seed_bytes = [0u8; SPENDKEY_LEN_BYTES];
+        rng.fill_bytes(&mut spend_seed_bytes);
+        SpendKeyBytes(spend_seed_bytes).into()
+    }
+
     // XXX how many of these do we need? leave them for now
     // but don't document until design is more settled
 
@@ -8,6 +8,7 @@ pub use decaf377_rdsa as rdsa;
 mod address;
 pub mod asset;
 mod delegation_token;
+mod disclosure;
 pub mod eddy;
 mod flow;
 mod identity_key;
@@ -16,19 +17,24 @@ pub mod memo;
 pub mod note;
 mod note_payload;
 mod nullifier;
+mod payload_key;
 mod prf;
 pub mod proofs;
+mod unbonding_token;
 pub mod value;
 
 pub use address::Address;
 pub use asset::Asset;
 pub use delegation_token::DelegationToken;
+pub use disclosure::PaymentDisclosure;
 pub use flow::MockFlowCiphertext;
 pub use identity_key::IdentityKey;
 pub use keys::FullViewingKey;
 pub use note::Note;
 pub use note_payload::NotePayload;
 pub use nullifier::Nullifier;
+pub use payload_key::PayloadKey;
+pub use unbonding_token::UnbondingToken;
 pub use value::Value;
 
 // Temporary for v0 to v1 testnet address migration.
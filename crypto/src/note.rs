@@ -51,7 +51,7 @@ pub struct Note {
 }
 
 /// The domain separator used to generate note commitments.
-static NOTECOMMIT_DOMAIN_SEP: Lazy<Fq> = Lazy::new(|| {
+pub(crate) static NOTECOMMIT_DOMAIN_SEP: Lazy<Fq> = Lazy::new(|| {
     Fq::from_le_bytes_mod_order(blake2b_simd::blake2b(b"penumbra.notecommit").as_bytes())
 });
 
@@ -158,20 +158,8 @@ impl Note {
         ovk: &OutgoingViewingKey,
         cv: value::Commitment,
     ) -> [u8; OVK_WRAPPED_LEN_BYTES] {
-        let cv_bytes: [u8; 32] = cv.into();
-        let cm_bytes: [u8; 32] = self.commit().into();
         let epk = esk.diversified_public(&self.diversified_generator());
-
-        // Use Blake2b-256 to derive an encryption key `ock` from the value commitment,
-        // note commitment, the ephemeral public key, and the outgoing viewing key.
-        let mut kdf_params = blake2b_simd::Params::new();
-        kdf_params.hash_length(32);
-        let mut kdf = kdf_params.to_state();
-        kdf.update(&ovk.0);
-        kdf.update(&cv_bytes);
-        kdf.update(&cm_bytes);
-        kdf.update(&epk.0);
-        let kdf_output = kdf.finalize();
+        let kdf_output = derive_outgoing_cipher_key(ovk, cv, self.commit(), &epk);
         let ock = Key::from_slice(kdf_output.as_bytes());
 
         let mut op = Vec::new();
@@ -271,6 +259,28 @@ pub(crate) fn derive_symmetric_key(
     kdf.finalize()
 }
 
+/// Use Blake2b-256 to derive an encryption key `ock` from the value commitment,
+/// note commitment, the ephemeral public key, and the outgoing viewing key.
+pub(crate) fn derive_outgoing_cipher_key(
+    ovk: &OutgoingViewingKey,
+    cv: value::Commitment,
+    cm: Commitment,
+    epk: &ka::Public,
+) -> blake2b_simd::Hash {
+    let cv_bytes: [u8; 32] = cv.into();
+    let cm_bytes: [u8; 32] = cm.into();
+
+    let mut kdf_params = blake2b_simd::Params::new();
+    kdf_params.hash_length(32);
+    let mut kdf = kdf_params.to_state();
+    kdf.update(&ovk.0);
+    kdf.update(&cv_bytes);
+    kdf.update(&cm_bytes);
+    kdf.update(&epk.0);
+
+    kdf.finalize()
+}
+
 impl std::fmt::Debug for Note {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Note")
@@ -0,0 +1,205 @@
+use std::convert::{TryFrom, TryInto};
+
+use anyhow::anyhow;
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use derivative::Derivative;
+use penumbra_proto::{crypto as pb, Protobuf};
+
+use crate::{
+    ka,
+    keys::{IncomingViewingKey, OutgoingViewingKey},
+    memo::{MemoCiphertext, MemoPlaintext, MEMO_ENCRYPTION_NONCE, MEMO_LEN_BYTES},
+    note::{
+        self, derive_outgoing_cipher_key, derive_symmetric_key, NOTE_ENCRYPTION_NONCE,
+        NOTE_LEN_BYTES, OVK_WRAPPED_LEN_BYTES,
+    },
+    value, Note,
+};
+
+/// The symmetric key used to encrypt an output's note and memo.
+///
+/// Both the recipient (with their incoming viewing key) and the sender (with
+/// their outgoing viewing key) can recover the payload key for an output, and
+/// the payload key alone is enough to decrypt the output, without revealing
+/// anything about either party's keys.
+#[derive(Copy, Clone, PartialEq, Eq, Derivative)]
+#[derivative(Debug)]
+pub struct PayloadKey(
+    #[derivative(Debug(bound = "", format_with = "crate::fmt_hex"))] pub [u8; 32],
+);
+
+impl PayloadKey {
+    /// Derive the payload key from the shared secret and the ephemeral public key.
+    pub fn derive(shared_secret: &ka::SharedSecret, epk: &ka::Public) -> Self {
+        let key = derive_symmetric_key(shared_secret, epk);
+        Self(
+            key.as_bytes()
+                .try_into()
+                .expect("symmetric key is 32 bytes"),
+        )
+    }
+
+    /// Recover the payload key for an output sent to `ivk`.
+    pub fn from_incoming(ivk: &IncomingViewingKey, epk: &ka::Public) -> anyhow::Result<Self> {
+        let shared_secret = ivk
+            .key_agreement_with(epk)
+            .map_err(|_| anyhow!("could not perform key agreement"))?;
+
+        Ok(Self::derive(&shared_secret, epk))
+    }
+
+    /// Recover the payload key for an output sent by the holder of `ovk`,
+    /// by decrypting the output's wrapped key.
+    pub fn from_outgoing(
+        ovk: &OutgoingViewingKey,
+        ovk_wrapped_key: &[u8; OVK_WRAPPED_LEN_BYTES],
+        cv: value::Commitment,
+        cm: note::Commitment,
+        epk: &ka::Public,
+    ) -> anyhow::Result<Self> {
+        let ock = derive_outgoing_cipher_key(ovk, cv, cm, epk);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(ock.as_bytes()));
+        let nonce = Nonce::from_slice(&*NOTE_ENCRYPTION_NONCE);
+        let plaintext = cipher
+            .decrypt(nonce, ovk_wrapped_key.as_ref())
+            .map_err(|_| anyhow!("could not decrypt wrapped key"))?;
+
+        // The wrapped key is the transmission key followed by the ephemeral secret.
+        let transmission_key = ka::Public::try_from(&plaintext[0..32])
+            .map_err(|_| anyhow!("invalid transmission key"))?;
+        let esk = ka::Secret::try_from(&plaintext[32..64])
+            .map_err(|_| anyhow!("invalid ephemeral secret"))?;
+        let shared_secret = esk
+            .key_agreement_with(&transmission_key)
+            .map_err(|_| anyhow!("could not perform key agreement"))?;
+
+        Ok(Self::derive(&shared_secret, epk))
+    }
+
+    /// Decrypt a note ciphertext with this payload key.
+    pub fn decrypt_note(&self, ciphertext: &[u8]) -> Result<Note, note::Error> {
+        let plaintext = self
+            .decrypt(&*NOTE_ENCRYPTION_NONCE, ciphertext)
+            .map_err(|_| note::Error::DecryptionError)?;
+
+        let plaintext_bytes: [u8; NOTE_LEN_BYTES] = plaintext
+            .try_into()
+            .map_err(|_| note::Error::DecryptionError)?;
+
+        plaintext_bytes
+            .try_into()
+            .map_err(|_| note::Error::DecryptionError)
+    }
+
+    /// Decrypt a memo ciphertext with this payload key.
+    pub fn decrypt_memo(&self, ciphertext: &MemoCiphertext) -> anyhow::Result<MemoPlaintext> {
+        let plaintext = self.decrypt(&*MEMO_ENCRYPTION_NONCE, &ciphertext.0)?;
+
+        let plaintext_bytes: [u8; MEMO_LEN_BYTES] = plaintext
+            .try_into()
+            .map_err(|_| anyhow!("could not fit plaintext into memo size"))?;
+
+        Ok(MemoPlaintext(plaintext_bytes))
+    }
+
+    fn decrypt(&self, nonce: &[u8; 12], ciphertext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&self.0));
+        cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("decryption error"))
+    }
+
+    pub fn parse_hex(str: &str) -> anyhow::Result<PayloadKey> {
+        let bytes = hex::decode(str)?;
+        PayloadKey::try_from(&bytes[..])
+    }
+}
+
+impl TryFrom<&[u8]> for PayloadKey {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(PayloadKey(
+            bytes
+                .try_into()
+                .map_err(|_| anyhow!("payload key must be 32 bytes"))?,
+        ))
+    }
+}
+
+impl std::fmt::Display for PayloadKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(&self.0))
+    }
+}
+
+impl Protobuf<pb::PayloadKey> for PayloadKey {}
+
+impl From<PayloadKey> for pb::PayloadKey {
+    fn from(key: PayloadKey) -> Self {
+        pb::PayloadKey {
+            inner: key.0.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<pb::PayloadKey> for PayloadKey {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::PayloadKey) -> Result<Self, Self::Error> {
+        msg.inner.as_slice().try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        keys::{SeedPhrase, SpendKey},
+        Fr, Value, STAKING_TOKEN_ASSET_ID,
+    };
+
+    #[test]
+    fn sender_and_recipient_recover_the_same_payload_key() {
+        let sender = SpendKey::from_seed_phrase(SeedPhrase::generate(&mut OsRng), 0);
+        let recipient = SpendKey::from_seed_phrase(SeedPhrase::generate(&mut OsRng), 0);
+        let (dest, _dtk) = recipient
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+
+        let note = Note::generate(
+            &mut OsRng,
+            &dest,
+            Value {
+                amount: 10,
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            },
+        );
+        let esk = ka::Secret::new(&mut OsRng);
+        let epk = esk.diversified_public(&note.diversified_generator());
+        let cv = note.value().commit(Fr::rand(&mut OsRng));
+        let ciphertext = note.encrypt(&esk);
+        let wrapped = note.encrypt_key(&esk, sender.full_viewing_key().outgoing(), cv);
+
+        let incoming =
+            PayloadKey::from_incoming(recipient.full_viewing_key().incoming(), &epk).unwrap();
+        let outgoing = PayloadKey::from_outgoing(
+            sender.full_viewing_key().outgoing(),
+            &wrapped,
+            cv,
+            note.commit(),
+            &epk,
+        )
+        .unwrap();
+
+        assert_eq!(incoming, outgoing);
+        assert_eq!(incoming.decrypt_note(&ciphertext).unwrap(), note);
+    }
+}
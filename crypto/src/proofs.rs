@@ -1 +1,105 @@
+//! Proofs of the spend and output statements.
+//!
+//! A [`SpendProof`] or [`OutputProof`] is either a [transparent](transparent)
+//! proof, which reveals its witness in the clear, or a [Groth16](groth16)
+//! proof, which does not.
+
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+use penumbra_tct as tct;
+
+use crate::{ka, note, value, Nullifier};
+
+pub mod groth16;
 pub mod transparent;
+
+/// A proof that a spend is valid.
+#[derive(Clone, Debug)]
+pub enum SpendProof {
+    Transparent(transparent::SpendProof),
+    Groth16(groth16::SpendProof),
+}
+
+impl SpendProof {
+    /// Called to verify the proof using the provided public inputs.
+    ///
+    /// Verifying a Groth16 proof requires that the verifying keys have been
+    /// [installed](groth16::install_verifying_keys).
+    pub fn verify(
+        &self,
+        anchor: tct::Root,
+        value_commitment: value::Commitment,
+        nullifier: Nullifier,
+        rk: VerificationKey<SpendAuth>,
+    ) -> anyhow::Result<()> {
+        match self {
+            SpendProof::Transparent(proof) => {
+                proof.verify(anchor, value_commitment, nullifier, rk)?
+            }
+            SpendProof::Groth16(proof) => proof.verify(
+                &groth16::verifying_keys()?.spend,
+                anchor,
+                value_commitment,
+                nullifier,
+                rk,
+            )?,
+        }
+        Ok(())
+    }
+}
+
+impl From<transparent::SpendProof> for SpendProof {
+    fn from(proof: transparent::SpendProof) -> Self {
+        SpendProof::Transparent(proof)
+    }
+}
+
+impl From<groth16::SpendProof> for SpendProof {
+    fn from(proof: groth16::SpendProof) -> Self {
+        SpendProof::Groth16(proof)
+    }
+}
+
+/// A proof that an output is valid.
+#[derive(Clone, Debug)]
+pub enum OutputProof {
+    Transparent(transparent::OutputProof),
+    Groth16(groth16::OutputProof),
+}
+
+impl OutputProof {
+    /// Called to verify the proof using the provided public inputs.
+    ///
+    /// Verifying a Groth16 proof requires that the verifying keys have been
+    /// [installed](groth16::install_verifying_keys).
+    pub fn verify(
+        &self,
+        value_commitment: value::Commitment,
+        note_commitment: note::Commitment,
+        epk: ka::Public,
+    ) -> anyhow::Result<()> {
+        match self {
+            OutputProof::Transparent(proof) => {
+                proof.verify(value_commitment, note_commitment, epk)?
+            }
+            OutputProof::Groth16(proof) => proof.verify(
+                &groth16::verifying_keys()?.output,
+                value_commitment,
+                note_commitment,
+                epk,
+            )?,
+        }
+        Ok(())
+    }
+}
+
+impl From<transparent::OutputProof> for OutputProof {
+    fn from(proof: transparent::OutputProof) -> Self {
+        OutputProof::Transparent(proof)
+    }
+}
+
+impl From<groth16::OutputProof> for OutputProof {
+    fn from(proof: groth16::OutputProof) -> Self {
+        OutputProof::Groth16(proof)
+    }
+}
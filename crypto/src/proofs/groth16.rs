@@ -0,0 +1,312 @@
+//! Groth16 zk-SNARK proofs of the spend and output statements.
+//!
+//! The statements are the same as those checked by the [transparent
+//! proofs](super::transparent), expressed as R1CS circuits over the decaf377
+//! base field, and proven over BLS12-377.
+//!
+//! Proving and verifying keys are produced by a local [`setup`], written to a
+//! directory, and loaded from it with [`ProvingKeys::load`] or
+//! [`VerifyingKeys::load`].  Once loaded, keys are [installed](install_proving_keys)
+//! process-wide, so that proofs can be created and checked without threading
+//! keys through every caller.
+//!
+//! Full nodes must all verify proofs against the same keys, so they don't load
+//! verifying keys from local files: they [decode](VerifyingKeys::decode) the
+//! keys recorded in the chain parameters instead.
+
+use std::{fs, path::Path};
+
+use anyhow::Context;
+use ark_bls12_377::Bls12_377;
+use ark_groth16::{PreparedVerifyingKey, ProvingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use once_cell::sync::OnceCell;
+use rand_core::{CryptoRng, RngCore};
+
+mod gadgets;
+mod output;
+mod spend;
+
+pub use output::{OutputCircuit, OutputProof};
+pub use spend::{SpendCircuit, SpendProof};
+
+type Groth16 = ark_groth16::Groth16<Bls12_377>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Invalid public input")]
+    InvalidPublicInput,
+    #[error("Proof did not verify")]
+    InvalidProof,
+    #[error("Proving failed")]
+    ProvingFailed,
+    #[error("Groth16 proof malformed")]
+    ProofMalformed,
+    #[error("Groth16 verifying keys have not been loaded")]
+    VerifyingKeysMissing,
+    #[error("Groth16 proving keys have not been loaded")]
+    ProvingKeysMissing,
+}
+
+const SPEND_PROVING_KEY_FILE: &str = "spend_pk.bin";
+const SPEND_VERIFYING_KEY_FILE: &str = "spend_vk.bin";
+const OUTPUT_PROVING_KEY_FILE: &str = "output_pk.bin";
+const OUTPUT_VERIFYING_KEY_FILE: &str = "output_vk.bin";
+
+static PROVING_KEYS: OnceCell<ProvingKeys> = OnceCell::new();
+static VERIFYING_KEYS: OnceCell<VerifyingKeys> = OnceCell::new();
+
+/// The proving keys for the spend and output circuits.
+pub struct ProvingKeys {
+    pub spend: ProvingKey<Bls12_377>,
+    pub output: ProvingKey<Bls12_377>,
+}
+
+/// The verifying keys for the spend and output circuits.
+pub struct VerifyingKeys {
+    pub spend: PreparedVerifyingKey<Bls12_377>,
+    pub output: PreparedVerifyingKey<Bls12_377>,
+}
+
+/// Generate fresh proving keys for the spend and output circuits.
+///
+/// This is a local, single-party setup: whoever runs it learns the toxic
+/// waste, so it is only suitable for development and testing networks.
+pub fn setup<R: RngCore + CryptoRng>(rng: &mut R) -> anyhow::Result<ProvingKeys> {
+    let (spend, _) = Groth16::circuit_specific_setup(SpendCircuit::blank(), rng)
+        .context("could not generate spend parameters")?;
+    let (output, _) = Groth16::circuit_specific_setup(OutputCircuit::blank(), rng)
+        .context("could not generate output parameters")?;
+    Ok(ProvingKeys { spend, output })
+}
+
+impl ProvingKeys {
+    /// Write the proving keys, and the verifying keys derived from them, to
+    /// the given directory.
+    pub fn write(&self, dir: &Path) -> anyhow::Result<()> {
+        fs::create_dir_all(dir)?;
+        write_key(&dir.join(SPEND_PROVING_KEY_FILE), &self.spend)?;
+        write_key(&dir.join(SPEND_VERIFYING_KEY_FILE), &self.spend.vk)?;
+        write_key(&dir.join(OUTPUT_PROVING_KEY_FILE), &self.output)?;
+        write_key(&dir.join(OUTPUT_VERIFYING_KEY_FILE), &self.output.vk)?;
+        Ok(())
+    }
+
+    /// Load the proving keys from the given directory.
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            spend: read_key(&dir.join(SPEND_PROVING_KEY_FILE))?,
+            output: read_key(&dir.join(OUTPUT_PROVING_KEY_FILE))?,
+        })
+    }
+
+    /// The verifying keys corresponding to these proving keys.
+    pub fn verifying_keys(&self) -> VerifyingKeys {
+        VerifyingKeys {
+            spend: ark_groth16::prepare_verifying_key(&self.spend.vk),
+            output: ark_groth16::prepare_verifying_key(&self.output.vk),
+        }
+    }
+}
+
+impl VerifyingKeys {
+    /// Load the verifying keys from the given directory.
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let (spend, output) = Self::load_encoded(dir)?;
+        Self::decode(&spend, &output)
+    }
+
+    /// Read the encoded spend and output verifying keys from the given
+    /// directory, without decoding them.
+    pub fn load_encoded(dir: &Path) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+        let read = |path: &Path| {
+            fs::read(path).with_context(|| format!("could not read {}", path.display()))
+        };
+        Ok((
+            read(&dir.join(SPEND_VERIFYING_KEY_FILE))?,
+            read(&dir.join(OUTPUT_VERIFYING_KEY_FILE))?,
+        ))
+    }
+
+    /// Decode the spend and output verifying keys from their encodings.
+    pub fn decode(spend: &[u8], output: &[u8]) -> anyhow::Result<Self> {
+        let spend = ark_groth16::VerifyingKey::<Bls12_377>::deserialize(spend)
+            .context("could not decode spend verifying key")?;
+        let output = ark_groth16::VerifyingKey::<Bls12_377>::deserialize(output)
+            .context("could not decode output verifying key")?;
+        Ok(Self {
+            spend: ark_groth16::prepare_verifying_key(&spend),
+            output: ark_groth16::prepare_verifying_key(&output),
+        })
+    }
+}
+
+/// Install the given proving keys (and their verifying keys) process-wide.
+///
+/// Keys can only be installed once; later calls return an error.
+pub fn install_proving_keys(keys: ProvingKeys) -> anyhow::Result<()> {
+    install_verifying_keys(keys.verifying_keys())?;
+    PROVING_KEYS
+        .set(keys)
+        .map_err(|_| anyhow::anyhow!("Groth16 proving keys are already installed"))
+}
+
+/// Install the given verifying keys process-wide.
+///
+/// Keys can only be installed once; later calls return an error.
+pub fn install_verifying_keys(keys: VerifyingKeys) -> anyhow::Result<()> {
+    VERIFYING_KEYS
+        .set(keys)
+        .map_err(|_| anyhow::anyhow!("Groth16 verifying keys are already installed"))
+}
+
+/// The installed proving keys, if any.
+pub fn proving_keys() -> Option<&'static ProvingKeys> {
+    PROVING_KEYS.get()
+}
+
+/// The installed verifying keys.
+pub fn verifying_keys() -> Result<&'static VerifyingKeys, Error> {
+    VERIFYING_KEYS.get().ok_or(Error::VerifyingKeysMissing)
+}
+
+fn write_key<T: CanonicalSerialize>(path: &Path, key: &T) -> anyhow::Result<()> {
+    let mut bytes = Vec::new();
+    key.serialize(&mut bytes)?;
+    fs::write(path, bytes).with_context(|| format!("could not write {}", path.display()))
+}
+
+fn read_key<T: CanonicalDeserialize>(path: &Path) -> anyhow::Result<T> {
+    let bytes = fs::read(path).with_context(|| format!("could not read {}", path.display()))?;
+    T::deserialize(&bytes[..]).with_context(|| format!("could not decode {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::UniformRand;
+    use penumbra_tct as tct;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        asset, ka,
+        keys::{SeedPhrase, SpendKey},
+        proofs::transparent,
+        rdsa::{SpendAuth, VerificationKey},
+        Fr, Note, Value,
+    };
+
+    #[test]
+    fn spend_proof_roundtrip() {
+        let mut rng = OsRng;
+        let keys = setup(&mut rng).unwrap();
+        let vks = keys.verifying_keys();
+
+        let seed_phrase = SeedPhrase::generate(&mut rng);
+        let sk_sender = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let fvk_sender = sk_sender.full_viewing_key();
+        let (sender, _dtk_d) = fvk_sender.incoming().payment_address(0u64.into());
+
+        let value = Value {
+            amount: 10,
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+        let v_blinding = Fr::rand(&mut rng);
+        let note = Note::generate(&mut rng, &sender, value);
+        let note_commitment = note.commit();
+        let spend_auth_randomizer = Fr::rand(&mut rng);
+        let rk: VerificationKey<SpendAuth> = sk_sender
+            .spend_auth_key()
+            .randomize(&spend_auth_randomizer)
+            .into();
+        let nk = *sk_sender.nullifier_key();
+
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, note_commitment).unwrap();
+        let anchor = nct.root();
+        let note_commitment_proof = nct.witness(note_commitment).unwrap();
+        let nullifier = nk.derive_nullifier(0.into(), &note_commitment);
+        let value_commitment = value.commit(v_blinding);
+
+        let witness = transparent::SpendProof {
+            note_commitment_proof,
+            g_d: *sender.diversified_generator(),
+            pk_d: *sender.transmission_key(),
+            value,
+            v_blinding,
+            note_blinding: note.note_blinding(),
+            spend_auth_randomizer,
+            ak: sk_sender.spend_auth_key().into(),
+            nk,
+        };
+        let circuit = SpendCircuit::new(anchor, value_commitment, nullifier, rk, witness);
+        let proof = SpendProof::prove(&mut rng, &keys.spend, circuit).unwrap();
+        let bytes: Vec<u8> = proof.into();
+        let proof = SpendProof::try_from(&bytes[..]).unwrap();
+
+        assert!(proof
+            .verify(&vks.spend, anchor, value_commitment, nullifier, rk)
+            .is_ok());
+
+        // A proof must not verify against a different anchor...
+        nct.insert(
+            tct::Witness::Forget,
+            Note::generate(&mut rng, &sender, value).commit(),
+        )
+        .unwrap();
+        let wrong_anchor = nct.root();
+        assert!(proof
+            .verify(&vks.spend, wrong_anchor, value_commitment, nullifier, rk)
+            .is_err());
+
+        // ...or a nullifier for a different position.
+        let wrong_nullifier = nk.derive_nullifier(1.into(), &note_commitment);
+        assert!(proof
+            .verify(&vks.spend, anchor, value_commitment, wrong_nullifier, rk)
+            .is_err());
+    }
+
+    #[test]
+    fn output_proof_roundtrip() {
+        let mut rng = OsRng;
+        let keys = setup(&mut rng).unwrap();
+        let vks = keys.verifying_keys();
+
+        let seed_phrase = SeedPhrase::generate(&mut rng);
+        let sk_recipient = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let fvk_recipient = sk_recipient.full_viewing_key();
+        let (dest, _dtk_d) = fvk_recipient.incoming().payment_address(0u64.into());
+
+        let value = Value {
+            amount: 10,
+            asset_id: asset::REGISTRY.parse_denom("upenumbra").unwrap().id(),
+        };
+        let v_blinding = Fr::rand(&mut rng);
+        let note = Note::generate(&mut rng, &dest, value);
+        let esk = ka::Secret::new(&mut rng);
+        let epk = esk.diversified_public(&note.diversified_generator());
+        let value_commitment = -value.commit(v_blinding);
+
+        let witness = transparent::OutputProof {
+            g_d: *dest.diversified_generator(),
+            pk_d: *dest.transmission_key(),
+            value,
+            v_blinding,
+            note_blinding: note.note_blinding(),
+            esk,
+        };
+        let circuit = OutputCircuit::new(value_commitment, note.commit(), epk, witness);
+        let proof = OutputProof::prove(&mut rng, &keys.output, circuit).unwrap();
+        let bytes: Vec<u8> = proof.into();
+        let proof = OutputProof::try_from(&bytes[..]).unwrap();
+
+        assert!(proof
+            .verify(&vks.output, value_commitment, note.commit(), epk)
+            .is_ok());
+        // A proof must not verify against different public inputs.
+        assert!(proof
+            .verify(&vks.output, -value_commitment, note.commit(), epk)
+            .is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! R1CS gadgets shared by the spend and output circuits.
+//!
+//! Each gadget mirrors an out-of-circuit computation elsewhere in this crate,
+//! and must be kept in sync with it.
+
+use ark_ff::{BitIteratorLE, PrimeField};
+use ark_r1cs_std::{prelude::*, uint64::UInt64};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use decaf377::{
+    r1cs::{ElementVar, FqVar},
+    Fq, Fr,
+};
+use penumbra_tct as tct;
+use poseidon377::r1cs::{hash_1, hash_2, hash_3, hash_4, hash_5};
+
+use crate::{
+    asset::VALUE_GENERATOR_DOMAIN_SEP, keys::IVK_DOMAIN_SEP, note::NOTECOMMIT_DOMAIN_SEP,
+    nullifier::NULLIFIER_DOMAIN_SEP, value::VALUE_BLINDING_GENERATOR,
+};
+
+/// The number of bits of a [`tct::Position`] used to index the tree.
+const POSITION_BITS: usize = 48;
+
+/// The height of the tiered commitment tree.
+const TREE_HEIGHT: usize = 24;
+
+/// Allocate a scalar as little-endian witness bits, for use in scalar multiplication.
+pub(super) fn fr_witness_bits(
+    cs: ConstraintSystemRef<Fq>,
+    scalar: Option<Fr>,
+) -> Result<Vec<Boolean<Fq>>, SynthesisError> {
+    let bits: Vec<Option<bool>> = match scalar {
+        Some(scalar) => BitIteratorLE::new(scalar.into_repr())
+            .take(Fr::size_in_bits())
+            .map(Some)
+            .collect(),
+        None => vec![None; Fr::size_in_bits()],
+    };
+
+    bits.into_iter()
+        .map(|bit| {
+            Boolean::new_witness(cs.clone(), || bit.ok_or(SynthesisError::AssignmentMissing))
+        })
+        .collect()
+}
+
+/// Allocate an amount as a witness, returning its little-endian bits and its
+/// value as a field element.
+pub(super) fn amount_witness(
+    cs: ConstraintSystemRef<Fq>,
+    amount: Option<u64>,
+) -> Result<(Vec<Boolean<Fq>>, FqVar), SynthesisError> {
+    let amount = UInt64::new_witness(cs, || amount.ok_or(SynthesisError::AssignmentMissing))?;
+    let bits = amount.to_bits_le();
+    let amount = Boolean::le_bits_to_fp_var(&bits)?;
+    Ok((bits, amount))
+}
+
+/// Enforce that an element is not the identity.
+///
+/// The use of decaf means that we do not need to check that points are not of
+/// small order, but we still need to exclude the identity.
+pub(super) fn enforce_not_identity(element: &ElementVar) -> Result<(), SynthesisError> {
+    element
+        .is_eq(&ElementVar::zero())?
+        .enforce_equal(&Boolean::FALSE)
+}
+
+/// Compute the note commitment, as in [`crate::note::commitment`].
+pub(super) fn note_commitment(
+    cs: ConstraintSystemRef<Fq>,
+    note_blinding: &FqVar,
+    amount: &FqVar,
+    asset_id: &FqVar,
+    diversified_generator: &ElementVar,
+    transmission_key: &ElementVar,
+) -> Result<FqVar, SynthesisError> {
+    let domain_sep = FqVar::new_constant(cs.clone(), *NOTECOMMIT_DOMAIN_SEP)?;
+    hash_5(
+        cs,
+        &domain_sep,
+        (
+            note_blinding.clone(),
+            amount.clone(),
+            asset_id.clone(),
+            diversified_generator.compress_to_field()?,
+            transmission_key.compress_to_field()?,
+        ),
+    )
+}
+
+/// Compute the (positive) value commitment, as in [`crate::Value::commit`].
+pub(super) fn value_commitment(
+    cs: ConstraintSystemRef<Fq>,
+    amount_bits: &[Boolean<Fq>],
+    asset_id: &FqVar,
+    blinding_bits: &[Boolean<Fq>],
+) -> Result<ElementVar, SynthesisError> {
+    let domain_sep = FqVar::new_constant(cs.clone(), *VALUE_GENERATOR_DOMAIN_SEP)?;
+    let value_generator =
+        ElementVar::map_to_group_cdh(&hash_1(cs.clone(), &domain_sep, asset_id.clone())?)?;
+    let blinding_generator = ElementVar::new_constant(cs, *VALUE_BLINDING_GENERATOR)?;
+
+    let value = value_generator.scalar_mul_le(amount_bits.iter())?;
+    let blinding = blinding_generator.scalar_mul_le(blinding_bits.iter())?;
+    Ok(value + blinding)
+}
+
+/// Compute the nullifier, as in [`crate::keys::NullifierKey::derive_nullifier`].
+pub(super) fn nullifier(
+    cs: ConstraintSystemRef<Fq>,
+    nk: &FqVar,
+    note_commitment: &FqVar,
+    position: &FqVar,
+) -> Result<FqVar, SynthesisError> {
+    let domain_sep = FqVar::new_constant(cs.clone(), *NULLIFIER_DOMAIN_SEP)?;
+    hash_3(
+        cs,
+        &domain_sep,
+        (nk.clone(), note_commitment.clone(), position.clone()),
+    )
+}
+
+/// Compute the transmission key for a diversified generator, given the
+/// components of a full viewing key, as in [`crate::keys::FullViewingKey`].
+pub(super) fn transmission_key(
+    cs: ConstraintSystemRef<Fq>,
+    ak: &ElementVar,
+    nk: &FqVar,
+    diversified_generator: &ElementVar,
+) -> Result<ElementVar, SynthesisError> {
+    let domain_sep = FqVar::new_constant(cs.clone(), *IVK_DOMAIN_SEP)?;
+    let ivk_mod_q = hash_2(cs, &domain_sep, (nk.clone(), ak.compress_to_field()?))?;
+
+    // Out of circuit, the ivk is reduced mod r before use.  Multiplying by
+    // the unreduced bits gives the same result, since the group has order r.
+    diversified_generator.scalar_mul_le(ivk_mod_q.to_bits_le()?.iter())
+}
+
+/// Allocate a position as a witness, returning the little-endian bits used to
+/// index the tree and its value as a field element.
+pub(super) fn position_witness(
+    cs: ConstraintSystemRef<Fq>,
+    position: Option<tct::Position>,
+) -> Result<(Vec<Boolean<Fq>>, FqVar), SynthesisError> {
+    let position = UInt64::new_witness(cs, || {
+        position
+            .map(u64::from)
+            .ok_or(SynthesisError::AssignmentMissing)
+    })?;
+    let bits = position.to_bits_le();
+
+    // Positions beyond the capacity of the tree can't be witnessed.
+    for bit in &bits[POSITION_BITS..] {
+        bit.enforce_equal(&Boolean::FALSE)?;
+    }
+
+    let position = Boolean::le_bits_to_fp_var(&bits)?;
+    Ok((bits[..POSITION_BITS].to_vec(), position))
+}
+
+/// Compute the root of the tiered commitment tree from a leaf commitment, its
+/// position bits, and its authentication path (ordered from root to leaf), as
+/// in [`tct::Proof::verify`].
+pub(super) fn merkle_root(
+    cs: ConstraintSystemRef<Fq>,
+    note_commitment: &FqVar,
+    position_bits: &[Boolean<Fq>],
+    auth_path: &[[FqVar; 3]],
+) -> Result<FqVar, SynthesisError> {
+    assert_eq!(position_bits.len(), POSITION_BITS);
+    assert_eq!(auth_path.len(), TREE_HEIGHT);
+
+    let domain_sep = *tct::structure::DOMAIN_SEPARATOR;
+    let leaf_domain_sep = FqVar::new_constant(cs.clone(), domain_sep)?;
+    let mut current = hash_1(cs.clone(), &leaf_domain_sep, note_commitment.clone())?;
+
+    for height in 1..=TREE_HEIGHT {
+        // The auth path is ordered from root to leaf, so the siblings at
+        // height 1 are the last entry.
+        let [s0, s1, s2] = &auth_path[TREE_HEIGHT - height];
+
+        // Two bits of the position select which child the path goes through.
+        let low = &position_bits[2 * (height - 1)];
+        let high = &position_bits[2 * (height - 1) + 1];
+        let is_leftmost = low.not().and(&high.not())?;
+        let is_left = low.and(&high.not())?;
+        let is_right = low.not().and(high)?;
+        let is_rightmost = low.and(high)?;
+
+        // Insert the current hash among its siblings, as in `WhichWay::insert`.
+        let c0 = FqVar::conditionally_select(&is_leftmost, &current, s0)?;
+        let c1 = FqVar::conditionally_select(
+            &is_leftmost,
+            s0,
+            &FqVar::conditionally_select(&is_left, &current, s1)?,
+        )?;
+        let c2 = FqVar::conditionally_select(
+            &is_rightmost,
+            s2,
+            &FqVar::conditionally_select(&is_right, &current, s1)?,
+        )?;
+        let c3 = FqVar::conditionally_select(&is_rightmost, &current, s2)?;
+
+        let node_domain_sep =
+            FqVar::new_constant(cs.clone(), domain_sep + Fq::from(height as u64))?;
+        current = hash_4(cs.clone(), &node_domain_sep, (c0, c1, c2, c3))?;
+    }
+
+    Ok(current)
+}
@@ -0,0 +1,193 @@
+use ark_bls12_377::Bls12_377;
+use ark_groth16::{PreparedVerifyingKey, Proof, ProvingKey};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use decaf377::{
+    r1cs::{ElementVar, FqVar},
+    FieldExt, Fq,
+};
+use rand_core::{CryptoRng, RngCore};
+
+use super::{gadgets, Error, Groth16};
+use crate::{ka, note, proofs::transparent, value};
+
+/// The output statement, as an R1CS circuit.
+///
+/// The public inputs are the value commitment, the note commitment, and the
+/// ephemeral public key.  The witness is the auxiliary data of a
+/// [`transparent::OutputProof`], which is absent when generating parameters.
+#[derive(Clone, Debug)]
+pub struct OutputCircuit {
+    value_commitment: value::Commitment,
+    note_commitment: note::Commitment,
+    epk: ka::Public,
+    witness: Option<transparent::OutputProof>,
+}
+
+impl OutputCircuit {
+    /// Construct the circuit for an output, given its public inputs and witness.
+    pub fn new(
+        value_commitment: value::Commitment,
+        note_commitment: note::Commitment,
+        epk: ka::Public,
+        witness: transparent::OutputProof,
+    ) -> Self {
+        Self {
+            value_commitment,
+            note_commitment,
+            epk,
+            witness: Some(witness),
+        }
+    }
+
+    /// Construct a circuit with no witness, used only to generate parameters.
+    pub(super) fn blank() -> Self {
+        let basepoint = decaf377::basepoint();
+        Self {
+            value_commitment: value::Commitment(basepoint),
+            note_commitment: note::Commitment(Fq::from(0u64)),
+            epk: ka::Public(basepoint.compress().0),
+            witness: None,
+        }
+    }
+
+    /// The public inputs to the circuit, in the order they are allocated.
+    fn public_inputs(
+        value_commitment: value::Commitment,
+        note_commitment: note::Commitment,
+        epk: ka::Public,
+    ) -> Result<Vec<Fq>, Error> {
+        Ok(vec![
+            value_commitment.0.compress_to_field(),
+            note_commitment.0,
+            Fq::from_bytes(epk.0).map_err(|_| Error::InvalidPublicInput)?,
+        ])
+    }
+}
+
+impl ConstraintSynthesizer<Fq> for OutputCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> Result<(), SynthesisError> {
+        let public_inputs =
+            Self::public_inputs(self.value_commitment, self.note_commitment, self.epk)
+                .map_err(|_| SynthesisError::AssignmentMissing)?;
+        let witness = self.witness.as_ref();
+
+        // Public inputs.
+        let [value_commitment, note_commitment, epk] = {
+            let vars = public_inputs
+                .into_iter()
+                .map(|input| FqVar::new_input(cs.clone(), || Ok(input)))
+                .collect::<Result<Vec<_>, _>>()?;
+            <[FqVar; 3]>::try_from(vars).expect("there are three public inputs")
+        };
+
+        // Witnessed note contents.
+        let g_d = ElementVar::new_witness(cs.clone(), || {
+            witness
+                .map(|w| w.g_d)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let pk_d = ElementVar::new_witness(cs.clone(), || {
+            witness
+                .and_then(|w| decaf377::Encoding(w.pk_d.0).decompress().ok())
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let (amount_bits, amount) =
+            gadgets::amount_witness(cs.clone(), witness.map(|w| w.value.amount))?;
+        let asset_id = FqVar::new_witness(cs.clone(), || {
+            witness
+                .map(|w| w.value.asset_id.0)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let note_blinding = FqVar::new_witness(cs.clone(), || {
+            witness
+                .map(|w| w.note_blinding)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // Witnessed randomness.
+        let v_blinding_bits = gadgets::fr_witness_bits(cs.clone(), witness.map(|w| w.v_blinding))?;
+        let esk_bits = gadgets::fr_witness_bits(
+            cs.clone(),
+            witness.and_then(|w| decaf377::Fr::from_bytes(w.esk.to_bytes()).ok()),
+        )?;
+
+        // The use of decaf means that we do not need to check that the
+        // diversified basepoint is of small order. However we instead
+        // check it is not identity.
+        gadgets::enforce_not_identity(&g_d)?;
+
+        // Note commitment integrity.
+        gadgets::note_commitment(cs.clone(), &note_blinding, &amount, &asset_id, &g_d, &pk_d)?
+            .enforce_equal(&note_commitment)?;
+
+        // Value commitment integrity.  Outputs subtract from the transaction
+        // value balance, so the commitment is negated.
+        gadgets::value_commitment(cs, &amount_bits, &asset_id, &v_blinding_bits)?
+            .negate()?
+            .compress_to_field()?
+            .enforce_equal(&value_commitment)?;
+
+        // Ephemeral public key integrity.
+        g_d.scalar_mul_le(esk_bits.iter())?
+            .compress_to_field()?
+            .enforce_equal(&epk)?;
+
+        Ok(())
+    }
+}
+
+/// A Groth16 proof of the output statement.
+#[derive(Clone, Debug)]
+pub struct OutputProof(Proof<Bls12_377>);
+
+impl OutputProof {
+    /// Prove the output statement, using the given proving key.
+    pub fn prove<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        pk: &ProvingKey<Bls12_377>,
+        circuit: OutputCircuit,
+    ) -> Result<Self, Error> {
+        Groth16::prove(pk, circuit, rng)
+            .map(Self)
+            .map_err(|_| Error::ProvingFailed)
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    pub fn verify(
+        &self,
+        vk: &PreparedVerifyingKey<Bls12_377>,
+        value_commitment: value::Commitment,
+        note_commitment: note::Commitment,
+        epk: ka::Public,
+    ) -> Result<(), Error> {
+        let public_inputs = OutputCircuit::public_inputs(value_commitment, note_commitment, epk)?;
+        match Groth16::verify_with_processed_vk(vk, &public_inputs, &self.0) {
+            Ok(true) => Ok(()),
+            _ => Err(Error::InvalidProof),
+        }
+    }
+}
+
+impl From<OutputProof> for Vec<u8> {
+    fn from(proof: OutputProof) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        proof
+            .0
+            .serialize(&mut bytes)
+            .expect("can serialize a proof into a vector");
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for OutputProof {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<OutputProof, Self::Error> {
+        Proof::deserialize(bytes)
+            .map(OutputProof)
+            .map_err(|_| Error::ProofMalformed)
+    }
+}
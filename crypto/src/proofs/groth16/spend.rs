@@ -0,0 +1,249 @@
+use ark_bls12_377::Bls12_377;
+use ark_groth16::{PreparedVerifyingKey, Proof, ProvingKey};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use decaf377::{
+    r1cs::{ElementVar, FqVar},
+    FieldExt, Fq,
+};
+use decaf377_rdsa::{SpendAuth, VerificationKey};
+use penumbra_tct as tct;
+use rand_core::{CryptoRng, RngCore};
+
+use super::{gadgets, Error, Groth16};
+use crate::{proofs::transparent, value, Nullifier};
+
+/// The spend statement, as an R1CS circuit.
+///
+/// The public inputs are the anchor, the value commitment, the nullifier, and
+/// the randomized spend verification key.  The witness is the auxiliary data of
+/// a [`transparent::SpendProof`], which is absent when generating parameters.
+#[derive(Clone, Debug)]
+pub struct SpendCircuit {
+    anchor: tct::Root,
+    value_commitment: value::Commitment,
+    nullifier: Nullifier,
+    rk: VerificationKey<SpendAuth>,
+    witness: Option<transparent::SpendProof>,
+}
+
+impl SpendCircuit {
+    /// Construct the circuit for a spend, given its public inputs and witness.
+    pub fn new(
+        anchor: tct::Root,
+        value_commitment: value::Commitment,
+        nullifier: Nullifier,
+        rk: VerificationKey<SpendAuth>,
+        witness: transparent::SpendProof,
+    ) -> Self {
+        Self {
+            anchor,
+            value_commitment,
+            nullifier,
+            rk,
+            witness: Some(witness),
+        }
+    }
+
+    /// Construct a circuit with no witness, used only to generate parameters.
+    pub(super) fn blank() -> Self {
+        let basepoint = decaf377::basepoint();
+        let rk: [u8; 32] = basepoint.compress().0;
+        Self {
+            anchor: tct::Tree::new().root(),
+            value_commitment: value::Commitment(basepoint),
+            nullifier: Nullifier(Fq::from(0u64)),
+            rk: rk
+                .try_into()
+                .expect("basepoint is a valid verification key"),
+            witness: None,
+        }
+    }
+
+    /// The public inputs to the circuit, in the order they are allocated.
+    fn public_inputs(
+        anchor: tct::Root,
+        value_commitment: value::Commitment,
+        nullifier: Nullifier,
+        rk: VerificationKey<SpendAuth>,
+    ) -> Result<Vec<Fq>, Error> {
+        let rk_bytes: [u8; 32] = rk.into();
+        Ok(vec![
+            Fq::from(anchor),
+            value_commitment.0.compress_to_field(),
+            nullifier.0,
+            Fq::from_bytes(rk_bytes).map_err(|_| Error::InvalidPublicInput)?,
+        ])
+    }
+}
+
+impl ConstraintSynthesizer<Fq> for SpendCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> Result<(), SynthesisError> {
+        let public_inputs =
+            Self::public_inputs(self.anchor, self.value_commitment, self.nullifier, self.rk)
+                .map_err(|_| SynthesisError::AssignmentMissing)?;
+        let witness = self.witness.as_ref();
+
+        // Public inputs.
+        let [anchor, value_commitment, nullifier, rk] = {
+            let vars = public_inputs
+                .into_iter()
+                .map(|input| FqVar::new_input(cs.clone(), || Ok(input)))
+                .collect::<Result<Vec<_>, _>>()?;
+            <[FqVar; 4]>::try_from(vars).expect("there are four public inputs")
+        };
+
+        // Witnessed note contents.
+        let g_d = ElementVar::new_witness(cs.clone(), || {
+            witness
+                .map(|w| w.g_d)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let pk_d = ElementVar::new_witness(cs.clone(), || {
+            witness
+                .and_then(|w| decaf377::Encoding(w.pk_d.0).decompress().ok())
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let (amount_bits, amount) =
+            gadgets::amount_witness(cs.clone(), witness.map(|w| w.value.amount))?;
+        let asset_id = FqVar::new_witness(cs.clone(), || {
+            witness
+                .map(|w| w.value.asset_id.0)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let note_blinding = FqVar::new_witness(cs.clone(), || {
+            witness
+                .map(|w| w.note_blinding)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // Witnessed keys and randomness.
+        let ak = ElementVar::new_witness(cs.clone(), || {
+            witness
+                .and_then(|w| {
+                    let ak_bytes: [u8; 32] = w.ak.into();
+                    decaf377::Encoding(ak_bytes).decompress().ok()
+                })
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let nk = FqVar::new_witness(cs.clone(), || {
+            witness
+                .map(|w| w.nk.0)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let v_blinding_bits = gadgets::fr_witness_bits(cs.clone(), witness.map(|w| w.v_blinding))?;
+        let spend_auth_randomizer_bits =
+            gadgets::fr_witness_bits(cs.clone(), witness.map(|w| w.spend_auth_randomizer))?;
+
+        // Witnessed position and authentication path.
+        let (position_bits, position) = gadgets::position_witness(
+            cs.clone(),
+            witness.map(|w| w.note_commitment_proof.position()),
+        )?;
+        let auth_path = (0..24)
+            .map(|level| {
+                let siblings = (0..3)
+                    .map(|i| {
+                        FqVar::new_witness(cs.clone(), || {
+                            witness
+                                .map(|w| Fq::from(w.note_commitment_proof.auth_path()[level][i]))
+                                .ok_or(SynthesisError::AssignmentMissing)
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(<[FqVar; 3]>::try_from(siblings).expect("three siblings"))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        // The use of decaf means that we do not need to check that the
+        // diversified basepoint is of small order. However we instead
+        // check it is not identity.
+        gadgets::enforce_not_identity(&g_d)?;
+        gadgets::enforce_not_identity(&ak)?;
+
+        // Note commitment integrity.
+        let note_commitment =
+            gadgets::note_commitment(cs.clone(), &note_blinding, &amount, &asset_id, &g_d, &pk_d)?;
+
+        // Merkle path integrity.  Zero-value notes don't need to be in the
+        // tree, so that dummy spends can be used to pad transactions.
+        let is_nonzero = amount.is_eq(&FqVar::zero())?.not();
+        gadgets::merkle_root(cs.clone(), &note_commitment, &position_bits, &auth_path)?
+            .conditional_enforce_equal(&anchor, &is_nonzero)?;
+
+        // Value commitment integrity.
+        gadgets::value_commitment(cs.clone(), &amount_bits, &asset_id, &v_blinding_bits)?
+            .compress_to_field()?
+            .enforce_equal(&value_commitment)?;
+
+        // Nullifier integrity.
+        gadgets::nullifier(cs.clone(), &nk, &note_commitment, &position)?
+            .enforce_equal(&nullifier)?;
+
+        // Spend authority.
+        let basepoint = ElementVar::new_constant(cs.clone(), decaf377::basepoint())?;
+        let rk_test = ak.clone() + basepoint.scalar_mul_le(spend_auth_randomizer_bits.iter())?;
+        rk_test.compress_to_field()?.enforce_equal(&rk)?;
+
+        // Diversified address integrity.
+        gadgets::transmission_key(cs, &ak, &nk, &g_d)?.enforce_equal(&pk_d)?;
+
+        Ok(())
+    }
+}
+
+/// A Groth16 proof of the spend statement.
+#[derive(Clone, Debug)]
+pub struct SpendProof(Proof<Bls12_377>);
+
+impl SpendProof {
+    /// Prove the spend statement, using the given proving key.
+    pub fn prove<R: RngCore + CryptoRng>(
+        rng: &mut R,
+        pk: &ProvingKey<Bls12_377>,
+        circuit: SpendCircuit,
+    ) -> Result<Self, Error> {
+        Groth16::prove(pk, circuit, rng)
+            .map(Self)
+            .map_err(|_| Error::ProvingFailed)
+    }
+
+    /// Called to verify the proof using the provided public inputs.
+    pub fn verify(
+        &self,
+        vk: &PreparedVerifyingKey<Bls12_377>,
+        anchor: tct::Root,
+        value_commitment: value::Commitment,
+        nullifier: Nullifier,
+        rk: VerificationKey<SpendAuth>,
+    ) -> Result<(), Error> {
+        let public_inputs = SpendCircuit::public_inputs(anchor, value_commitment, nullifier, rk)?;
+        match Groth16::verify_with_processed_vk(vk, &public_inputs, &self.0) {
+            Ok(true) => Ok(()),
+            _ => Err(Error::InvalidProof),
+        }
+    }
+}
+
+impl From<SpendProof> for Vec<u8> {
+    fn from(proof: SpendProof) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        proof
+            .0
+            .serialize(&mut bytes)
+            .expect("can serialize a proof into a vector");
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for SpendProof {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<SpendProof, Self::Error> {
+        Proof::deserialize(bytes)
+            .map(SpendProof)
+            .map_err(|_| Error::ProofMalformed)
+    }
+}
@@ -87,10 +87,13 @@ impl SpendProof {
             return Err(Error::TransmissionKeyMismatch);
         }
 
-        // Merkle path integrity.
-        self.note_commitment_proof
-            .verify(anchor)
-            .map_err(|_| Error::MerkleRootMismatch)?;
+        // Merkle path integrity.  Zero-value notes don't need to be in the
+        // tree, so that dummy spends can be used to pad transactions.
+        if self.value.amount != 0 {
+            self.note_commitment_proof
+                .verify(anchor)
+                .map_err(|_| Error::MerkleRootMismatch)?;
+        }
 
         // Value commitment integrity.
         if self.value.commit(self.v_blinding) != value_commitment {
@@ -12,7 +12,7 @@ pub use balance::BalanceCmd;
 pub use chain::ChainCmd;
 pub use query::QueryCmd;
 pub use stake::StakeCmd;
-pub use tx::TxCmd;
+pub use tx::{verify_disclosure, TxCmd};
 pub use validator::ValidatorCmd;
 pub use wallet::WalletCmd;
 
@@ -50,6 +50,13 @@ pub enum Command {
     /// View chain data.
     #[clap(subcommand)]
     Chain(ChainCmd),
+    /// Verifies a payment disclosure against the chain, printing the disclosed payment.
+    ///
+    /// The disclosed transaction is fetched from pd, so this doesn't need a wallet.
+    VerifyDisclosure {
+        /// The path to a file containing the JSON-encoded payment disclosure.
+        disclosure: String,
+    },
 }
 
 impl Command {
@@ -65,6 +72,7 @@ impl Command {
             Command::Stake(cmd) => cmd.needs_sync(),
             Command::Chain(cmd) => cmd.needs_sync(),
             Command::Q(_) => false,
+            Command::VerifyDisclosure { .. } => false,
         }
     }
 }
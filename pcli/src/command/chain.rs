@@ -81,6 +81,22 @@ impl ChainCmd {
                 "Missed Blocks Max",
                 &format!("{}", params.missed_blocks_maximum),
             ])
+            .add_row(vec![
+                "Max Commission (bps)",
+                &format!("{}", params.max_commission_bps),
+            ])
+            .add_row(vec![
+                "Max Commission Increase Per Epoch (bps)",
+                &format!("{}", params.max_commission_increase_bps_per_epoch),
+            ])
+            .add_row(vec![
+                "Min Self-Delegation",
+                &format!("{}", params.min_self_delegation),
+            ])
+            .add_row(vec![
+                "Max Anchor Age (blocks)",
+                &format!("{}", params.max_anchor_age),
+            ])
             .add_row(vec!["IBC Enabled", &format!("{}", params.ibc_enabled)])
             .add_row(vec![
                 "Inbound ICS-20 Enabled",
@@ -1,8 +1,8 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use jmt::KeyHash;
-use penumbra_chain::{quarantined::Scheduled, CompactBlock, NoteSource};
+use penumbra_chain::{CompactBlock, NoteSource};
 use penumbra_component::shielded_pool::Delible;
-use penumbra_crypto::Nullifier;
+use penumbra_crypto::{asset, Nullifier};
 use penumbra_proto::Protobuf;
 use penumbra_tct::Commitment;
 
@@ -14,10 +14,19 @@ pub enum QueryCmd {
     Key {
         /// The key to query.
         key: String,
+        /// The height at which to query the key, rather than the latest height whose app hash
+        /// has been committed to by a block header.
+        #[clap(long)]
+        height: Option<u64>,
     },
     /// Queries shielded pool data.
     #[clap(subcommand)]
     ShieldedPool(ShieldedPool),
+    /// Queries the current total supply of an asset.
+    Supply {
+        /// The base denomination of the asset to query.
+        denom: String,
+    },
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -27,11 +36,6 @@ pub enum ShieldedPool {
         /// The height to query.
         height: u64,
     },
-    /// Queries the scheduled notes and nullifiers to unquarantine at a given epoch.
-    Scheduled {
-        /// The epoch to query.
-        epoch: u64,
-    },
     /// Queries the source of a given commitment.
     Commitment {
         /// The commitment to query.
@@ -44,12 +48,6 @@ pub enum ShieldedPool {
         #[clap(parse(try_from_str = Nullifier::parse_hex))]
         nullifier: Nullifier,
     },
-    /// Queries the note source of a given quarantined nullifier.
-    QuarantinedNullifier {
-        /// The nullifier to query.
-        #[clap(parse(try_from_str = Nullifier::parse_hex))]
-        nullifier: Nullifier,
-    },
     /// Queries the compact block at a given height.
     CompactBlock { height: u64 },
 }
@@ -58,16 +56,51 @@ impl QueryCmd {
     pub async fn exec(&self, app: &mut App) -> Result<()> {
         let mut client = app.specific_client().await?;
 
+        if let QueryCmd::Supply { denom } = self {
+            let denom = asset::REGISTRY
+                .parse_denom(denom)
+                .ok_or_else(|| anyhow!("{} is not a base denomination", denom))?;
+            let info = client
+                .asset_supply(penumbra_proto::client::specific::AssetSupplyRequest {
+                    asset_id: Some(denom.id().into()),
+                    ..Default::default()
+                })
+                .await?
+                .into_inner();
+
+            println!(
+                "{}{} as of block height {}",
+                denom.default_unit().format_value(info.total_supply),
+                denom.default_unit(),
+                info.as_of_block_height
+            );
+            return Ok(());
+        }
+
         let key_hash = self.key_hash();
 
-        let req = if let QueryCmd::Key { key } = self {
+        // Fetch the root hash that the chain committed to at the requested height (or the latest
+        // height that has one), so that we can check the node's answer against it
+        let height = if let QueryCmd::Key { height, .. } = self {
+            *height
+        } else {
+            None
+        };
+        let (height, trusted_root) =
+            penumbra_view::committed_root(app.tendermint_url.as_str(), height).await?;
+
+        let req = if let QueryCmd::Key { key, .. } = self {
             penumbra_proto::client::specific::KeyValueRequest {
                 key: key.as_bytes().to_vec(),
+                height: Some(height),
+                proof: true,
                 ..Default::default()
             }
         } else {
             penumbra_proto::client::specific::KeyValueRequest {
                 key_hash: key_hash.0.to_vec(),
+                height: Some(height),
+                proof: true,
                 ..Default::default()
             }
         };
@@ -75,14 +108,26 @@ impl QueryCmd {
 
         let rsp = client.key_value(req).await?.into_inner();
 
-        self.display_value(&rsp.value)?;
+        // Check the value against the committed root hash, rather than trusting the node blindly
+        let proven = penumbra_storage::verify_key_value(key_hash, trusted_root, rsp)?;
+        tracing::debug!(
+            height = proven.version,
+            root = %hex::encode(proven.root.0),
+            "verified proof of value"
+        );
+
+        match proven.value {
+            Some(value) => self.display_value(&value)?,
+            None => println!("key not found at height {}", proven.version),
+        }
         Ok(())
     }
 
     fn key_hash(&self) -> KeyHash {
         match self {
-            QueryCmd::Key { key } => key.as_bytes().into(),
+            QueryCmd::Key { key, .. } => key.as_bytes().into(),
             QueryCmd::ShieldedPool(sp) => sp.key_hash(),
+            QueryCmd::Supply { .. } => unreachable!("supply is queried with its own RPC"),
         }
     }
 
@@ -92,6 +137,7 @@ impl QueryCmd {
                 println!("{}", hex::encode(bytes));
             }
             QueryCmd::ShieldedPool(sp) => sp.display_value(bytes)?,
+            QueryCmd::Supply { .. } => unreachable!("supply is queried with its own RPC"),
         }
 
         Ok(())
@@ -104,12 +150,8 @@ impl ShieldedPool {
         match self {
             ShieldedPool::Anchor { height } => state_key::anchor_by_height(height),
             ShieldedPool::CompactBlock { height } => state_key::compact_block(*height),
-            ShieldedPool::Scheduled { epoch } => state_key::scheduled_to_apply(*epoch),
             ShieldedPool::Commitment { commitment } => state_key::note_source(commitment),
             ShieldedPool::Nullifier { nullifier } => state_key::spent_nullifier_lookup(nullifier),
-            ShieldedPool::QuarantinedNullifier { nullifier } => {
-                state_key::quarantined_spent_nullifier_lookup(nullifier)
-            }
         }
     }
 
@@ -123,10 +165,6 @@ impl ShieldedPool {
                 let compact_block = CompactBlock::decode(bytes)?;
                 println!("{}", serde_json::to_string_pretty(&compact_block)?);
             }
-            ShieldedPool::Scheduled { .. } => {
-                let notes = Scheduled::decode(bytes)?;
-                println!("{}", serde_json::to_string_pretty(&notes.scheduled)?);
-            }
             ShieldedPool::Commitment { .. } => {
                 let note_source = Delible::<NoteSource>::decode(bytes)?;
                 println!("{}", serde_json::to_string_pretty(&note_source)?);
@@ -135,10 +173,6 @@ impl ShieldedPool {
                 let note_source = NoteSource::decode(bytes)?;
                 println!("{}", serde_json::to_string_pretty(&note_source)?);
             }
-            ShieldedPool::QuarantinedNullifier { .. } => {
-                let note_source = Delible::<NoteSource>::decode(bytes)?;
-                println!("{}", serde_json::to_string_pretty(&note_source)?);
-            }
         }
         Ok(())
     }
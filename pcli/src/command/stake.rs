@@ -4,8 +4,13 @@ use anyhow::{anyhow, Context, Result};
 use comfy_table::{presets, Table};
 use futures::stream::TryStreamExt;
 use penumbra_component::stake::{rate::RateData, validator};
-use penumbra_crypto::{DelegationToken, IdentityKey, Value, STAKING_TOKEN_ASSET_ID};
-use penumbra_proto::client::oblivious::ValidatorInfoRequest;
+use penumbra_crypto::{
+    DelegationToken, IdentityKey, UnbondingToken, Value, STAKING_TOKEN_ASSET_ID,
+};
+use penumbra_proto::client::{
+    oblivious::ValidatorInfoRequest,
+    specific::{UnbondingPenaltyRequest, ValidatorRateHistoryRequest},
+};
 use penumbra_view::ViewClient;
 use penumbra_wallet::plan;
 use rand_core::OsRng;
@@ -39,6 +44,16 @@ pub enum StakeCmd {
         #[clap(long)]
         source: Option<u64>,
     },
+    /// Claim unbonding tokens from past undelegations as staking tokens,
+    /// once their unbonding period has elapsed.
+    Claim {
+        /// The transaction fee (paid in upenumbra), deducted from each claim.
+        #[clap(long, default_value = "0")]
+        fee: u64,
+        /// Optional. Only claim unbonding tokens received by the given address index.
+        #[clap(long)]
+        source: Option<u64>,
+    },
     /// Redelegate stake from one validator's delegation pool to another.
     Redelegate {
         /// The identity key of the validator to withdraw delegation from.
@@ -58,6 +73,17 @@ pub enum StakeCmd {
     },
     /// Display this wallet's delegations and their value.
     Show,
+    /// Display a validator's exchange rate history and realized APR.
+    Rates {
+        /// The identity key of the validator to display rates for.
+        validator: String,
+        /// The number of past epochs to display.
+        #[clap(long, default_value = "30")]
+        epochs: u64,
+        /// The number of epochs per year, used to annualize the realized return.
+        #[clap(long, default_value = "365")]
+        epochs_per_year: u64,
+    },
     /// Display all of the validators participating in the chain.
     ListValidators {
         /// Whether to show validators that are not currently part of the consensus set.
@@ -71,7 +97,7 @@ pub enum StakeCmd {
 
 impl StakeCmd {
     pub fn needs_sync(&self) -> bool {
-        true
+        !matches!(self, StakeCmd::Rates { .. })
     }
 
     pub async fn exec(&self, app: &mut App) -> Result<()> {
@@ -156,6 +182,7 @@ impl StakeCmd {
                     self_address,
                     *source,
                     None,
+                    false,
                 )
                 .await?;
 
@@ -204,10 +231,75 @@ impl StakeCmd {
                 )
                 .await?;
 
-                // Pass None as the change to await, since the change will be quarantined, so we won't detect it.
-                // But it's not spendable anyways, so we don't need to detect it.
-                let tx = app.build_transaction(undelegate_plan).await?;
-                app.submit_transaction(&tx, None).await?;
+                app.build_and_submit_transaction(undelegate_plan).await?;
+            }
+            StakeCmd::Claim { fee, source } => {
+                let mut client = app.specific_client().await?;
+                let chain_id = app.view().chain_params().await?.chain_id;
+                let asset_cache = app.view().assets().await?;
+
+                // Unbonding tokens are just notes, so we identify them by their denom.
+                let notes = app
+                    .view()
+                    .unspent_notes_by_asset_and_address(app.fvk.hash())
+                    .await?;
+                let mut claimed_any = false;
+                for (asset_id, notes_by_address) in notes {
+                    let token = match asset_cache
+                        .get(&asset_id)
+                        .and_then(|denom| UnbondingToken::try_from(denom.clone()).ok())
+                    {
+                        Some(token) => token,
+                        None => continue,
+                    };
+
+                    let penalty = client
+                        .unbonding_penalty(UnbondingPenaltyRequest {
+                            chain_id: chain_id.clone(),
+                            identity_key: Some(token.validator().into()),
+                            start_epoch_index: token.start_epoch_index(),
+                        })
+                        .await?
+                        .into_inner();
+
+                    if !penalty.claimable {
+                        println!(
+                            "{} can be claimed starting in epoch {}",
+                            token, penalty.claimable_epoch_index
+                        );
+                        continue;
+                    }
+
+                    let unbonding_notes = notes_by_address
+                        .into_iter()
+                        .filter(|(index, _)| {
+                            source.map_or(true, |source| u128::from(*index) == source as u128)
+                        })
+                        .flat_map(|(_, records)| records)
+                        .collect::<Vec<_>>();
+                    if unbonding_notes.is_empty() {
+                        continue;
+                    }
+
+                    let plan = plan::undelegate_claim(
+                        &app.fvk,
+                        &mut app.view,
+                        OsRng,
+                        token.validator(),
+                        token.start_epoch_index(),
+                        penalty.penalty,
+                        unbonding_notes,
+                        *fee,
+                        *source,
+                    )
+                    .await?;
+                    app.build_and_submit_transaction(plan).await?;
+                    claimed_any = true;
+                }
+
+                if !claimed_any {
+                    println!("no unbonding tokens are ready to be claimed");
+                }
             }
             StakeCmd::Redelegate { .. } => {
                 todo!()
@@ -229,8 +321,16 @@ impl StakeCmd {
                     .into_iter()
                     .map(TryInto::try_into)
                     .collect::<Result<Vec<validator::Info>, _>>()?;
+                let validator_name = |identity_key: &IdentityKey| {
+                    validators
+                        .iter()
+                        .find(|v| &v.validator.identity_key == identity_key)
+                        .map(|v| v.validator.name.clone())
+                        .unwrap_or_else(|| identity_key.to_string())
+                };
 
                 let fvk_hash = app.fvk.hash();
+                let positions = app.view().delegations(fvk_hash).await?;
                 let notes = app
                     .view()
                     .unspent_notes_by_asset_and_address(fvk_hash)
@@ -239,47 +339,44 @@ impl StakeCmd {
 
                 let mut table = Table::new();
                 table.load_preset(presets::NOTHING);
-                table.set_header(vec!["Name", "Value", "Exch. Rate", "Tokens"]);
+                table.set_header(vec!["Name", "Value", "Exch. Rate", "Tokens", "Rewards"]);
                 table
                     .get_column_mut(1)
                     .unwrap()
                     .set_cell_alignment(comfy_table::CellAlignment::Right);
 
-                for (asset_id, notes_by_address) in notes.iter() {
-                    let dt = if let Some(Ok(dt)) = asset_cache
-                        .get(asset_id)
-                        .map(|denom| DelegationToken::try_from(denom.clone()))
-                    {
-                        dt
-                    } else {
-                        continue;
-                    };
-
-                    let info = validators
-                        .iter()
-                        .find(|v| v.validator.identity_key == dt.validator())
-                        .unwrap();
-
+                for position in positions.iter().filter(|p| p.delegation_amount > 0) {
                     let delegation = Value {
-                        amount: notes_by_address
-                            .values()
-                            .flat_map(|notes| notes.iter().map(|n| n.note.amount()))
-                            .sum::<u64>(),
-                        asset_id: dt.id(),
+                        amount: position.delegation_amount,
+                        asset_id: DelegationToken::from(&position.identity_key).id(),
                     };
 
                     let unbonded = Value {
-                        amount: info.rate_data.unbonded_amount(delegation.amount),
+                        amount: position.unbonded_amount,
+                        asset_id: *STAKING_TOKEN_ASSET_ID,
+                    };
+
+                    let rewards = Value {
+                        amount: position.accrued_rewards.unsigned_abs(),
                         asset_id: *STAKING_TOKEN_ASSET_ID,
                     };
 
-                    let rate = info.rate_data.validator_exchange_rate as f64 / 1_0000_0000.0;
+                    let rate = position.rate_data.validator_exchange_rate as f64 / 1_0000_0000.0;
 
                     table.add_row(vec![
-                        info.validator.name.clone(),
+                        validator_name(&position.identity_key),
                         unbonded.try_format(&asset_cache).unwrap(),
                         format!("{:.4}", rate),
                         delegation.try_format(&asset_cache).unwrap(),
+                        format!(
+                            "{}{}",
+                            if position.accrued_rewards < 0 {
+                                "-"
+                            } else {
+                                ""
+                            },
+                            rewards.try_format(&asset_cache).unwrap()
+                        ),
                     ]);
 
                     total += unbonded.amount;
@@ -302,6 +399,7 @@ impl StakeCmd {
                     unbonded.try_format(&asset_cache).unwrap(),
                     format!("{:.4}", 1.0),
                     unbonded.try_format(&asset_cache).unwrap(),
+                    String::new(),
                 ]);
 
                 let total = Value {
@@ -314,8 +412,121 @@ impl StakeCmd {
                     total.try_format(&asset_cache).unwrap(),
                     String::new(),
                     String::new(),
+                    String::new(),
                 ]);
                 println!("{}", table);
+
+                let mut pending = Table::new();
+                pending.load_preset(presets::NOTHING);
+                pending.set_header(vec!["Name", "Value", "Claimable Epoch"]);
+                pending
+                    .get_column_mut(1)
+                    .unwrap()
+                    .set_cell_alignment(comfy_table::CellAlignment::Right);
+
+                let mut any_pending = false;
+                for position in &positions {
+                    for undelegation in &position.pending_undelegations {
+                        any_pending = true;
+                        pending.add_row(vec![
+                            validator_name(&position.identity_key),
+                            undelegation
+                                .value
+                                .try_format(&asset_cache)
+                                .unwrap_or_else(|| undelegation.value.amount.to_string()),
+                            undelegation.unbonding_epoch.to_string(),
+                        ]);
+                    }
+                }
+
+                if any_pending {
+                    println!("\nPending undelegations:");
+                    println!("{}", pending);
+                }
+            }
+            StakeCmd::Rates {
+                validator,
+                epochs,
+                epochs_per_year,
+            } => {
+                let identity_key = validator.parse::<IdentityKey>()?;
+
+                let mut client = app.specific_client().await?;
+                let current_rate: RateData = client
+                    .next_validator_rate(tonic::Request::new(identity_key.clone().into()))
+                    .await?
+                    .into_inner()
+                    .try_into()?;
+                // The next rate is for the epoch after the current one.
+                let current_epoch_index = current_rate.epoch_index.saturating_sub(1);
+
+                let history = client
+                    .validator_rate_history(ValidatorRateHistoryRequest {
+                        identity_key: Some(identity_key.into()),
+                        start_epoch_index: current_epoch_index.saturating_sub(*epochs),
+                        end_epoch_index: current_epoch_index,
+                        ..Default::default()
+                    })
+                    .await?
+                    .into_inner()
+                    .try_collect::<Vec<_>>()
+                    .await?
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<Vec<RateData>, _>>()?;
+
+                let mut table = Table::new();
+                table.load_preset(presets::NOTHING);
+                table.set_header(vec!["Epoch", "Reward Rate", "Exch. Rate", "Epoch Return"]);
+
+                for (prev, rate) in std::iter::once(None)
+                    .chain(history.iter().map(Some))
+                    .zip(history.iter())
+                {
+                    // Compute the realized return from the exchange rates,
+                    // rather than using the reward rate, so that slashing
+                    // penalties are included.
+                    let epoch_return = prev
+                        .filter(|prev| prev.epoch_index + 1 == rate.epoch_index)
+                        .map(|prev| {
+                            format!(
+                                "{:.4}%",
+                                100.0
+                                    * (rate.validator_exchange_rate as f64
+                                        / prev.validator_exchange_rate as f64
+                                        - 1.0)
+                            )
+                        })
+                        .unwrap_or_default();
+
+                    table.add_row(vec![
+                        rate.epoch_index.to_string(),
+                        format!(
+                            "{:.4}%",
+                            100.0 * rate.validator_reward_rate as f64 / 1_0000_0000.0
+                        ),
+                        format!("{:.8}", rate.validator_exchange_rate as f64 / 1_0000_0000.0),
+                        epoch_return,
+                    ]);
+                }
+                println!("{}", table);
+
+                match (history.first(), history.last()) {
+                    (Some(first), Some(last)) if last.epoch_index > first.epoch_index => {
+                        // Annualize the growth of the exchange rate over the displayed range.
+                        let growth = last.validator_exchange_rate as f64
+                            / first.validator_exchange_rate as f64;
+                        let elapsed = (last.epoch_index - first.epoch_index) as f64;
+                        let apr = growth.powf(*epochs_per_year as f64 / elapsed) - 1.0;
+                        println!(
+                            "Realized APR over epochs {}..={}: {:.2}%",
+                            first.epoch_index,
+                            last.epoch_index,
+                            100.0 * apr
+                        );
+                    }
+                    _ => println!("Not enough rate history to compute a realized APR."),
+                }
             }
             StakeCmd::ListValidators {
                 show_inactive,
@@ -1,7 +1,26 @@
-use anyhow::Result;
-use penumbra_crypto::Value;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use comfy_table::{presets, Table};
+use penumbra_chain::TransactionLocation;
+use penumbra_crypto::{
+    asset, memo::MemoPlaintext, Asset, FullViewingKey, Note, PaymentDisclosure, Value,
+};
+use penumbra_proto::{
+    client::{
+        explorer::{block_explorer_client::BlockExplorerClient, TransactionsInBlockRequest},
+        oblivious::{oblivious_query_client::ObliviousQueryClient, AssetListRequest},
+        specific::{specific_query_client::SpecificQueryClient, TransactionByHashRequest},
+    },
+    view::TransactionPerspectiveRequest,
+};
+use penumbra_transaction::{
+    action::Output, Action, ActionView, OutputView, SpendView, Transaction, TransactionView,
+};
+use penumbra_view::ViewClient;
 use penumbra_wallet::plan;
 use rand_core::OsRng;
+use url::Url;
 
 use crate::App;
 
@@ -23,6 +42,10 @@ pub enum TxCmd {
         /// Optional. Set the transaction's memo field to the provided text.
         #[clap(long)]
         memo: Option<String>,
+        /// Optional. Pad the transaction with dummy spends and outputs, so
+        /// that their counts are rounded up to the next power of two.
+        #[clap(long)]
+        pad: bool,
     },
     /// Sweeps small notes of the same denomination into a few larger notes.
     ///
@@ -33,6 +56,23 @@ pub enum TxCmd {
     ///
     /// Currently, only zero-fee sweep transactions are implemented.
     Sweep,
+    /// Views a transaction from the perspective of this wallet, decrypting
+    /// the outputs and spends that it can see.
+    View {
+        /// The hex-encoded hash of a committed transaction, or the path to a
+        /// file containing an encoded transaction.
+        tx: String,
+    },
+    /// Discloses a single output of a transaction to a third party.
+    ///
+    /// Prints a payment disclosure, which lets whoever holds it decrypt that
+    /// output and nothing else, without access to this wallet's keys.
+    Disclose {
+        /// The hex-encoded hash of the transaction.
+        tx_hash: String,
+        /// The index of the output to disclose, among the transaction's outputs.
+        output_index: u64,
+    },
 }
 
 impl TxCmd {
@@ -41,6 +81,8 @@ impl TxCmd {
         match self {
             TxCmd::Send { .. } => true,
             TxCmd::Sweep { .. } => true,
+            TxCmd::View { .. } => true,
+            TxCmd::Disclose { .. } => true,
         }
     }
 
@@ -52,6 +94,7 @@ impl TxCmd {
                 fee,
                 source: from,
                 memo,
+                pad,
             } => {
                 // Parse all of the values provided.
                 let values = values
@@ -71,6 +114,7 @@ impl TxCmd {
                     to,
                     *from,
                     memo.clone(),
+                    *pad,
                 )
                 .await?;
                 app.build_and_submit_transaction(plan).await?;
@@ -79,11 +123,242 @@ impl TxCmd {
                 todo!("port to new API");
                 //sweep(opt, state).await?;
             }
+            TxCmd::View { tx } => {
+                let mut request = TransactionPerspectiveRequest {
+                    fvk_hash: Some(app.fvk.hash().into()),
+                    ..Default::default()
+                };
+                if Path::new(tx).exists() {
+                    let bytes = std::fs::read(tx)
+                        .with_context(|| format!("cannot read transaction from {}", tx))?;
+                    request.tx = Some(Transaction::try_from(&bytes[..])?.into());
+                } else {
+                    request.tx_hash = hex::decode(tx)
+                        .map_err(|_| anyhow!("{} is neither a file nor a transaction hash", tx))?;
+                }
+
+                let (transaction, perspective) =
+                    app.view().transaction_perspective(request).await?;
+                let view = TransactionView::new(transaction, &app.fvk, &perspective);
+                let asset_cache = app.view().assets().await?;
+
+                print_transaction_view(&view, &app.fvk, &asset_cache);
+            }
+            TxCmd::Disclose {
+                tx_hash,
+                output_index,
+            } => {
+                let tx_hash = hex::decode(tx_hash)
+                    .map_err(|_| anyhow!("{} is not a transaction hash", tx_hash))?;
+                let (transaction, perspective) = app
+                    .view()
+                    .transaction_perspective(TransactionPerspectiveRequest {
+                        fvk_hash: Some(app.fvk.hash().into()),
+                        tx_hash,
+                        tx: None,
+                    })
+                    .await?;
+
+                let output = nth_output(&transaction, *output_index)?;
+                let payload_key = perspective
+                    .payload_keys
+                    .get(&output.body.note_payload.note_commitment)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "output {} was neither sent to nor from this wallet",
+                            output_index
+                        )
+                    })?;
+
+                let disclosure = PaymentDisclosure {
+                    tx_hash: transaction.id(),
+                    output_index: *output_index,
+                    payload_key: *payload_key,
+                };
+                println!("{}", serde_json::to_string_pretty(&disclosure)?);
+            }
         }
         Ok(())
     }
 }
 
+/// Verify the payment disclosure in the file at `disclosure_path` against the
+/// transaction it discloses, fetched from pd at `pd_url`.
+///
+/// This needs no wallet, since the disclosure's payload key is all that's
+/// needed to decrypt the disclosed output.
+pub async fn verify_disclosure(pd_url: &Url, disclosure_path: &str) -> Result<()> {
+    let disclosure: PaymentDisclosure = serde_json::from_slice(
+        &std::fs::read(disclosure_path)
+            .with_context(|| format!("cannot read disclosure from {}", disclosure_path))?,
+    )?;
+
+    let mut client = SpecificQueryClient::connect(pd_url.as_ref().to_owned()).await?;
+    let location: TransactionLocation = client
+        .transaction_by_hash(TransactionByHashRequest {
+            chain_id: String::new(),
+            tx_hash: disclosure.tx_hash.to_vec(),
+        })
+        .await?
+        .into_inner()
+        .try_into()?;
+
+    let mut client = BlockExplorerClient::connect(pd_url.as_ref().to_owned()).await?;
+    let info = client
+        .transactions_in_block(TransactionsInBlockRequest {
+            height: location.height,
+            offset: location.index,
+            limit: 1,
+        })
+        .await?
+        .into_inner()
+        .transactions
+        .pop()
+        .ok_or_else(|| anyhow!("transaction missing from block {}", location.height))?;
+    let transaction = Transaction::try_from(&info.raw[..])?;
+    if transaction.id() != disclosure.tx_hash {
+        return Err(anyhow!("pd returned the wrong transaction"));
+    }
+
+    let output = nth_output(&transaction, disclosure.output_index)?;
+    let note = disclosure.verify(&output.body.note_payload)?;
+    let memo = disclosure.decrypt_memo(&output.body.encrypted_memo)?;
+
+    let mut client = ObliviousQueryClient::connect(pd_url.as_ref().to_owned()).await?;
+    let asset_cache = client
+        .asset_list(AssetListRequest {
+            chain_id: String::new(),
+        })
+        .await?
+        .into_inner()
+        .assets
+        .into_iter()
+        .map(|asset| Ok(Asset::try_from(asset)?.denom))
+        .collect::<Result<asset::Cache>>()?;
+
+    println!(
+        "Output {} of transaction {} (block {}) pays {}",
+        disclosure.output_index,
+        hex::encode(disclosure.tx_hash),
+        location.height,
+        format_value(note.value(), &asset_cache),
+    );
+    let memo = memo_text(&memo);
+    if !memo.is_empty() {
+        println!("Memo: {:?}", memo);
+    }
+
+    Ok(())
+}
+
+/// The output of `transaction` at `index` among its outputs.
+fn nth_output(transaction: &Transaction, index: u64) -> Result<&Output> {
+    transaction
+        .outputs()
+        .nth(index as usize)
+        .ok_or_else(|| anyhow!("transaction has no output {}", index))
+}
+
+fn format_value(value: Value, cache: &asset::Cache) -> String {
+    value
+        .try_format(cache)
+        .unwrap_or_else(|| format!("{} {}", value.amount, value.asset_id))
+}
+
+/// The text of a memo, without its zero padding.
+fn memo_text(memo: &MemoPlaintext) -> String {
+    String::from_utf8_lossy(&memo.0)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+/// Render a human-readable summary of a [`TransactionView`].
+fn print_transaction_view(view: &TransactionView, fvk: &FullViewingKey, cache: &asset::Cache) {
+    // Describes the owner of a note, if it's one of our addresses.
+    let our_address = |note: &Note| {
+        let ivk = fvk.incoming();
+        if ivk.diversified_public(&note.diversified_generator()) == note.transmission_key() {
+            Some(u128::from(ivk.index_for_diversifier(&note.diversifier())))
+        } else {
+            None
+        }
+    };
+
+    let mut table = Table::new();
+    table.load_preset(presets::NOTHING);
+    table.set_header(vec!["Action", "Description"]);
+    for action in &view.actions {
+        let (name, description) = match action {
+            ActionView::Spend(SpendView::Visible { note, .. }) => (
+                "Spend",
+                match our_address(note) {
+                    Some(index) => format!(
+                        "{} from address {}",
+                        format_value(note.value(), cache),
+                        index
+                    ),
+                    None => format_value(note.value(), cache),
+                },
+            ),
+            ActionView::Spend(SpendView::Opaque { .. }) => ("Spend", "[hidden]".to_string()),
+            ActionView::Output(OutputView::Visible { note, memo, .. }) => {
+                let mut description = match our_address(note) {
+                    Some(index) => {
+                        format!("{} to address {}", format_value(note.value(), cache), index)
+                    }
+                    None => format!("{} to another wallet", format_value(note.value(), cache)),
+                };
+                let memo = memo_text(memo);
+                if !memo.is_empty() {
+                    description.push_str(&format!(" with memo {:?}", memo));
+                }
+                ("Output", description)
+            }
+            ActionView::Output(OutputView::Opaque { .. }) => ("Output", "[hidden]".to_string()),
+            ActionView::Public(Action::Delegate(delegate)) => (
+                "Delegate",
+                format!(
+                    "{}upenumbra to {}",
+                    delegate.unbonded_amount, delegate.validator_identity
+                ),
+            ),
+            ActionView::Public(Action::Undelegate(undelegate)) => (
+                "Undelegate",
+                format!(
+                    "{}upenumbra from {}",
+                    undelegate.unbonded_amount, undelegate.validator_identity
+                ),
+            ),
+            ActionView::Public(Action::UndelegateClaim(claim)) => (
+                "Undelegate Claim",
+                format!("from {}", claim.validator_identity),
+            ),
+            ActionView::Public(Action::ValidatorDefinition(_)) => {
+                ("Validator Definition", String::new())
+            }
+            ActionView::Public(Action::IBCAction(_)) => ("IBC Action", String::new()),
+            ActionView::Public(Action::Spend(spend)) => {
+                ("Spend", format!("nullifier {}", spend.body.nullifier))
+            }
+            ActionView::Public(Action::Output(output)) => (
+                "Output",
+                format!(
+                    "note commitment {}",
+                    output.body.note_payload.note_commitment
+                ),
+            ),
+        };
+        table.add_row(vec![name.to_string(), description]);
+    }
+
+    println!("Transaction {}", hex::encode(view.id));
+    println!("{}", table);
+    println!("Fee: {}upenumbra", view.fee.0);
+    if view.expiry_height != 0 {
+        println!("Expires after height {}", view.expiry_height);
+    }
+}
+
 // TODO: port to new API
 /*
 
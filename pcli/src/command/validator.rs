@@ -1,10 +1,17 @@
 use std::{fs::File, io::Write};
 
 use anyhow::{Context, Result};
+use comfy_table::{presets, Table};
 use futures::TryStreamExt;
-use penumbra_component::stake::{validator, validator::Validator, FundingStream, FundingStreams};
-use penumbra_crypto::IdentityKey;
-use penumbra_proto::{stake::Validator as ProtoValidator, Message};
+use penumbra_chain::Epoch;
+use penumbra_component::stake::{
+    rate::RateData, validator, validator::Validator, FundingStream, FundingStreams, Uptime,
+};
+use penumbra_crypto::{IdentityKey, Value, STAKING_TOKEN_ASSET_ID};
+use penumbra_proto::{
+    client::specific::ValidatorUptimeRequest, stake::Validator as ProtoValidator, Message,
+};
+use penumbra_view::ViewClient;
 use penumbra_wallet::plan;
 use rand_core::OsRng;
 
@@ -19,6 +26,25 @@ pub enum ValidatorCmd {
         /// The JSON file containing the ValidatorDefinition to upload
         #[clap(long)]
         file: String,
+        /// Optional. An amount of stake to add to the validator's self-bond in
+        /// the same transaction. New validators must self-bond at least the
+        /// chain's minimum self-delegation when uploading their definition.
+        #[clap(long)]
+        self_delegation: Option<String>,
+        /// The transaction fee (paid in upenumbra).
+        #[clap(long, default_value = "0")]
+        fee: u64,
+        /// Optional. Only spend funds originally received by the given address index.
+        #[clap(long)]
+        source: Option<u64>,
+    },
+    /// Withdraw delegation tokens from this wallet's validator's self-bond.
+    ///
+    /// The self-bond can't be withdrawn below the chain's minimum
+    /// self-delegation unless the validator is disabled.
+    UndelegateSelfBond {
+        /// The amount of delegation tokens to withdraw.
+        amount: u64,
         /// The transaction fee (paid in upenumbra).
         #[clap(long, default_value = "0")]
         fee: u64,
@@ -43,6 +69,15 @@ pub enum ValidatorCmd {
         /// The identity key of the validator to fetch.
         identity_key: String,
     },
+    /// Displays a validator's uptime over the signed blocks window, and how
+    /// close it is to being jailed for downtime.
+    Uptime {
+        /// The identity key of the validator to display uptime for.
+        identity_key: String,
+        /// Whether to list the heights of the missed blocks.
+        #[clap(short, long)]
+        detailed: bool,
+    },
 }
 
 impl ValidatorCmd {
@@ -50,8 +85,10 @@ impl ValidatorCmd {
         match self {
             ValidatorCmd::Identity => false,
             ValidatorCmd::UploadDefinition { .. } => true,
+            ValidatorCmd::UndelegateSelfBond { .. } => true,
             ValidatorCmd::TemplateDefinition { .. } => false,
             ValidatorCmd::FetchDefinition { .. } => false,
+            ValidatorCmd::Uptime { .. } => false,
         }
     }
 
@@ -65,7 +102,12 @@ impl ValidatorCmd {
 
                 println!("{}", ik);
             }
-            ValidatorCmd::UploadDefinition { file, fee, source } => {
+            ValidatorCmd::UploadDefinition {
+                file,
+                self_delegation,
+                fee,
+                source,
+            } => {
                 // The definitions are stored in a JSON document,
                 // however for ease of use it's best for us to generate
                 // the signature here based on the configured wallet.
@@ -84,20 +126,93 @@ impl ValidatorCmd {
                 let protobuf_serialized: ProtoValidator = new_validator.clone().into();
                 let v_bytes = protobuf_serialized.encode_to_vec();
                 let auth_sig = sk.spend_auth_key().sign(&mut OsRng, &v_bytes);
+                let identity_key = new_validator.identity_key.clone();
                 let vd = validator::Definition {
                     validator: new_validator,
                     auth_sig,
                 };
+
+                let self_delegation = if let Some(amount) = self_delegation {
+                    let Value { amount, asset_id } = amount.parse::<Value>()?;
+                    if asset_id != *STAKING_TOKEN_ASSET_ID {
+                        return Err(anyhow::anyhow!(
+                            "staking can only be done with the staking token"
+                        ));
+                    }
+
+                    let mut client = app.specific_client().await?;
+                    let rate_data: RateData = match client
+                        .next_validator_rate(tonic::Request::new(identity_key.clone().into()))
+                        .await
+                    {
+                        Ok(rsp) => rsp.into_inner().try_into()?,
+                        // A validator being defined for the first time has no
+                        // rates yet, and will be added with an exchange rate of 1
+                        // for the next epoch.
+                        Err(e) if e.code() == tonic::Code::NotFound => {
+                            let fvk_hash = app.fvk.hash();
+                            let view: &mut dyn ViewClient = &mut app.view;
+                            let sync_height = view.status(fvk_hash).await?.sync_height;
+                            let epoch_duration = view.chain_params().await?.epoch_duration;
+                            RateData {
+                                identity_key,
+                                epoch_index: Epoch::from_height(sync_height, epoch_duration).index
+                                    + 1,
+                                validator_reward_rate: 0,
+                                validator_exchange_rate: 1_0000_0000,
+                            }
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+                    Some((rate_data, amount))
+                } else {
+                    None
+                };
+
                 // Construct a new transaction and include the validator definition.
-                let plan =
-                    plan::validator_definition(&app.fvk, &mut app.view, OsRng, vd, *fee, *source)
-                        .await?;
+                let plan = plan::validator_definition(
+                    &app.fvk,
+                    &mut app.view,
+                    OsRng,
+                    vd,
+                    self_delegation,
+                    *fee,
+                    *source,
+                )
+                .await?;
                 app.build_and_submit_transaction(plan).await?;
                 // Only commit the state if the transaction was submitted
                 // successfully, so that we don't store pending notes that will
                 // never appear on-chain.
                 println!("Uploaded validator definition");
             }
+            ValidatorCmd::UndelegateSelfBond {
+                amount,
+                fee,
+                source,
+            } => {
+                let identity_key = IdentityKey(fvk.spend_verification_key().clone());
+
+                let mut client = app.specific_client().await?;
+                let rate_data: RateData = client
+                    .next_validator_rate(tonic::Request::new(identity_key.into()))
+                    .await?
+                    .into_inner()
+                    .try_into()?;
+
+                let plan = plan::undelegate_self_bond(
+                    &app.fvk,
+                    &mut app.view,
+                    OsRng,
+                    rate_data,
+                    *amount,
+                    *fee,
+                    *source,
+                )
+                .await?;
+                app.build_and_submit_transaction(plan).await?;
+                println!("Withdrew {} delegation tokens from self-bond", amount);
+            }
             ValidatorCmd::TemplateDefinition { file } => {
                 let (address, _dtk) = fvk.incoming().payment_address(0u64.into());
                 let identity_key = IdentityKey(fvk.spend_verification_key().clone());
@@ -179,6 +294,69 @@ impl ValidatorCmd {
                     .write_all(&serde_json::to_vec_pretty(&validator)?)
                     .context("could not write file")?;
             }
+            ValidatorCmd::Uptime {
+                identity_key,
+                detailed,
+            } => {
+                let identity_key = identity_key.parse::<IdentityKey>()?;
+
+                let mut client = app.specific_client().await?;
+                let rsp = client
+                    .validator_uptime(ValidatorUptimeRequest {
+                        identity_key: Some(identity_key.into()),
+                        ..Default::default()
+                    })
+                    .await?
+                    .into_inner();
+
+                let uptime: Uptime = rsp
+                    .uptime
+                    .ok_or_else(|| anyhow::anyhow!("missing uptime in response"))?
+                    .try_into()?;
+                let window_len = uptime.window_len() as u64;
+                let signed_blocks = window_len - rsp.missed_blocks;
+
+                let mut table = Table::new();
+                table.load_preset(presets::NOTHING);
+                table
+                    .set_header(vec!["", ""])
+                    .add_row(vec![
+                        "As Of Height".to_string(),
+                        uptime.as_of_block_height().to_string(),
+                    ])
+                    .add_row(vec![
+                        "Signed Blocks".to_string(),
+                        format!(
+                            "{}/{} ({:.2}%)",
+                            signed_blocks,
+                            window_len,
+                            100.0 * signed_blocks as f64 / window_len as f64
+                        ),
+                    ])
+                    .add_row(vec![
+                        "Missed Blocks".to_string(),
+                        rsp.missed_blocks.to_string(),
+                    ])
+                    .add_row(vec![
+                        "Missed Blocks Maximum".to_string(),
+                        rsp.missed_blocks_maximum.to_string(),
+                    ])
+                    .add_row(vec![
+                        "Remaining Before Jailing".to_string(),
+                        rsp.missed_blocks_maximum
+                            .saturating_sub(rsp.missed_blocks)
+                            .to_string(),
+                    ]);
+                println!("{}", table);
+
+                if *detailed {
+                    let missed = uptime
+                        .missed_block_heights()
+                        .map(|height| height.to_string())
+                        .collect::<Vec<_>>();
+                    println!("Missed block heights: [{}]", missed.join(", "));
+                }
+            }
         }
 
         Ok(())
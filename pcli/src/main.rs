@@ -101,6 +101,13 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Verifying a payment disclosure doesn't need a wallet, so it's also
+    // handled before building the `App`.
+    if let Command::VerifyDisclosure { disclosure } = &opt.cmd {
+        command::verify_disclosure(&opt.pd_url()?, disclosure).await?;
+        return Ok(());
+    }
+
     let (mut app, cmd) = opt.into_app().await?;
 
     if cmd.needs_sync() {
@@ -123,6 +130,9 @@ async fn main() -> Result<()> {
         Command::Stake(cmd) => cmd.exec(&mut app).await?,
         Command::Chain(cmd) => cmd.exec(&mut app).await?,
         Command::Q(cmd) => cmd.exec(&mut app).await?,
+        Command::VerifyDisclosure { disclosure } => {
+            command::verify_disclosure(&app.pd_url, disclosure).await?
+        }
     }
 
     Ok(())
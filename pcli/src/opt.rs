@@ -8,7 +8,8 @@ use anyhow::{Context, Result};
 use camino::Utf8PathBuf;
 use clap::Parser;
 use directories::ProjectDirs;
-use penumbra_crypto::FullViewingKey;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use penumbra_crypto::{proofs::groth16, FullViewingKey};
 use penumbra_custody::SoftHSM;
 use penumbra_proto::{
     custody::{
@@ -55,6 +56,14 @@ pub struct Opt {
     /// The filter for `pcli`'s log messages.
     #[clap( long, default_value_t = EnvFilter::new("warn"), env = "RUST_LOG")]
     trace_filter: EnvFilter,
+    /// If set, load Groth16 proving keys from this directory and create
+    /// Groth16 proofs instead of transparent ones.
+    #[clap(long, env = "PENUMBRA_PROOF_PARAMS")]
+    proof_params: Option<Utf8PathBuf>,
+    /// If set, serve metrics from the local view service on this port of
+    /// localhost while `pcli` runs.
+    #[clap(long, env = "PENUMBRA_PCLI_METRICS_PORT")]
+    metrics_port: Option<u16>,
 }
 
 impl Opt {
@@ -68,6 +77,20 @@ impl Opt {
         // Create the data directory if it is missing.
         std::fs::create_dir_all(&self.data_path).context("Failed to create data directory")?;
 
+        if let Some(metrics_port) = self.metrics_port {
+            PrometheusBuilder::new()
+                .with_http_listener(SocketAddr::from(([127, 0, 0, 1], metrics_port)))
+                .install()
+                .context("Failed to install prometheus exporter")?;
+            penumbra_view::register_metrics();
+        }
+
+        if let Some(proof_params) = &self.proof_params {
+            let keys = groth16::ProvingKeys::load(proof_params.as_std_path())
+                .context("Failed to load Groth16 proving keys")?;
+            groth16::install_proving_keys(keys)?;
+        }
+
         let custody_path = self.data_path.join(crate::CUSTODY_FILE_NAME);
         let legacy_wallet_path = self.data_path.join(legacy::WALLET_FILE_NAME);
 
@@ -89,13 +112,8 @@ impl Opt {
         // ...and the view service...
         let view = self.view_client(&fvk).await?;
 
-        let mut tendermint_url = format!("http://{}", self.node)
-            .parse::<Url>()
-            .with_context(|| format!("Invalid node URL: {}", self.node))?;
-        let mut pd_url = tendermint_url.clone();
-        pd_url
-            .set_port(Some(self.pd_port))
-            .expect("pd URL will not be `file://`");
+        let pd_url = self.pd_url()?;
+        let mut tendermint_url = self.node_url()?;
         tendermint_url
             .set_port(Some(self.tendermint_port))
             .expect("tendermint URL will not be `file://`");
@@ -111,6 +129,21 @@ impl Opt {
         Ok((app, self.cmd))
     }
 
+    /// The URL of pd's gRPC server.
+    pub fn pd_url(&self) -> Result<Url> {
+        let mut pd_url = self.node_url()?;
+        pd_url
+            .set_port(Some(self.pd_port))
+            .expect("pd URL will not be `file://`");
+        Ok(pd_url)
+    }
+
+    fn node_url(&self) -> Result<Url> {
+        format!("http://{}", self.node)
+            .parse::<Url>()
+            .with_context(|| format!("Invalid node URL: {}", self.node))
+    }
+
     /// Constructs a [`ViewProtocolClient`] based on the command-line options.
     async fn view_client(
         &self,
@@ -16,7 +16,7 @@ use tower_abci::BoxError;
 use tracing::error_span;
 
 use super::{Message, Worker};
-use crate::RequestExt;
+use crate::{stateless::StatelessChecker, RequestExt};
 
 #[derive(Clone)]
 pub struct Consensus {
@@ -24,17 +24,24 @@ pub struct Consensus {
 }
 
 impl Consensus {
-    pub async fn new(storage: Storage) -> anyhow::Result<(Self, watch::Receiver<block::Height>)> {
-        let (queue_tx, queue_rx) = mpsc::channel(10);
+    pub async fn new(
+        storage: Storage,
+        checker: StatelessChecker,
+    ) -> anyhow::Result<(Self, watch::Receiver<block::Height>)> {
+        // Tendermint pipelines DeliverTx requests, so a deeper queue lets the
+        // worker verify the signatures of more transactions in each batch.
+        let (queue_tx, queue_rx) = mpsc::channel(128);
         let initial_height = match storage.latest_version().await? {
             Some(version) => version.try_into().unwrap(),
             _ => 0u32.into(),
         };
         let (height_tx, height_rx) = watch::channel(initial_height);
 
-        tokio::task::Builder::new()
-            .name("consensus::Worker")
-            .spawn(Worker::new(storage, queue_rx, height_tx).await?.run());
+        tokio::task::Builder::new().name("consensus::Worker").spawn(
+            Worker::new(storage, queue_rx, height_tx, checker)
+                .await?
+                .run(),
+        );
 
         Ok((
             Self {
@@ -1,9 +1,11 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
+use bytes::Bytes;
 
-use penumbra_proto::Protobuf;
+use penumbra_proto::{client::explorer::BlockTransaction, Message as _, Protobuf};
 
-use penumbra_chain::genesis;
+use penumbra_chain::{genesis, params::ChainParams, View as _};
 use penumbra_component::{Component, Context};
+use penumbra_crypto::proofs::groth16;
 use penumbra_storage::Storage;
 use penumbra_transaction::Transaction;
 use tendermint::{
@@ -14,99 +16,164 @@ use tokio::sync::{mpsc, watch};
 use tracing::{instrument, Instrument};
 
 use super::Message;
-use crate::App;
+use crate::{
+    stateless::{Pending, StatelessChecker},
+    App,
+};
 
 pub struct Worker {
     queue: mpsc::Receiver<Message>,
     height_tx: watch::Sender<block::Height>,
     storage: Storage,
     app: App,
+    checker: StatelessChecker,
+    /// The transactions delivered so far in the current block, with the
+    /// results of executing them.
+    block_transactions: Vec<BlockTransaction>,
 }
 
 impl Worker {
-    #[instrument(skip(storage, queue, height_tx), name = "consensus::Worker::new")]
+    #[instrument(
+        skip(storage, queue, height_tx, checker),
+        name = "consensus::Worker::new"
+    )]
     pub async fn new(
         storage: Storage,
         queue: mpsc::Receiver<Message>,
         height_tx: watch::Sender<block::Height>,
+        checker: StatelessChecker,
     ) -> Result<Self> {
         let app = App::new(storage.clone()).await;
 
+        // A node restarting on an initialized chain doesn't see InitChain
+        // again, so it has to take the verifying keys from its state.
+        if storage.latest_version().await?.is_some() {
+            let params = storage.state().await?.get_chain_params().await?;
+            install_verifying_keys(&params)?;
+        }
+
         Ok(Self {
             queue,
             height_tx,
             storage,
             app,
+            checker,
+            block_transactions: Vec::new(),
         })
     }
 
     pub async fn run(mut self) -> Result<()> {
-        while let Some(Message {
-            req,
-            rsp_sender,
-            span,
-        }) = self.queue.recv().await
-        {
-            // The send only fails if the receiver was dropped, which happens
-            // if the caller didn't propagate the message back to tendermint
-            // for some reason -- but that's not our problem.
-            let _ = rsp_sender.send(match req {
-                Request::InitChain(init_chain) => Response::InitChain(
-                    self.init_chain(init_chain)
-                        .instrument(span)
-                        .await
-                        .expect("init_chain must succeed"),
-                ),
-                Request::BeginBlock(begin_block) => Response::BeginBlock(
-                    self.begin_block(begin_block)
-                        .instrument(span)
-                        .await
-                        .expect("begin_block must succeed"),
-                ),
-                Request::DeliverTx(deliver_tx) => {
-                    let ctx = Context::new();
-                    let rsp = self
-                        .deliver_tx(ctx.clone(), deliver_tx)
-                        .instrument(span.clone())
-                        .await;
-                    span.in_scope(|| {
-                        Response::DeliverTx(match rsp {
-                            Ok(()) => {
-                                tracing::info!("deliver_tx succeeded");
-                                abci::response::DeliverTx {
-                                    events: ctx.into_events(),
-                                    ..Default::default()
-                                }
-                            }
-                            Err(e) => {
-                                tracing::info!(?e, "deliver_tx failed");
-                                abci::response::DeliverTx {
-                                    code: 1,
-                                    log: e.to_string(),
-                                    events: ctx.into_events(),
-                                    ..Default::default()
-                                }
-                            }
-                        })
-                    })
-                }
-                Request::EndBlock(end_block) => Response::EndBlock(
-                    self.end_block(end_block)
-                        .instrument(span)
-                        .await
-                        .expect("end_block must succeed"),
-                ),
-                Request::Commit => Response::Commit(
-                    self.commit()
-                        .instrument(span)
-                        .await
-                        .expect("commit must succeed"),
-                ),
+        while let Some(message) = self.queue.recv().await {
+            // Tendermint pipelines DeliverTx requests, so by the time we
+            // receive one, more are usually waiting behind it.  Drain the queue
+            // so that all of their signatures can be verified in one batch.
+            let mut messages = vec![message];
+            while let Ok(message) = self.queue.try_recv() {
+                messages.push(message);
+            }
+            let tx_bytes = messages
+                .iter()
+                .map(|message| match &message.req {
+                    Request::DeliverTx(deliver_tx) => Some(deliver_tx.tx.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let prechecked =
+                tokio::task::spawn_blocking(move || precheck_transactions(tx_bytes)).await?;
+
+            // Start the rest of the stateless checks concurrently; they are
+            // awaited in order as each transaction is delivered.
+            let transactions = prechecked.into_iter().map(|transaction| {
+                transaction.map(|transaction| match transaction {
+                    Ok(transaction) => self.checker.check_except_signatures(transaction),
+                    Err(e) => Pending::ready(Err(e)),
+                })
             });
+            let transactions = transactions.collect::<Vec<_>>();
+
+            for (message, transaction) in messages.into_iter().zip(transactions) {
+                self.handle(message, transaction).await;
+            }
         }
         Ok(())
     }
 
+    /// Handles a single message, given its pre-checked transaction if it was a
+    /// DeliverTx request.
+    async fn handle(
+        &mut self,
+        Message {
+            req,
+            rsp_sender,
+            span,
+        }: Message,
+        transaction: Option<Pending>,
+    ) {
+        // The send only fails if the receiver was dropped, which happens
+        // if the caller didn't propagate the message back to tendermint
+        // for some reason -- but that's not our problem.
+        let _ = rsp_sender.send(match req {
+            Request::InitChain(init_chain) => Response::InitChain(
+                self.init_chain(init_chain)
+                    .instrument(span)
+                    .await
+                    .expect("init_chain must succeed"),
+            ),
+            Request::BeginBlock(begin_block) => Response::BeginBlock(
+                self.begin_block(begin_block)
+                    .instrument(span)
+                    .await
+                    .expect("begin_block must succeed"),
+            ),
+            Request::DeliverTx(deliver_tx) => {
+                let ctx = Context::new();
+                let transaction = transaction.expect("DeliverTx requests are always pre-checked");
+                let rsp = self
+                    .deliver_tx(ctx.clone(), transaction)
+                    .instrument(span.clone())
+                    .await;
+                let rsp = span.in_scope(|| match rsp {
+                    Ok(()) => {
+                        tracing::info!("deliver_tx succeeded");
+                        abci::response::DeliverTx {
+                            events: ctx.into_events(),
+                            ..Default::default()
+                        }
+                    }
+                    Err(e) => {
+                        tracing::info!(?e, "deliver_tx failed");
+                        abci::response::DeliverTx {
+                            code: 1,
+                            log: e.to_string(),
+                            events: ctx.into_events(),
+                            ..Default::default()
+                        }
+                    }
+                });
+                // Failed transactions are still part of the block, so they're
+                // recorded too, along with why they failed.
+                self.block_transactions.push(BlockTransaction {
+                    raw: deliver_tx.tx.to_vec(),
+                    code: rsp.code,
+                    log: rsp.log.clone(),
+                });
+                Response::DeliverTx(rsp)
+            }
+            Request::EndBlock(end_block) => Response::EndBlock(
+                self.end_block(end_block)
+                    .instrument(span)
+                    .await
+                    .expect("end_block must succeed"),
+            ),
+            Request::Commit => Response::Commit(
+                self.commit()
+                    .instrument(span)
+                    .await
+                    .expect("commit must succeed"),
+            ),
+        });
+    }
+
     /// Initializes the chain based on the genesis data.
     ///
     /// The genesis data is provided by tendermint, and is used to initialize
@@ -124,7 +191,14 @@ impl Worker {
         if self.storage.latest_version().await?.is_some() {
             return Err(anyhow!("database already initialized"));
         }
+        install_verifying_keys(&app_state.chain_params)?;
         self.app.init_chain(&app_state).await;
+        // Genesis issuance is checked like any other block's, but there's
+        // nowhere to report its events.
+        self.app
+            .check_supply_changes(Context::new())
+            .await
+            .context("genesis supply changes are inconsistent")?;
 
         // Extract the Tendermint validators from the app state
         //
@@ -171,16 +245,15 @@ impl Worker {
     /// We must perform all checks again here even though they are performed in `CheckTx`, as a
     /// Byzantine node may propose a block containing double spends or other disallowed behavior,
     /// so it is not safe to assume all checks performed in `CheckTx` were done.
-    async fn deliver_tx(
-        &mut self,
-        ctx: Context,
-        deliver_tx: abci::request::DeliverTx,
-    ) -> Result<()> {
-        // Verify the transaction is well-formed...
-        let transaction = Transaction::decode(deliver_tx.tx)?;
-        // ... and statelessly valid...
-        App::check_tx_stateless(ctx.clone(), &transaction)?;
-        // ... and statefully valid.
+    ///
+    /// The transaction has already been decoded and had its signatures verified
+    /// by [`precheck_transactions`], in a batch with other queued transactions,
+    /// and its remaining stateless checks are running on the [`StatelessChecker`].
+    async fn deliver_tx(&mut self, ctx: Context, transaction: Pending) -> Result<()> {
+        // Wait for the transaction to be found well-formed, correctly signed,
+        // and statelessly valid...
+        let transaction = transaction.wait().await?;
+        // ... and check that it's statefully valid.
         self.app
             .check_tx_stateful(ctx.clone(), &transaction)
             .await?;
@@ -197,6 +270,10 @@ impl Worker {
     ) -> Result<abci::response::EndBlock> {
         let ctx = Context::new();
         self.app.end_block(ctx.clone(), &end_block).await;
+        self.app
+            .check_supply_changes(ctx.clone())
+            .await
+            .context("supply changes are inconsistent with the block")?;
 
         // Set `tm_validator_updates` to the complete set of
         // validators and voting power. This must be the last step performed,
@@ -219,17 +296,24 @@ impl Worker {
     async fn commit(&mut self) -> Result<abci::response::Commit> {
         // Begin sidecar code
 
+        // Retain the block's transactions, to serve them to block explorers.
+        // They're written along with the block's state, in the commit below.
+        let transactions = std::mem::take(&mut self.block_transactions)
+            .into_iter()
+            .map(|tx| tx.encode_to_vec())
+            .collect();
+        self.storage.stage_block_transactions(transactions);
+
         // Note: App::commit resets internal components, so we don't need to do that ourselves.
         let (jmt_root, _) = self.app.commit(self.storage.clone()).await?;
         let app_hash = jmt_root.0.to_vec();
-        let _ = self.height_tx.send(
-            self.storage
-                .latest_version()
-                .await?
-                .expect("just committed version")
-                .try_into()
-                .unwrap(),
-        );
+        let height = self
+            .storage
+            .latest_version()
+            .await?
+            .expect("just committed version");
+
+        let _ = self.height_tx.send(height.try_into().unwrap());
 
         tracing::info!(app_hash = ?hex::encode(&app_hash), "finished block commit");
 
@@ -239,3 +323,51 @@ impl Worker {
         })
     }
 }
+
+/// Installs the Groth16 verifying keys recorded in the chain parameters.
+///
+/// Every node has to reach the same verdict on a proof, so these are the only
+/// keys pd verifies against; if the chain has none, all Groth16 proofs are
+/// rejected.
+fn install_verifying_keys(params: &ChainParams) -> Result<()> {
+    if let Some(keys) = params.groth16_verifying_keys()? {
+        groth16::install_verifying_keys(keys)?;
+        tracing::info!("installed Groth16 verifying keys from the chain parameters");
+    }
+    Ok(())
+}
+
+/// Decodes the given DeliverTx transactions, and verifies all of their
+/// signatures in a single batch.
+///
+/// The result is aligned with `tx_bytes`, with `None` for messages that are
+/// not DeliverTx requests.
+fn precheck_transactions(tx_bytes: Vec<Option<Bytes>>) -> Vec<Option<Result<Transaction>>> {
+    let decoded = tx_bytes
+        .into_iter()
+        .map(|tx_bytes| tx_bytes.map(Transaction::decode))
+        .collect::<Vec<_>>();
+
+    let signatures = {
+        let transactions = decoded
+            .iter()
+            .flatten()
+            .filter_map(|transaction| transaction.as_ref().ok())
+            .collect::<Vec<_>>();
+        Transaction::verify_signatures_batch(&transactions)
+    };
+    let mut signatures = signatures.into_iter();
+
+    decoded
+        .into_iter()
+        .map(|transaction| {
+            transaction.map(|transaction| {
+                let transaction = transaction?;
+                signatures
+                    .next()
+                    .expect("one signature result per decoded transaction")?;
+                Ok(transaction)
+            })
+        })
+        .collect()
+}
@@ -17,6 +17,7 @@ use tracing::Instrument;
 
 use crate::RequestExt;
 
+mod explorer;
 mod oblivious;
 mod specific;
 
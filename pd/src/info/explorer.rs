@@ -0,0 +1,237 @@
+use penumbra_proto::{
+    client::explorer::{
+        block_explorer_server::BlockExplorer, BlockByHeightRequest, BlockInfo, BlockTransaction,
+        TransactionInfo, TransactionsInBlockRequest, TransactionsInBlockResponse,
+    },
+    Message, Protobuf,
+};
+use penumbra_transaction::{Action, Transaction};
+use tonic::Status;
+use tracing::instrument;
+
+use super::Info;
+
+/// The number of transactions returned per page, if the request doesn't specify.
+const DEFAULT_PAGE_SIZE: u64 = 100;
+/// The maximum number of transactions returned per page.
+const MAX_PAGE_SIZE: u64 = 1000;
+
+impl Info {
+    /// Returns the transactions included in the block at `height`, or an
+    /// error if no such block has been committed.
+    async fn committed_block_transactions(
+        &self,
+        height: u64,
+    ) -> Result<Vec<BlockTransaction>, Status> {
+        let latest_height = self
+            .storage
+            .latest_version()
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting latest height: {}", e)))?;
+        if latest_height.map_or(true, |latest_height| height > latest_height) {
+            return Err(Status::not_found(format!(
+                "block {} has not been committed",
+                height
+            )));
+        }
+
+        self.storage
+            .block_transactions(height)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting transactions: {}", e)))?
+            .into_iter()
+            .map(|record| {
+                BlockTransaction::decode(record.as_slice()).map_err(|e| {
+                    Status::internal(format!("stored transaction record is invalid: {}", e))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Describes the `index`th transaction in a block.
+///
+/// Transactions that failed to execute are included in blocks too, and may
+/// not even decode, in which case only their raw bytes and result are given.
+fn transaction_info(index: u64, transaction: BlockTransaction) -> TransactionInfo {
+    let mut info = TransactionInfo {
+        index,
+        code: transaction.code,
+        log: transaction.log,
+        ..Default::default()
+    };
+    if let Ok(decoded) = Transaction::decode(transaction.raw.as_slice()) {
+        let body = decoded.transaction_body();
+        info.id = decoded.id().to_vec();
+        info.fee = Some(body.fee.into());
+        // Spends and outputs are shielded, so only their encrypted data is
+        // public; everything else is decoded for display.
+        info.public_actions = body
+            .actions
+            .into_iter()
+            .filter(|action| !matches!(action, Action::Spend(_) | Action::Output(_)))
+            .map(Into::into)
+            .collect();
+    }
+    info.raw = transaction.raw;
+    info
+}
+
+#[tonic::async_trait]
+impl BlockExplorer for Info {
+    #[instrument(skip(self, request), fields(height = request.get_ref().height))]
+    async fn block_by_height(
+        &self,
+        request: tonic::Request<BlockByHeightRequest>,
+    ) -> Result<tonic::Response<BlockInfo>, Status> {
+        let height = request.into_inner().height;
+        let transactions = self.committed_block_transactions(height).await?;
+
+        let transaction_ids = transactions
+            .into_iter()
+            .enumerate()
+            .map(|(index, transaction)| transaction_info(index as u64, transaction).id)
+            .collect();
+
+        Ok(tonic::Response::new(BlockInfo {
+            height,
+            transaction_ids,
+        }))
+    }
+
+    #[instrument(skip(self, request), fields(height = request.get_ref().height))]
+    async fn transactions_in_block(
+        &self,
+        request: tonic::Request<TransactionsInBlockRequest>,
+    ) -> Result<tonic::Response<TransactionsInBlockResponse>, Status> {
+        let request = request.into_inner();
+        let limit = match request.limit {
+            0 => DEFAULT_PAGE_SIZE,
+            limit => limit.min(MAX_PAGE_SIZE),
+        };
+
+        let transactions = self.committed_block_transactions(request.height).await?;
+        let total = transactions.len() as u64;
+
+        let transactions = transactions
+            .into_iter()
+            .enumerate()
+            .skip(request.offset.try_into().unwrap_or(usize::MAX))
+            .take(limit as usize)
+            .map(|(index, transaction)| transaction_info(index as u64, transaction))
+            .collect();
+
+        Ok(tonic::Response::new(TransactionsInBlockResponse {
+            transactions,
+            total,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::rdsa::{Binding, SigningKey};
+    use penumbra_storage::Storage;
+    use penumbra_tct as tct;
+    use penumbra_transaction::{Fee, TransactionBody};
+    use rand_core::OsRng;
+    use tendermint::block;
+    use tokio::sync::watch;
+
+    use super::*;
+
+    // Every transaction in a block is served in order, including ones which
+    // failed or couldn't even be decoded.
+    #[tokio::test]
+    async fn serves_every_transaction_in_a_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("explorer-testing.db"))
+            .await
+            .unwrap();
+
+        let transaction = Transaction {
+            transaction_body: TransactionBody {
+                actions: Vec::new(),
+                expiry_height: 0,
+                chain_id: "explorer-testing".to_string(),
+                fee: Fee(7),
+            },
+            binding_sig: SigningKey::<Binding>::new(OsRng).sign(OsRng, b"unchecked"),
+            anchor: tct::Tree::new().root(),
+        };
+        let records = vec![
+            BlockTransaction {
+                raw: transaction.encode_to_vec(),
+                code: 0,
+                log: String::new(),
+            },
+            BlockTransaction {
+                raw: b"not a transaction".to_vec(),
+                code: 1,
+                log: "could not decode transaction".to_string(),
+            },
+        ];
+        storage.stage_block_transactions(records.iter().map(Message::encode_to_vec).collect());
+        let state = storage.state().await.unwrap();
+        state
+            .write()
+            .await
+            .put("explorer/test".to_string().into(), b"value".to_vec());
+        state.write().await.commit(storage.clone()).await.unwrap();
+
+        let info = Info::new(storage, watch::channel(block::Height::from(0u32)).1);
+
+        let block = info
+            .block_by_height(tonic::Request::new(BlockByHeightRequest { height: 0 }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            block.transaction_ids,
+            vec![transaction.id().to_vec(), Vec::new()]
+        );
+
+        let page = info
+            .transactions_in_block(tonic::Request::new(TransactionsInBlockRequest {
+                height: 0,
+                offset: 0,
+                limit: 0,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.transactions.len(), 2);
+        let succeeded = &page.transactions[0];
+        assert_eq!(succeeded.id, transaction.id().to_vec());
+        assert_eq!(succeeded.fee, Some(Fee(7).into()));
+        assert_eq!(succeeded.code, 0);
+
+        // Paging skips to the failed transaction, which is served as it was
+        // included in the block.
+        let page = info
+            .transactions_in_block(tonic::Request::new(TransactionsInBlockRequest {
+                height: 0,
+                offset: 1,
+                limit: 1,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.transactions.len(), 1);
+        let failed = &page.transactions[0];
+        assert_eq!(failed.index, 1);
+        assert_eq!(failed.raw, b"not a transaction".to_vec());
+        assert_eq!(failed.code, 1);
+        assert_eq!(failed.log, "could not decode transaction");
+        assert!(failed.id.is_empty());
+        assert!(failed.fee.is_none());
+
+        let e = info
+            .block_by_height(tonic::Request::new(BlockByHeightRequest { height: 1 }))
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), tonic::Code::NotFound);
+    }
+}
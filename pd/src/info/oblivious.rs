@@ -12,7 +12,8 @@ use penumbra_proto::{
     chain::{ChainParams, CompactBlock, KnownAssets},
     client::oblivious::{
         oblivious_query_server::ObliviousQuery, AssetListRequest, ChainParamsRequest,
-        CompactBlockRangeRequest, ValidatorInfoRequest,
+        CompactBlockRangeRequest, ValidAnchorRangeRequest, ValidAnchorRangeResponse,
+        ValidatorInfoRequest,
     },
     stake::ValidatorInfo,
     Protobuf,
@@ -86,6 +87,24 @@ impl ObliviousQuery for Info {
         Ok(tonic::Response::new(known_assets.into()))
     }
 
+    #[instrument(skip(self, request))]
+    async fn valid_anchor_range(
+        &self,
+        request: tonic::Request<ValidAnchorRangeRequest>,
+    ) -> Result<tonic::Response<ValidAnchorRangeResponse>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let heights = state.valid_anchor_heights().await.map_err(|e| {
+            tonic::Status::unavailable(format!("error getting valid anchor range: {}", e))
+        })?;
+
+        Ok(tonic::Response::new(ValidAnchorRangeResponse {
+            start_height: *heights.start(),
+            end_height: *heights.end(),
+        }))
+    }
+
     #[instrument(skip(self, request), fields(show_inactive = request.get_ref().show_inactive))]
     async fn validator_info(
         &self,
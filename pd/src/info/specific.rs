@@ -1,3 +1,7 @@
+use std::pin::Pin;
+
+use async_stream::try_stream;
+use futures::{StreamExt, TryStreamExt};
 use penumbra_chain::View as _;
 use penumbra_component::shielded_pool::View as _;
 use penumbra_component::stake::View as _;
@@ -5,12 +9,16 @@ use penumbra_proto::{
     self as proto,
     chain::NoteSource,
     client::specific::{
-        specific_query_server::SpecificQuery, KeyValueRequest, KeyValueResponse,
-        ValidatorStatusRequest,
+        specific_query_server::SpecificQuery, AssetSupplyRequest, KeyValueRequest,
+        KeyValueResponse, TransactionByHashRequest, TransactionByNullifierRequest,
+        UnbondingPenaltyRequest, UnbondingPenaltyResponse, ValidatorRateHistoryRequest,
+        ValidatorStatusRequest, ValidatorUptimeRequest, ValidatorUptimeResponse,
     },
     crypto::NoteCommitment,
+    Protobuf,
 };
 
+use penumbra_storage::JmtProof;
 use tonic::Status;
 use tracing::instrument;
 
@@ -24,6 +32,9 @@ use super::Info;
 
 #[tonic::async_trait]
 impl SpecificQuery for Info {
+    type ValidatorRateHistoryStream =
+        Pin<Box<dyn futures::Stream<Item = Result<proto::stake::RateData, Status>> + Send>>;
+
     #[instrument(skip(self, request))]
     async fn transaction_by_note(
         &self,
@@ -90,6 +101,227 @@ impl SpecificQuery for Info {
         }
     }
 
+    #[instrument(
+        skip(self, request),
+        fields(
+            start_epoch_index = request.get_ref().start_epoch_index,
+            end_epoch_index = request.get_ref().end_epoch_index,
+        ),
+    )]
+    async fn validator_rate_history(
+        &self,
+        request: tonic::Request<ValidatorRateHistoryRequest>,
+    ) -> Result<tonic::Response<Self::ValidatorRateHistoryStream>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let ValidatorRateHistoryRequest {
+            identity_key,
+            start_epoch_index,
+            end_epoch_index,
+            ..
+        } = request.into_inner();
+
+        let identity_key = identity_key
+            .ok_or_else(|| Status::invalid_argument("missing identity key"))?
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid identity key"))?;
+
+        let current_epoch = state
+            .get_current_epoch()
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting current epoch: {}", e)))?;
+
+        // Treat end_epoch_index = 0 as a request for the history up to the
+        // current epoch, and never return rates past the current epoch.
+        let end_epoch_index = if end_epoch_index == 0 {
+            current_epoch.index
+        } else {
+            std::cmp::min(end_epoch_index, current_epoch.index)
+        };
+
+        let s = try_stream! {
+            for epoch_index in start_epoch_index..=end_epoch_index {
+                if let Some(rate_data) = state
+                    .validator_rate_by_epoch(&identity_key, epoch_index)
+                    .await?
+                {
+                    yield rate_data.to_proto();
+                }
+            }
+        };
+
+        Ok(tonic::Response::new(
+            s.map_err(|e: anyhow::Error| {
+                tonic::Status::unavailable(format!("error getting rate history: {}", e))
+            })
+            .boxed(),
+        ))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn validator_uptime(
+        &self,
+        request: tonic::Request<ValidatorUptimeRequest>,
+    ) -> Result<tonic::Response<ValidatorUptimeResponse>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let identity_key = request
+            .into_inner()
+            .identity_key
+            .ok_or_else(|| Status::invalid_argument("missing identity key"))?
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid identity key"))?;
+
+        // Uptime is only tracked for validators that have been active, so a
+        // missing uptime doesn't necessarily mean the validator is unknown.
+        let uptime = state
+            .validator_uptime(&identity_key)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting validator uptime: {}", e)))?
+            .ok_or_else(|| Status::not_found("no uptime tracked for validator"))?;
+
+        let missed_blocks_maximum = state
+            .missed_blocks_maximum()
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting chain parameters: {}", e)))?;
+
+        Ok(tonic::Response::new(ValidatorUptimeResponse {
+            missed_blocks: uptime.num_missed_blocks() as u64,
+            missed_blocks_maximum,
+            uptime: Some(uptime.into()),
+        }))
+    }
+
+    #[instrument(
+        skip(self, request),
+        fields(start_epoch_index = request.get_ref().start_epoch_index)
+    )]
+    async fn unbonding_penalty(
+        &self,
+        request: tonic::Request<UnbondingPenaltyRequest>,
+    ) -> Result<tonic::Response<UnbondingPenaltyResponse>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let request = request.into_inner();
+        let identity_key = request
+            .identity_key
+            .ok_or_else(|| Status::invalid_argument("missing identity key"))?
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid identity key"))?;
+
+        let current_epoch = state
+            .get_current_epoch()
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting current epoch: {}", e)))?;
+        let claimable_epoch_index = state
+            .unbonding_end_epoch_for(&identity_key, request.start_epoch_index)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting unbonding epoch: {}", e)))?;
+        let penalty = state
+            .unbonding_penalty(&identity_key, request.start_epoch_index)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting penalty: {}", e)))?;
+
+        Ok(tonic::Response::new(UnbondingPenaltyResponse {
+            penalty,
+            claimable_epoch_index,
+            claimable: current_epoch.index >= claimable_epoch_index,
+        }))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn asset_supply(
+        &self,
+        request: tonic::Request<AssetSupplyRequest>,
+    ) -> Result<tonic::Response<proto::chain::AssetInfo>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let asset_id = request
+            .into_inner()
+            .asset_id
+            .ok_or_else(|| Status::invalid_argument("missing asset id"))?
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid asset id"))?;
+
+        let denom = state
+            .denom_by_asset(&asset_id)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting denom: {}", e)))?
+            .ok_or_else(|| Status::not_found("asset not found"))?;
+        let total_supply = state
+            .token_supply(&asset_id)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting token supply: {}", e)))?
+            .unwrap_or(0);
+        let as_of_block_height = state
+            .get_block_height()
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting block height: {}", e)))?;
+
+        Ok(tonic::Response::new(
+            penumbra_chain::params::AssetInfo {
+                asset_id,
+                denom,
+                as_of_block_height,
+                total_supply,
+            }
+            .into(),
+        ))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn transaction_by_nullifier(
+        &self,
+        request: tonic::Request<TransactionByNullifierRequest>,
+    ) -> Result<tonic::Response<proto::chain::TransactionLocation>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let nullifier = request
+            .into_inner()
+            .nullifier
+            .ok_or_else(|| Status::invalid_argument("missing nullifier"))?
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid nullifier"))?;
+
+        let location = state
+            .transaction_by_nullifier(&nullifier)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting transaction: {}", e)))?
+            .ok_or_else(|| Status::not_found("nullifier has not been spent"))?;
+        tracing::debug!(?nullifier, ?location);
+
+        Ok(tonic::Response::new(location.into()))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn transaction_by_hash(
+        &self,
+        request: tonic::Request<TransactionByHashRequest>,
+    ) -> Result<tonic::Response<proto::chain::TransactionLocation>, Status> {
+        let state = self.state_tonic().await?;
+        state.check_chain_id(&request.get_ref().chain_id).await?;
+
+        let id: [u8; 32] = request
+            .into_inner()
+            .tx_hash
+            .try_into()
+            .map_err(|_| Status::invalid_argument("invalid transaction hash"))?;
+
+        let location = state
+            .transaction_by_hash(&id)
+            .await
+            .map_err(|e| Status::unavailable(format!("error getting transaction: {}", e)))?
+            .ok_or_else(|| Status::not_found("transaction not found"))?;
+        tracing::debug!(?location);
+
+        Ok(tonic::Response::new(location.into()))
+    }
+
     #[instrument(skip(self, request))]
     async fn key_value(
         &self,
@@ -101,63 +333,174 @@ impl SpecificQuery for Info {
         let request = request.into_inner();
         tracing::debug!(?request);
 
-        if request.proof {
-            if request.key.is_empty() {
-                return Err(Status::invalid_argument("key is empty"));
+        let key_hash = match (!request.key.is_empty(), !request.key_hash.is_empty()) {
+            (false, true) => jmt::KeyHash(
+                request
+                    .key_hash
+                    .try_into()
+                    .map_err(|_| Status::invalid_argument("invalid key_hash"))?,
+            ),
+            (true, false) => request.key.as_slice().into(),
+            (false, false) => {
+                return Err(Status::invalid_argument("key and key_hash are both empty"))
             }
-            if !request.key_hash.is_empty() {
+            (true, true) => {
                 return Err(Status::invalid_argument(
-                    "key_hash is nonempty but proof was requested",
-                ));
+                    "key and key_hash were both provided",
+                ))
             }
+        };
+        tracing::debug!(?key_hash);
 
-            let (value, proof) = state
+        // Unproven queries of the latest state can be answered from the state itself
+        if !request.proof && request.height.is_none() {
+            let value = state
                 .read()
                 .await
-                .get_with_proof(request.key)
+                .get(key_hash)
                 .await
-                .map_err(|e| tonic::Status::internal(e.to_string()))?;
+                .map_err(|e| Status::internal(e.to_string()))?
+                .ok_or_else(|| Status::not_found("requested key not found in state"))?;
 
-            let commitment_proof = ics23::CommitmentProof {
-                proof: Some(ics23::commitment_proof::Proof::Exist(proof)),
-            };
+            return Ok(tonic::Response::new(KeyValueResponse {
+                value,
+                exists: true,
+                ..Default::default()
+            }));
+        }
+
+        // Otherwise, read the value from the requested version of the JMT
+        let (proven, proof) = self
+            .storage
+            .get_with_proof(key_hash, request.height)
+            .await
+            .map_err(|e| Status::not_found(e.to_string()))?;
+
+        if !request.proof {
+            let value = proven
+                .value
+                .ok_or_else(|| Status::not_found("requested key not found in state"))?;
 
-            Ok(tonic::Response::new(KeyValueResponse {
+            return Ok(tonic::Response::new(KeyValueResponse {
                 value,
-                proof: Some(commitment_proof),
-            }))
-        } else {
-            let key_hash = match (!request.key.is_empty(), !request.key_hash.is_empty()) {
-                (false, true) => jmt::KeyHash(
-                    request
-                        .key_hash
-                        .try_into()
-                        .map_err(|_| Status::invalid_argument("invalid key_hash"))?,
-                ),
-                (true, false) => request.key.as_slice().into(),
-                (false, false) => {
-                    return Err(Status::invalid_argument("key and key_hash are both empty"))
-                }
-                (true, true) => {
-                    return Err(Status::invalid_argument(
-                        "key and key_hash were both provided",
-                    ))
-                }
-            };
-            tracing::debug!(?key_hash);
+                height: proven.version,
+                exists: true,
+                ..Default::default()
+            }));
+        }
 
-            let value = state
-                .read()
+        // ICS-23 proofs can only be made for keys which exist, and are requested by key rather
+        // than by key hash
+        let ics23_proof = if proven.value.is_some() && !request.key.is_empty() {
+            let proof = jmt::JellyfishMerkleTree::new(&self.storage)
+                .get_with_ics23_proof(request.key, proven.version)
                 .await
-                .get(key_hash)
+                .map_err(|e| Status::internal(e.to_string()))?;
+            Some(ics23::CommitmentProof {
+                proof: Some(ics23::commitment_proof::Proof::Exist(proof)),
+            })
+        } else {
+            None
+        };
+
+        Ok(tonic::Response::new(KeyValueResponse {
+            exists: proven.value.is_some(),
+            value: proven.value.unwrap_or_default(),
+            proof: ics23_proof,
+            height: proven.version,
+            root: proven.root.0.to_vec(),
+            jmt_proof: Some(JmtProof::from(proof).into()),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use decaf377::Fq;
+    use penumbra_chain::{params::ChainParams, TransactionLocation, View as _};
+    use penumbra_component::shielded_pool::View as _;
+    use penumbra_crypto::Nullifier;
+    use penumbra_storage::Storage;
+    use tendermint::block;
+    use tokio::sync::watch;
+
+    use super::*;
+
+    // Indexed transactions are served by hash and by the nullifiers they spent,
+    // and lookups of anything else are not found.
+    #[tokio::test]
+    async fn serves_transaction_locations() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("specific-testing.db"))
+            .await
+            .unwrap();
+
+        let location = TransactionLocation {
+            id: [1; 32],
+            height: 5,
+            index: 2,
+        };
+        let spent = Nullifier(Fq::from(1u64));
+        let state = storage.state().await.unwrap();
+        state
+            .put_chain_params(ChainParams {
+                chain_id: "specific-testing".to_string(),
+                ..Default::default()
+            })
+            .await;
+        state.set_transaction_location(location).await;
+        state.set_transaction_by_nullifier(&spent, location).await;
+        state.write().await.commit(storage.clone()).await.unwrap();
+
+        let info = Info::new(storage, watch::channel(block::Height::from(0u32)).1);
+
+        let by_hash = |tx_hash: [u8; 32]| {
+            info.transaction_by_hash(tonic::Request::new(TransactionByHashRequest {
+                chain_id: "specific-testing".to_string(),
+                tx_hash: tx_hash.to_vec(),
+            }))
+        };
+        let served: TransactionLocation = by_hash(location.id)
+            .await
+            .unwrap()
+            .into_inner()
+            .try_into()
+            .unwrap();
+        assert_eq!(served, location);
+        assert_eq!(
+            by_hash([2; 32]).await.unwrap_err().code(),
+            tonic::Code::NotFound
+        );
+
+        let by_nullifier = |nullifier: Nullifier| {
+            info.transaction_by_nullifier(tonic::Request::new(TransactionByNullifierRequest {
+                chain_id: "specific-testing".to_string(),
+                nullifier: Some(nullifier.into()),
+            }))
+        };
+        let served: TransactionLocation = by_nullifier(spent)
+            .await
+            .unwrap()
+            .into_inner()
+            .try_into()
+            .unwrap();
+        assert_eq!(served, location);
+        assert_eq!(
+            by_nullifier(Nullifier(Fq::from(2u64)))
                 .await
-                .map_err(|e| Status::internal(e.to_string()))?
-                .ok_or_else(|| Status::not_found("requested key not found in state"))?;
+                .unwrap_err()
+                .code(),
+            tonic::Code::NotFound
+        );
 
-            Ok(tonic::Response::new(KeyValueResponse {
-                value,
-                proof: None,
+        // Requests for another chain are rejected
+        let wrong_chain = info
+            .transaction_by_hash(tonic::Request::new(TransactionByHashRequest {
+                chain_id: "another-chain".to_string(),
+                tx_hash: location.id.to_vec(),
             }))
-        }
+            .await
+            .unwrap_err();
+        assert_eq!(wrong_chain.code(), tonic::Code::FailedPrecondition);
     }
 }
@@ -8,8 +8,10 @@ mod consensus;
 mod info;
 mod mempool;
 mod metrics;
+mod pruner;
 mod request_ext;
 mod snapshot;
+mod stateless;
 
 pub mod testnet;
 
@@ -20,4 +22,6 @@ pub use consensus::Consensus;
 pub use info::Info;
 pub use mempool::Mempool;
 pub use penumbra_component::app::App;
+pub use pruner::Pruner;
 pub use snapshot::Snapshot;
+pub use stateless::StatelessChecker;
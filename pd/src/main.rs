@@ -15,12 +15,13 @@ use metrics_exporter_prometheus::PrometheusBuilder;
 use pd::testnet::{canonicalize_path, generate_tm_config, write_configs, ValidatorKeys};
 use penumbra_chain::{genesis::Allocation, params::ChainParams};
 use penumbra_component::stake::{validator::Validator, FundingStream, FundingStreams};
-use penumbra_crypto::{keys::SpendKey, DelegationToken};
+use penumbra_crypto::{keys::SpendKey, proofs::groth16, DelegationToken};
 use penumbra_proto::client::{
+    explorer::block_explorer_server::BlockExplorerServer,
     oblivious::oblivious_query_server::ObliviousQueryServer,
     specific::specific_query_server::SpecificQueryServer,
 };
-use penumbra_storage::Storage;
+use penumbra_storage::{PrunePolicy, Storage};
 use rand::Rng;
 use rand_core::OsRng;
 use tokio::runtime;
@@ -58,6 +59,26 @@ enum RootCommand {
         /// Bind the metrics endpoint to this port.
         #[clap(short, long, default_value = "9000")]
         metrics_port: u16,
+        /// Prune the JMT in the background, keeping only this many of the most
+        /// recent versions, rather than keeping every version forever.
+        ///
+        /// Historical queries can only be served for the versions that are kept.
+        #[clap(long)]
+        prune_keep_versions: Option<u64>,
+        /// How many blocks to commit between each round of pruning, if pruning
+        /// is enabled.
+        #[clap(long, default_value = "1000")]
+        prune_interval: u64,
+    },
+
+    /// Generate Groth16 proving and verifying keys for the spend and output
+    /// circuits.
+    ///
+    /// This is an insecure, single-party setup, suitable only for testnets.
+    SetupProofs {
+        /// Directory to write the keys to.
+        #[clap(long)]
+        output_dir: PathBuf,
     },
 
     /// Generate, join, or reset a testnet.
@@ -70,6 +91,28 @@ enum RootCommand {
         #[clap(subcommand)]
         tn_cmd: TestnetCommand,
     },
+
+    /// Manage pd's storage.
+    Storage {
+        #[clap(subcommand)]
+        storage_cmd: StorageCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum StorageCommand {
+    /// Prune old versions of the JMT and compact the database, to reclaim
+    /// disk space.
+    ///
+    /// This must be run while pd is stopped.
+    Prune {
+        /// The path used to store pd-releated data, including the Rocks database.
+        #[clap(long)]
+        home: PathBuf,
+        /// Keep only this many of the most recent versions of the JMT.
+        #[clap(long, default_value = "100000")]
+        keep_versions: u64,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -101,6 +144,11 @@ enum TestnetCommand {
         /// IP Address to start `tendermint` nodes on. Increments by three to make room for `pd` per node.
         #[clap(long, default_value = "192.167.10.11")]
         starting_ip: Ipv4Addr,
+        /// Record the Groth16 verifying keys in this directory (as written by
+        /// `pd setup-proofs`) in the chain parameters. Without them, the chain
+        /// rejects Groth16 proofs.
+        #[clap(long, parse(from_os_str))]
+        proof_params: Option<PathBuf>,
     },
 
     /// Like `testnet generate`, but joins the testnet the specified node is part of.
@@ -156,6 +204,8 @@ async fn main() -> anyhow::Result<()> {
             abci_port,
             grpc_port,
             metrics_port,
+            prune_keep_versions,
+            prune_interval,
         } => {
             tracing::info!(?host, ?abci_port, ?grpc_port, "starting pd");
 
@@ -166,11 +216,26 @@ async fn main() -> anyhow::Result<()> {
                 .await
                 .context("Unable to initialize RocksDB storage")?;
 
-            let (consensus, height_rx) = pd::Consensus::new(storage.clone()).await?;
-            let mempool = pd::Mempool::new(storage.clone(), height_rx.clone()).await?;
-            let info = pd::Info::new(storage.clone(), height_rx);
+            // Stateless checks are shared between the consensus and mempool
+            // workers, so that together they run at most one check per core.
+            let checker = pd::StatelessChecker::default();
+            let (consensus, height_rx) =
+                pd::Consensus::new(storage.clone(), checker.clone()).await?;
+            let mempool = pd::Mempool::new(storage.clone(), height_rx.clone(), checker).await?;
+            let info = pd::Info::new(storage.clone(), height_rx.clone());
             let snapshot = pd::Snapshot {};
 
+            if let Some(keep_versions) = prune_keep_versions {
+                let policy = PrunePolicy {
+                    keep_versions,
+                    interval: prune_interval,
+                };
+                tracing::info!(?policy, "pruning JMT in the background");
+                tokio::task::Builder::new()
+                    .name("Pruner")
+                    .spawn(pd::Pruner::new(storage.clone(), policy, height_rx).run());
+            }
+
             let abci_server = tokio::task::Builder::new().name("abci_server").spawn(
                 tower_abci::Server::builder()
                     .consensus(consensus)
@@ -192,6 +257,7 @@ async fn main() -> anyhow::Result<()> {
                     })
                     .add_service(ObliviousQueryServer::new(info.clone()))
                     .add_service(SpecificQueryServer::new(info.clone()))
+                    .add_service(BlockExplorerServer::new(info.clone()))
                     .serve(
                         format!("{}:{}", host, grpc_port)
                             .parse()
@@ -230,6 +296,50 @@ async fn main() -> anyhow::Result<()> {
             };
         }
 
+        RootCommand::Storage {
+            storage_cmd:
+                StorageCommand::Prune {
+                    home,
+                    keep_versions,
+                },
+        } => {
+            let mut rocks_path = home;
+            rocks_path.push("rocksdb");
+
+            // Opening the database would otherwise create it if it doesn't exist
+            if !rocks_path.exists() {
+                return Err(anyhow::anyhow!("{:?} does not exist", rocks_path));
+            }
+            let storage = Storage::load(rocks_path)
+                .await
+                .context("Unable to open RocksDB storage; is pd still running?")?;
+
+            let policy = PrunePolicy {
+                keep_versions,
+                ..Default::default()
+            };
+            match storage.prune(policy).await? {
+                Some(stats) => println!(
+                    "deleted {} stale JMT nodes ({} bytes), keeping versions from {} onwards",
+                    stats.deleted_nodes, stats.deleted_bytes, stats.oldest_retained
+                ),
+                None => println!(
+                    "fewer than {} versions stored, nothing to prune",
+                    keep_versions
+                ),
+            }
+
+            tracing::info!("compacting JMT storage");
+            storage.compact_jmt().await?;
+        }
+
+        RootCommand::SetupProofs { output_dir } => {
+            tracing::info!(?output_dir, "generating Groth16 proof parameters");
+            let keys = groth16::setup(&mut OsRng)?;
+            keys.write(&output_dir)?;
+            println!("wrote Groth16 proof parameters to {}", output_dir.display());
+        }
+
         RootCommand::Testnet {
             tn_cmd: TestnetCommand::UnsafeResetAll {},
             testnet_dir,
@@ -317,6 +427,7 @@ async fn main() -> anyhow::Result<()> {
                     validators_input_file,
                     chain_id,
                     preserve_chain_id,
+                    proof_params,
                 },
             testnet_dir,
         } => {
@@ -490,6 +601,17 @@ async fn main() -> anyhow::Result<()> {
                 })
                 .collect::<Result<Vec<Validator>, anyhow::Error>>()?;
 
+            let (spend_verifying_key, output_verifying_key) = match &proof_params {
+                Some(proof_params) => {
+                    let keys = groth16::VerifyingKeys::load_encoded(proof_params)
+                        .context("Unable to load Groth16 verifying keys")?;
+                    // Check that the keys decode before putting them in genesis.
+                    groth16::VerifyingKeys::decode(&keys.0, &keys.1)?;
+                    keys
+                }
+                None => (Vec::new(), Vec::new()),
+            };
+
             let app_state = genesis::AppState {
                 allocations: allocations.clone(),
                 chain_params: ChainParams {
@@ -497,6 +619,8 @@ async fn main() -> anyhow::Result<()> {
                     epoch_duration,
                     unbonding_epochs,
                     active_validator_limit,
+                    spend_verifying_key,
+                    output_verifying_key,
                     ..Default::default()
                 },
                 validators: validators.clone().into_iter().map(Into::into).collect(),
@@ -1,11 +1,13 @@
 use anyhow::Result;
-use bytes::Bytes;
 use tokio::sync::oneshot;
 use tracing::Span;
 
+use crate::stateless::Pending;
+
 #[derive(Debug)]
 pub struct Message {
-    pub tx_bytes: Bytes,
+    /// The transaction, whose stateless checks were started when it arrived.
+    pub tx: Pending,
     pub rsp_sender: oneshot::Sender<Result<()>>,
     pub span: Span,
 }
@@ -20,17 +20,20 @@ use tracing::{error_span, Instrument};
 
 use super::{Message, Worker};
 use crate::metrics;
+use crate::stateless::StatelessChecker;
 use crate::RequestExt;
 
 #[derive(Clone)]
 pub struct Mempool {
     queue: PollSender<Message>,
+    checker: StatelessChecker,
 }
 
 impl Mempool {
     pub async fn new(
         storage: Storage,
         height_rx: watch::Receiver<block::Height>,
+        checker: StatelessChecker,
     ) -> anyhow::Result<Self> {
         let (queue_tx, queue_rx) = mpsc::channel(10);
 
@@ -40,6 +43,7 @@ impl Mempool {
 
         Ok(Self {
             queue: PollSender::new(queue_tx),
+            checker,
         })
     }
 }
@@ -71,9 +75,14 @@ impl tower::Service<MempoolRequest> for Mempool {
             tx: tx_bytes, kind, ..
         }) = req;
 
+        // Start the stateless checks now, so that they run concurrently with
+        // those of other transactions; the worker waits for their results in
+        // order before performing stateful checks.
+        let checked = span.in_scope(|| self.checker.check(tx_bytes));
+
         self.queue
             .send_item(Message {
-                tx_bytes,
+                tx: checked,
                 rsp_sender: tx,
                 span: span.clone(),
             })
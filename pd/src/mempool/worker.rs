@@ -1,16 +1,13 @@
 use anyhow::Result;
-use bytes::Bytes;
 
 use penumbra_component::{Component, Context};
-use penumbra_proto::Protobuf;
 use penumbra_storage::Storage;
-use penumbra_transaction::Transaction;
 use tendermint::block;
 use tokio::sync::{mpsc, watch};
 use tracing::{instrument, Instrument};
 
 use super::Message;
-use crate::App;
+use crate::{stateless::Pending, App};
 
 pub struct Worker {
     queue: mpsc::Receiver<Message>,
@@ -36,14 +33,12 @@ impl Worker {
         })
     }
 
-    /// Currently, we perform all stateless and stateful checks sequentially in
-    /// the mempool worker.  A possibly more performant design would be to only
-    /// perform the stateful checks in the worker, and have a frontend service
-    /// that performs the stateless checks.  However, this probably isn't
-    /// important to do until we know that it's a bottleneck.
-    async fn check_and_execute_tx(&mut self, ctx: Context, tx_bytes: Bytes) -> Result<()> {
-        let tx = Transaction::decode(tx_bytes.as_ref())?;
-        App::check_tx_stateless(ctx.clone(), &tx)?;
+    /// The stateless checks were started by the [`Mempool`](super::Mempool)
+    /// service when the transaction arrived, and run concurrently with those of
+    /// other transactions.  Here we wait for them to finish, in order, then
+    /// perform the stateful checks and execution sequentially.
+    async fn check_and_execute_tx(&mut self, ctx: Context, tx: Pending) -> Result<()> {
+        let tx = tx.wait().await?;
         self.app.check_tx_stateful(ctx.clone(), &tx).await?;
         self.app.execute_tx(ctx.clone(), &tx).await;
         Ok(())
@@ -69,13 +64,13 @@ impl Worker {
                 }
                 message = self.queue.recv() => {
                     if let Some(Message {
-                        tx_bytes,
+                        tx,
                         rsp_sender,
                         span,
                     }) = message {
                         let ctx = Context::new();
                         let _ = rsp_sender.send(
-                            self.check_and_execute_tx(ctx.clone(), tx_bytes)
+                            self.check_and_execute_tx(ctx.clone(), tx)
                                 .instrument(span)
                                 .await
                         );
@@ -0,0 +1,49 @@
+use penumbra_storage::{PrunePolicy, Storage};
+use tendermint::block;
+use tokio::sync::watch;
+use tracing::instrument;
+
+/// Prunes old versions of the JMT in the background, as new blocks are
+/// committed, according to a [`PrunePolicy`].
+pub struct Pruner {
+    storage: Storage,
+    policy: PrunePolicy,
+    height_rx: watch::Receiver<block::Height>,
+}
+
+impl Pruner {
+    pub fn new(
+        storage: Storage,
+        policy: PrunePolicy,
+        height_rx: watch::Receiver<block::Height>,
+    ) -> Self {
+        Self {
+            storage,
+            policy,
+            height_rx,
+        }
+    }
+
+    #[instrument(skip(self), name = "Pruner::run")]
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        // Prune once on startup, so that a node that was running without
+        // pruning catches up straight away.
+        let mut next_prune = 0;
+        loop {
+            let height = self.height_rx.borrow().value();
+            if height >= next_prune {
+                // A failure to prune is only a missed opportunity to save
+                // space, so it shouldn't take down the node.
+                if let Err(e) = self.storage.prune(self.policy).await {
+                    tracing::error!(?e, "failed to prune JMT");
+                }
+                next_prune = height + self.policy.interval.max(1);
+            }
+
+            if self.height_rx.changed().await.is_err() {
+                tracing::info!("consensus worker shut down, shutting down pruner");
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::{fmt, sync::Arc};
+
+use anyhow::Result;
+use bytes::Bytes;
+use futures::{future::BoxFuture, FutureExt};
+use penumbra_component::{Component, Context};
+use penumbra_proto::Protobuf;
+use penumbra_transaction::Transaction;
+use tokio::sync::Semaphore;
+
+use crate::App;
+
+/// A bounded pool of blocking workers for stateless transaction checks.
+///
+/// Stateless checks (proof and signature verification) are the expensive part
+/// of checking a transaction, but they don't depend on the chain state, so they
+/// can run concurrently with each other.  The mempool and consensus workers
+/// start these checks as soon as transactions arrive, then await their results
+/// in order, so that stateful checks and execution remain strictly ordered.
+#[derive(Clone)]
+pub struct StatelessChecker {
+    permits: Arc<Semaphore>,
+}
+
+impl Default for StatelessChecker {
+    /// Create a checker that runs up to one check per available core.
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism()
+            .map(usize::from)
+            .unwrap_or(1);
+        Self::new(cores)
+    }
+}
+
+impl StatelessChecker {
+    /// Create a checker that runs at most `max_concurrent` checks at a time.
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+        }
+    }
+
+    /// Start decoding the given transaction and performing all of its
+    /// stateless checks.
+    pub fn check(&self, tx_bytes: Bytes) -> Pending {
+        self.spawn(move || {
+            let tx = Transaction::decode(tx_bytes)?;
+            App::check_tx_stateless(Context::new(), &tx)?;
+            Ok(tx)
+        })
+    }
+
+    /// Start performing all of the given transaction's stateless checks except
+    /// for signature verification, which the caller has already performed.
+    pub fn check_except_signatures(&self, tx: Transaction) -> Pending {
+        self.spawn(move || {
+            App::check_tx_stateless_except_signatures(Context::new(), &tx)?;
+            Ok(tx)
+        })
+    }
+
+    fn spawn<F>(&self, check: F) -> Pending
+    where
+        F: FnOnce() -> Result<Transaction> + Send + 'static,
+    {
+        Pending(self.run(check))
+    }
+
+    /// Run `check` on a blocking worker once fewer than `max_concurrent`
+    /// other checks are running.
+    fn run<T, F>(&self, check: F) -> BoxFuture<'static, Result<T>>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T> + Send + 'static,
+    {
+        let permits = self.permits.clone();
+        let span = tracing::Span::current();
+        let handle = tokio::spawn(async move {
+            let _permit = permits
+                .acquire_owned()
+                .await
+                .expect("stateless checker semaphore is never closed");
+            tokio::task::spawn_blocking(move || span.in_scope(check)).await?
+        });
+        handle.map(|result| -> Result<T> { result? }).boxed()
+    }
+}
+
+/// The eventual result of a stateless check: the checked transaction, or the
+/// reason it was rejected.
+pub struct Pending(BoxFuture<'static, Result<Transaction>>);
+
+impl Pending {
+    /// A check that has already finished with the given result.
+    pub fn ready(result: Result<Transaction>) -> Self {
+        Pending(futures::future::ready(result).boxed())
+    }
+
+    /// Wait for the check to finish.
+    pub async fn wait(self) -> Result<Transaction> {
+        self.0.await
+    }
+}
+
+impl fmt::Debug for Pending {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Pending").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::*;
+
+    // Checks finish out of order, but each result must still be delivered to
+    // the request that started it, and no more than `max_concurrent` checks
+    // may run at once.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn checks_are_bounded_and_answer_their_own_requests() {
+        let checker = StatelessChecker::new(2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        let pending = (0..8u64)
+            .map(|i| {
+                let running = running.clone();
+                let max_running = max_running.clone();
+                checker.run(move || {
+                    let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now_running, Ordering::SeqCst);
+                    // Earlier requests take longer, so they finish last.
+                    std::thread::sleep(Duration::from_millis(10 * (8 - i)));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    if i % 3 == 0 {
+                        Err(anyhow::anyhow!("request {} rejected", i))
+                    } else {
+                        Ok(i)
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for (i, pending) in (0..8u64).zip(pending) {
+            match pending.await {
+                Ok(result) => assert_eq!(result, i),
+                Err(e) => {
+                    assert_eq!(i % 3, 0);
+                    assert_eq!(e.to_string(), format!("request {} rejected", i));
+                }
+            }
+        }
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+
+    // A check which panics is reported as a failure of that check alone, and
+    // releases its permit for the checks after it.
+    #[tokio::test]
+    async fn panicking_check_fails_only_its_request() {
+        let checker = StatelessChecker::new(1);
+        let panicked = checker.run(|| -> Result<()> { panic!("check panicked") });
+        let after = checker.run(|| Ok(()));
+
+        assert!(panicked.await.is_err());
+        after.await.unwrap();
+    }
+}
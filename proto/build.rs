@@ -64,6 +64,7 @@ fn main() -> Result<()> {
         &[
             "proto/client/oblivious.proto",
             "proto/client/specific.proto",
+            "proto/client/explorer.proto",
             "proto/view.proto",
             "proto/custody.proto",
         ],
@@ -102,6 +103,7 @@ static TYPE_ATTRIBUTES: &[(&str, &str)] = &[
     (".penumbra.stake.BaseRateData", SERIALIZE),
     (".penumbra.stake.Delegate", SERIALIZE),
     (".penumbra.stake.Undelegate", SERIALIZE),
+    (".penumbra.stake.UndelegateClaim", SERIALIZE),
     (".penumbra.stake.DelegationChanges", SERIALIZE),
     (".penumbra.stake.CommissionAmount", SERIALIZE),
     (".penumbra.stake.CommissionAmounts", SERIALIZE),
@@ -134,6 +136,9 @@ static TYPE_ATTRIBUTES: &[(&str, &str)] = &[
     (".penumbra.crypto.DiversifierIndex", SERDE_TRANSPARENT),
     (".penumbra.crypto.Nullifier", SERIALIZE),
     (".penumbra.crypto.Nullifier", SERDE_TRANSPARENT),
+    (".penumbra.crypto.PayloadKey", SERIALIZE),
+    (".penumbra.crypto.PayloadKey", SERDE_TRANSPARENT),
+    (".penumbra.crypto.PaymentDisclosure", SERIALIZE),
     (".penumbra.crypto.AuthPath", SERIALIZE),
     (".penumbra.chain.ChainParams", SERIALIZE),
     (".penumbra.chain.CompactBlock", SERIALIZE),
@@ -143,10 +148,9 @@ static TYPE_ATTRIBUTES: &[(&str, &str)] = &[
     (".penumbra.chain.NoteSource", SERDE_TRANSPARENT),
     (".penumbra.chain.GenesisAppState", SERIALIZE),
     (".penumbra.chain.GenesisAllocation", SERIALIZE),
-    (".penumbra.chain.Quarantined", SERIALIZE),
-    (".penumbra.chain.QuarantinedPerValidator", SERIALIZE),
     (".penumbra.view.NoteRecord", SERIALIZE),
-    (".penumbra.view.QuarantinedNoteRecord", SERIALIZE),
+    (".penumbra.view.DelegationPosition", SERIALIZE),
+    (".penumbra.view.PendingUndelegation", SERIALIZE),
     (".penumbra.transaction.TransactionPlan", SERIALIZE),
     (".penumbra.transaction.Fee", SERIALIZE),
     (".penumbra.transaction.ActionPlan", SERIALIZE),
@@ -178,6 +182,8 @@ static FIELD_ATTRIBUTES: &[(&str, &str)] = &[
     ),
     (".penumbra.crypto.FullViewingKeyHash.inner", AS_HEX),
     (".penumbra.crypto.Diversifier.inner", AS_HEX),
+    (".penumbra.crypto.PayloadKey.inner", AS_HEX),
+    (".penumbra.crypto.PaymentDisclosure.tx_hash", AS_HEX),
     (".penumbra.crypto.DiversifierIndex.inner", AS_HEX),
     (".penumbra.crypto.IdentityKey.ik", AS_BECH32_IDENTITY_KEY),
     (".penumbra.crypto.Note.note_blinding", AS_HEX),
@@ -192,6 +198,8 @@ static FIELD_ATTRIBUTES: &[(&str, &str)] = &[
     ),
     (".penumbra.crypto.Nullifier.inner", AS_HEX),
     (".penumbra.chain.NoteSource.inner", AS_HEX),
+    (".penumbra.chain.ChainParams.spend_verifying_key", AS_HEX),
+    (".penumbra.chain.ChainParams.output_verifying_key", AS_HEX),
     (
         ".penumbra.transaction.SpendPlan.randomizer",
         AS_HEX_FOR_BYTES,
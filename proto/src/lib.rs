@@ -56,6 +56,9 @@ pub mod client {
     pub mod specific {
         tonic::include_proto!("penumbra.client.specific");
     }
+    pub mod explorer {
+        tonic::include_proto!("penumbra.client.explorer");
+    }
 }
 
 /// IBC protocol structures.
@@ -8,10 +8,14 @@ use tokio::sync::RwLock;
 
 mod metrics;
 mod overlay_ext;
+mod proof;
+mod prune;
 mod storage;
 
 pub use crate::metrics::register_metrics;
 pub use overlay_ext::StateExt;
+pub use proof::{verify_key_value, JmtLeaf, JmtProof, ProvenValue};
+pub use prune::{PrunePolicy, PruneStats};
 pub use storage::Storage;
 
 pub type State = Arc<RwLock<WriteOverlay<Storage>>>;
@@ -19,8 +19,49 @@ pub fn register_metrics() {
     describe_gauge!(
         TCT_SIZE_BYTES,
         Unit::Bytes,
-        "The size of the serialized TCT in bytes"
+        "The size of the stored log of TCT updates in bytes"
+    );
+
+    register_counter!(JMT_PRUNE_TOTAL);
+    describe_counter!(
+        JMT_PRUNE_TOTAL,
+        Unit::Count,
+        "The total number of times stale JMT nodes have been pruned"
+    );
+
+    register_counter!(JMT_PRUNED_NODES_TOTAL);
+    describe_counter!(
+        JMT_PRUNED_NODES_TOTAL,
+        Unit::Count,
+        "The total number of stale JMT nodes deleted by pruning"
+    );
+
+    register_counter!(JMT_PRUNED_BYTES_TOTAL);
+    describe_counter!(
+        JMT_PRUNED_BYTES_TOTAL,
+        Unit::Bytes,
+        "The total size of the stale JMT nodes deleted by pruning in bytes"
+    );
+
+    register_gauge!(JMT_OLDEST_RETAINED_VERSION);
+    describe_gauge!(
+        JMT_OLDEST_RETAINED_VERSION,
+        Unit::Count,
+        "The oldest version of the JMT which has not been pruned"
+    );
+
+    register_histogram!(JMT_PRUNE_DURATION_SECONDS);
+    describe_histogram!(
+        JMT_PRUNE_DURATION_SECONDS,
+        Unit::Seconds,
+        "The time taken to prune stale JMT nodes"
     );
 }
 
 pub const TCT_SIZE_BYTES: &str = "penumbra_storage_tct_size_bytes";
+
+pub const JMT_PRUNE_TOTAL: &str = "penumbra_storage_jmt_prune_total";
+pub const JMT_PRUNED_NODES_TOTAL: &str = "penumbra_storage_jmt_pruned_nodes_total";
+pub const JMT_PRUNED_BYTES_TOTAL: &str = "penumbra_storage_jmt_pruned_bytes_total";
+pub const JMT_OLDEST_RETAINED_VERSION: &str = "penumbra_storage_jmt_oldest_retained_version";
+pub const JMT_PRUNE_DURATION_SECONDS: &str = "penumbra_storage_jmt_prune_duration_seconds";
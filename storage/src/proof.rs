@@ -0,0 +1,206 @@
+//! Proven key-value queries, which can be verified by clients without trusting
+//! the node which served them.
+
+use anyhow::{anyhow, Context, Result};
+use jmt::{proof::SparseMerkleProof, KeyHash, RootHash, ValueHash, Version};
+use penumbra_proto::{client::specific as pb, Protobuf};
+use serde::{Deserialize, Serialize};
+
+/// The value of a key (or its absence) at some version of the JMT, along with
+/// the root hash of that version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProvenValue {
+    /// The version (block height) of the JMT the value was read from.
+    pub version: Version,
+    /// The root hash of the JMT at that version.
+    pub root: RootHash,
+    /// The value of the key, or `None` if the key had no value.
+    pub value: Option<Vec<u8>>,
+}
+
+/// A proof of the value of a key in the JMT, or of the absence of any value,
+/// as it is sent to clients.
+///
+/// This converts to and from [`SparseMerkleProof`], which can be used to
+/// verify it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JmtProof {
+    /// The leaf at the end of the key's path, or `None` if the path ends in an
+    /// empty subtree.
+    pub leaf: Option<JmtLeaf>,
+    /// The hashes of the siblings of the nodes on the path, from the bottom of
+    /// the tree to the root.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// A leaf of the JMT, as it appears in a [`JmtProof`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JmtLeaf {
+    /// The hash of the leaf's key.
+    pub key_hash: KeyHash,
+    /// The hash of the leaf's value.
+    pub value_hash: ValueHash,
+}
+
+// `jmt` doesn't expose the constructor or all the fields of its proofs, so
+// these conversions go through their (identically shaped) serializations
+// instead.
+
+impl From<SparseMerkleProof> for JmtProof {
+    fn from(proof: SparseMerkleProof) -> Self {
+        bincode::deserialize(&bincode::serialize(&proof).expect("proofs can be serialized"))
+            .expect("`JmtProof` has the same shape as `SparseMerkleProof`")
+    }
+}
+
+impl From<JmtProof> for SparseMerkleProof {
+    fn from(proof: JmtProof) -> Self {
+        bincode::deserialize(&bincode::serialize(&proof).expect("proofs can be serialized"))
+            .expect("`JmtProof` has the same shape as `SparseMerkleProof`")
+    }
+}
+
+impl Protobuf<pb::JmtProof> for JmtProof {}
+
+impl From<JmtProof> for pb::JmtProof {
+    fn from(proof: JmtProof) -> Self {
+        pb::JmtProof {
+            leaf: proof.leaf.map(|leaf| pb::JmtLeaf {
+                key_hash: leaf.key_hash.0.to_vec(),
+                value_hash: leaf.value_hash.0.to_vec(),
+            }),
+            siblings: proof
+                .siblings
+                .into_iter()
+                .map(|sibling| sibling.to_vec())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::JmtProof> for JmtProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: pb::JmtProof) -> Result<Self, Self::Error> {
+        let hash = |bytes: Vec<u8>| -> Result<[u8; 32]> {
+            bytes
+                .try_into()
+                .map_err(|_| anyhow!("JMT proof hashes must be 32 bytes"))
+        };
+
+        Ok(JmtProof {
+            leaf: proof
+                .leaf
+                .map(|leaf| -> Result<_> {
+                    Ok(JmtLeaf {
+                        key_hash: KeyHash(hash(leaf.key_hash)?),
+                        value_hash: ValueHash(hash(leaf.value_hash)?),
+                    })
+                })
+                .transpose()?,
+            siblings: proof
+                .siblings
+                .into_iter()
+                .map(hash)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Verifies the proof in the response to a proven [`KeyValueRequest`] for the
+/// key with the given hash against `trusted_root`, returning the proven value.
+///
+/// The root hash in the response comes from the same node as the proof, so it
+/// is never used for verification: `trusted_root` should instead be the app
+/// hash committed to by the block header following the queried height, which
+/// can be fetched using `penumbra_view::committed_root`.
+///
+/// [`KeyValueRequest`]: penumbra_proto::client::specific::KeyValueRequest
+pub fn verify_key_value(
+    key_hash: KeyHash,
+    trusted_root: RootHash,
+    response: pb::KeyValueResponse,
+) -> Result<ProvenValue> {
+    if !response.root.is_empty() && response.root != trusted_root.0 {
+        return Err(anyhow!(
+            "node claims root hash {} at height {}, but the trusted root hash is {}",
+            hex::encode(&response.root),
+            response.height,
+            hex::encode(trusted_root.0)
+        ));
+    }
+    let proof: SparseMerkleProof = JmtProof::try_from(
+        response
+            .jmt_proof
+            .ok_or_else(|| anyhow!("response does not contain a proof"))?,
+    )
+    .context("invalid JMT proof in response")?
+    .into();
+
+    let value = if response.exists {
+        Some(response.value)
+    } else {
+        None
+    };
+    proof
+        .verify(trusted_root, key_hash, value.as_ref())
+        .with_context(|| {
+            format!(
+                "proof does not verify against trusted root hash {} at height {}",
+                hex::encode(trusted_root.0),
+                response.height
+            )
+        })?;
+
+    Ok(ProvenValue {
+        version: response.height,
+        root: trusted_root,
+        value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::Storage;
+
+    // Proofs of values and of absence both survive the trip through their
+    // protobuf encoding, and still verify against the root they were made for.
+    #[tokio::test]
+    async fn proofs_verify_after_protobuf_round_trip() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("proof-testing.db"))
+            .await
+            .unwrap();
+        let state = storage.state().await.unwrap();
+        for key in ["present", "other"] {
+            state.write().await.put(key.into(), key.as_bytes().to_vec());
+        }
+        state.write().await.commit(storage.clone()).await.unwrap();
+
+        for key in ["present", "absent"] {
+            let key_hash: KeyHash = key.into();
+            let (proven, proof) = storage.get_with_proof(key_hash, None).await.unwrap();
+            let proof = JmtProof::from(proof);
+            let decoded = JmtProof::decode(&proof.encode_to_vec()[..]).unwrap();
+            assert_eq!(decoded, proof);
+
+            let response = pb::KeyValueResponse {
+                exists: proven.value.is_some(),
+                value: proven.value.clone().unwrap_or_default(),
+                height: proven.version,
+                jmt_proof: Some(decoded.into()),
+                ..Default::default()
+            };
+            assert_eq!(
+                verify_key_value(key_hash, proven.root, response.clone()).unwrap(),
+                proven
+            );
+
+            // The proof is checked against the trusted root, not just decoded
+            assert!(verify_key_value(key_hash, RootHash([0; 32]), response).is_err());
+        }
+    }
+}
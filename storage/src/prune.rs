@@ -0,0 +1,193 @@
+//! Pruning of old versions of the JMT.
+//!
+//! Every commit writes new nodes for each path in the JMT that it changes,
+//! without removing the nodes they replace, so that old versions of the tree
+//! remain readable. Pruning removes the nodes which can no longer be reached
+//! from any version of the tree that we want to keep.
+
+use std::{collections::HashSet, time::Instant};
+
+use anyhow::Result;
+use jmt::{storage::NodeKey, Version};
+use rocksdb::{WriteBatch, DB};
+
+use crate::metrics;
+
+/// How many nodes to delete in each write to RocksDB, so that pruning doesn't
+/// hold on to the database for too long at once.
+const DELETE_CHUNK_SIZE: usize = 10_000;
+
+/// When and how much of the JMT's history to prune.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrunePolicy {
+    /// The number of most recent versions of the JMT to keep, including the
+    /// latest one.
+    ///
+    /// This bounds how far back historical queries can be served, so it should
+    /// be large enough for any client that needs to prove values at past
+    /// heights, and to cover the heights of any state-sync snapshots.
+    pub keep_versions: u64,
+    /// The number of versions to commit between each round of pruning.
+    pub interval: u64,
+}
+
+impl Default for PrunePolicy {
+    fn default() -> Self {
+        Self {
+            keep_versions: 100_000,
+            interval: 1_000,
+        }
+    }
+}
+
+impl PrunePolicy {
+    /// The oldest version to keep when the latest version is `latest`, or
+    /// `None` if there are not yet enough versions for anything to be pruned.
+    pub fn oldest_retained(&self, latest: Version) -> Option<Version> {
+        let oldest = latest.checked_sub(self.keep_versions.saturating_sub(1))?;
+        if oldest == 0 {
+            None
+        } else {
+            Some(oldest)
+        }
+    }
+}
+
+/// What was removed by a round of pruning.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PruneStats {
+    /// The oldest version of the JMT which can still be read.
+    pub oldest_retained: Version,
+    /// The number of stale nodes deleted.
+    pub deleted_nodes: u64,
+    /// The total size of the keys and values of the deleted nodes, in bytes.
+    pub deleted_bytes: u64,
+}
+
+/// Deletes every node in the `jmt` column family which can't be reached from
+/// the tree at `oldest_retained` or any later version.
+///
+/// Node keys sort by version first, so walking backwards from
+/// `oldest_retained`, the first node we see at each nibble path is the one in
+/// the tree at `oldest_retained`, and every earlier node at the same path has
+/// been replaced, which means no later version can refer to it either.
+///
+/// The JMT doesn't support deletion (deleted values are overwritten with a
+/// tombstone instead), so every node which is no longer in the tree has been
+/// replaced by a newer node at the same path.
+pub(crate) fn prune_jmt(db: &DB, oldest_retained: Version) -> Result<PruneStats> {
+    let start = Instant::now();
+    let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
+
+    let mut stats = PruneStats {
+        oldest_retained,
+        ..Default::default()
+    };
+    let mut seen = HashSet::new();
+    let mut batch = WriteBatch::default();
+
+    // Every key for a version after `oldest_retained` is longer than (and so
+    // sorts after) this prefix, while every key for an earlier version sorts
+    // before it.
+    let mut iter = db.raw_iterator_cf(jmt_cf);
+    iter.seek_for_prev((oldest_retained + 1).to_be_bytes());
+    while let (Some(key), Some(value)) = (iter.key(), iter.value()) {
+        let node_key = NodeKey::decode(key)?;
+        if !seen.insert(node_key.nibble_path().clone()) {
+            batch.delete_cf(jmt_cf, key);
+            stats.deleted_nodes += 1;
+            stats.deleted_bytes += (key.len() + value.len()) as u64;
+
+            if batch.len() >= DELETE_CHUNK_SIZE {
+                db.write(std::mem::take(&mut batch))?;
+            }
+        }
+        iter.prev();
+    }
+    iter.status()?;
+    db.write(batch)?;
+
+    metrics::increment_counter!(metrics::JMT_PRUNE_TOTAL);
+    metrics::counter!(metrics::JMT_PRUNED_NODES_TOTAL, stats.deleted_nodes);
+    metrics::counter!(metrics::JMT_PRUNED_BYTES_TOTAL, stats.deleted_bytes);
+    metrics::gauge!(metrics::JMT_OLDEST_RETAINED_VERSION, oldest_retained as f64);
+    metrics::histogram!(
+        metrics::JMT_PRUNE_DURATION_SECONDS,
+        start.elapsed().as_secs_f64()
+    );
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use jmt::KeyHash;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::Storage;
+
+    // Retained versions must still be readable and provable after pruning,
+    // including values which were overwritten or deleted in later versions.
+    #[tokio::test]
+    async fn pruning_keeps_retained_versions_provable() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("prune-testing.db"))
+            .await
+            .unwrap();
+        let key = |i: u64| -> KeyHash { format!("test/{}", i).into() };
+
+        // Each version adds a key, overwrites another, and deletes a third,
+        // which the JMT represents as an empty value.
+        let mut values = BTreeMap::new();
+        let mut snapshots = Vec::new();
+        for version in 0..10u64 {
+            let state = storage.state().await.unwrap();
+            let mut writes = vec![(version + 2, format!("added in {}", version).into_bytes())];
+            if version >= 1 {
+                writes.push((version, format!("overwritten in {}", version).into_bytes()));
+            }
+            if version >= 2 {
+                writes.push((version - 2, Vec::new()));
+            }
+            for (i, value) in writes {
+                state.write().await.put(key(i), value.clone());
+                values.insert(i, value);
+            }
+            let (root, committed) = state.write().await.commit(storage.clone()).await.unwrap();
+            assert_eq!(committed, version);
+            snapshots.push((root, values.clone()));
+        }
+
+        let stats = storage
+            .prune(PrunePolicy {
+                keep_versions: 4,
+                interval: 1,
+            })
+            .await
+            .unwrap()
+            .expect("there are enough versions to prune");
+        assert_eq!(stats.oldest_retained, 6);
+        assert!(stats.deleted_nodes > 0);
+
+        for (version, (root, values)) in snapshots.into_iter().enumerate() {
+            let version = version as Version;
+            // Also check a key which was never written.
+            for i in 0..=values.len() as u64 + 2 {
+                let result = storage.get_with_proof(key(i), Some(version)).await;
+                if version < stats.oldest_retained {
+                    let e = result.expect_err("pruned versions can't be read");
+                    assert!(e.to_string().contains("has been pruned"));
+                    continue;
+                }
+
+                let (proven, proof) = result.unwrap();
+                assert_eq!(proven.root, root);
+                assert_eq!(proven.value.as_ref(), values.get(&i));
+                proof.verify(root, key(i), proven.value.as_ref()).unwrap();
+            }
+        }
+    }
+}
@@ -1,22 +1,36 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
-use ::metrics::gauge;
+use ::metrics::{gauge, increment_gauge};
 use anyhow::Result;
 use futures::future::BoxFuture;
 use jmt::{
+    proof::SparseMerkleProof,
     storage::{Node, NodeBatch, NodeKey, TreeReader, TreeWriter},
-    WriteOverlay,
+    KeyHash, WriteOverlay,
 };
-use rocksdb::{Options, DB};
+use rocksdb::{Direction, IteratorMode, Options, WriteBatch, DB};
 use tokio::sync::RwLock;
 use tracing::{instrument, Span};
 
 use penumbra_tct as tct;
 
-use crate::{metrics, State};
+use crate::{
+    metrics,
+    proof::ProvenValue,
+    prune::{self, PrunePolicy, PruneStats},
+    State,
+};
 
 #[derive(Clone, Debug)]
-pub struct Storage(Arc<DB>);
+pub struct Storage {
+    db: Arc<DB>,
+    /// The encoded transactions of the block being committed, which are
+    /// written along with the tree nodes of the next commit.
+    staged_transactions: Arc<Mutex<Vec<Vec<u8>>>>,
+}
 
 impl Storage {
     pub async fn load(path: PathBuf) -> Result<Self> {
@@ -30,7 +44,36 @@ impl Storage {
                     opts.create_if_missing(true);
                     opts.create_missing_column_families(true);
 
-                    Ok(Self(Arc::new(DB::open_cf(&opts, path, ["jmt", "nct"])?)))
+                    Ok(Self {
+                        db: Arc::new(DB::open_cf(&opts, path, ["jmt", "nct", "transactions"])?),
+                        staged_transactions: Default::default(),
+                    })
+                })
+            })
+            .await
+            .unwrap()
+    }
+
+    /// Opens the database at `path` for inspection, without creating or
+    /// otherwise writing to it.
+    ///
+    /// Writes through the returned [`Storage`] fail.
+    pub async fn load_read_only(path: PathBuf) -> Result<Self> {
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("open_rocksdb_read_only")
+            .spawn_blocking(move || {
+                span.in_scope(|| {
+                    tracing::info!(?path, "opening rocksdb read-only");
+                    let opts = Options::default();
+                    // Only open the column families that already exist, since
+                    // a read-only database can't create the others.
+                    let cfs = DB::list_cf(&opts, &path)?;
+
+                    Ok(Self {
+                        db: Arc::new(DB::open_cf_for_read_only(&opts, &path, cfs, false)?),
+                        staged_transactions: Default::default(),
+                    })
                 })
             })
             .await
@@ -46,6 +89,101 @@ impl Storage {
             .map(|(node_key, _)| node_key.version()))
     }
 
+    /// Reads the value of the key with the given hash at a version of the
+    /// tree, or at the latest version if `version` is `None`, along with a
+    /// proof of its existence or absence which can be checked using
+    /// [`verify_key_value`](crate::verify_key_value).
+    pub async fn get_with_proof(
+        &self,
+        key_hash: KeyHash,
+        version: Option<jmt::Version>,
+    ) -> Result<(ProvenValue, SparseMerkleProof)> {
+        let latest = self
+            .latest_version()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("no versions of the tree have been committed"))?;
+        let version = version.unwrap_or(latest);
+        if version > latest {
+            return Err(anyhow::anyhow!(
+                "version {} has not been committed (the latest version is {})",
+                version,
+                latest
+            ));
+        }
+
+        // Every commit writes a new root, so if the root is gone, so is the rest
+        // of the tree at that version
+        if self
+            .get_node_option(&NodeKey::new_empty_path(version))
+            .await?
+            .is_none()
+        {
+            return Err(anyhow::anyhow!("version {} has been pruned", version));
+        }
+
+        let tree = jmt::JellyfishMerkleTree::new(self);
+        let (value, proof) = tree.get_with_proof(key_hash, version).await?;
+        let root = tree.get_root_hash(version).await?;
+
+        Ok((
+            ProvenValue {
+                version,
+                root,
+                value,
+            },
+            proof,
+        ))
+    }
+
+    /// Deletes the nodes of the tree which are only needed to read versions
+    /// older than the most recent `policy.keep_versions`, returning `None` if
+    /// there are not yet enough versions for anything to be pruned.
+    ///
+    /// This runs alongside commits rather than blocking them, since commits
+    /// only ever write nodes at newer versions than the ones it deletes.
+    pub async fn prune(&self, policy: PrunePolicy) -> Result<Option<PruneStats>> {
+        let oldest_retained = match self.latest_version().await? {
+            Some(latest) => match policy.oldest_retained(latest) {
+                Some(oldest_retained) => oldest_retained,
+                None => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+
+        let db = self.db.clone();
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("prune_jmt")
+            .spawn_blocking(move || {
+                span.in_scope(|| {
+                    tracing::debug!(oldest_retained, "pruning JMT");
+                    let stats = prune::prune_jmt(&db, oldest_retained)?;
+                    tracing::info!(?stats, "pruned JMT");
+                    Ok(Some(stats))
+                })
+            })
+            .await?
+    }
+
+    /// Compacts the `jmt` column family, so that the space freed by
+    /// [`Self::prune`] is returned to the filesystem.
+    ///
+    /// This rewrites the whole column family, so it's best run offline.
+    pub async fn compact_jmt(&self) -> Result<()> {
+        let db = self.db.clone();
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("compact_jmt")
+            .spawn_blocking(move || {
+                span.in_scope(|| {
+                    let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
+                    db.compact_range_cf(jmt_cf, None::<&[u8]>, None::<&[u8]>);
+                    Ok(())
+                })
+            })
+            .await?
+    }
+
     /// Returns a new [`State`] on top of the latest version of the tree.
     pub async fn state(&self) -> Result<State> {
         // If the tree is empty, use PRE_GENESIS_VERSION as the version,
@@ -74,13 +212,16 @@ impl Storage {
             .map_err(|e| tonic::Status::internal(e.to_string()))
     }
 
-    pub async fn put_nct(&self, tct: &tct::Tree) -> Result<()> {
-        let db = self.0.clone();
+    /// Appends the updates made to the TCT since they were last taken to its log
+    /// of updates in storage, compacting the log every
+    /// [`NCT_COMPACTION_INTERVAL`] appends.
+    pub async fn put_nct(&self, tct: &mut tct::Tree) -> Result<()> {
+        let db = self.db.clone();
 
-        tracing::debug!("serializing TCT");
-        let tct_data = bincode::serialize(tct)?;
-        tracing::debug!(tct_bytes = tct_data.len(), "serialized TCT");
-        gauge!(metrics::TCT_SIZE_BYTES, tct_data.len() as f64);
+        let batch = tct::storage::Batch::take(tct);
+        tracing::debug!(updates = batch.updates.len(), "encoding TCT updates");
+        let update_data = batch.encode();
+        tracing::debug!(update_bytes = update_data.len(), "encoded TCT updates");
 
         let span = Span::current();
         tokio::task::Builder::new()
@@ -88,7 +229,13 @@ impl Storage {
             .spawn_blocking(move || {
                 span.in_scope(|| {
                     let nct_cf = db.cf_handle("nct").expect("nct column family not found");
-                    db.put_cf(nct_cf, "tct", &tct_data)?;
+                    let index = last_nct_index(&db)?.map_or(0, |last| last + 1);
+                    db.put_cf(nct_cf, index.to_be_bytes(), &update_data)?;
+                    increment_gauge!(metrics::TCT_SIZE_BYTES, update_data.len() as f64);
+
+                    if index >= NCT_COMPACTION_INTERVAL {
+                        compact_nct(&db)?;
+                    }
                     Ok::<_, anyhow::Error>(())
                 })
             })
@@ -96,24 +243,149 @@ impl Storage {
     }
 
     pub async fn get_nct(&self) -> Result<tct::Tree> {
-        let db = self.0.clone();
+        let db = self.db.clone();
         let span = Span::current();
         tokio::task::Builder::new()
             .name("get_nct")
             .spawn_blocking(move || {
                 span.in_scope(|| {
-                    let nct_cf = db.cf_handle("nct").expect("nct column family not found");
-                    if let Some(tct_bytes) = db.get_cf(nct_cf, "tct")? {
-                        Ok(bincode::deserialize(&tct_bytes)?)
-                    } else {
-                        Ok(tct::Tree::new())
+                    let (_, batches) = nct_batches(&db)?;
+                    tracing::debug!(batches = batches.len(), "replaying TCT updates");
+                    Ok(tct::Tree::from_batches(batches)?)
+                })
+            })
+            .await?
+    }
+
+    /// Stages the encoded transactions included in the block being committed,
+    /// in order, to be written in the same batch as the tree nodes of the
+    /// next commit.
+    ///
+    /// Like the NCT, the transactions are stored outside of the main state,
+    /// since they're only retained to serve queries.  Writing them with the
+    /// tree means that every committed height has its transactions, even if
+    /// pd stops partway through a commit.
+    pub fn stage_block_transactions(&self, transactions: Vec<Vec<u8>>) {
+        *self
+            .staged_transactions
+            .lock()
+            .expect("staged transactions lock is not poisoned") = transactions;
+    }
+
+    /// Returns the encoded transactions included in the block at `height`, in
+    /// order.
+    pub async fn block_transactions(&self, height: u64) -> Result<Vec<Vec<u8>>> {
+        let db = self.db.clone();
+        let span = Span::current();
+        tokio::task::Builder::new()
+            .name("block_transactions")
+            .spawn_blocking(move || {
+                span.in_scope(|| {
+                    let txs_cf = db
+                        .cf_handle("transactions")
+                        .expect("transactions column family not found");
+                    let start = transaction_key(height, 0);
+                    let mut transactions = Vec::new();
+                    for (key, value) in
+                        db.iterator_cf(txs_cf, IteratorMode::From(&start, Direction::Forward))
+                    {
+                        if key[..8] != start[..8] {
+                            break;
+                        }
+                        transactions.push(value.into_vec());
                     }
+                    Ok(transactions)
                 })
             })
             .await?
     }
 }
 
+/// How many times updates are appended to the TCT's log before it is compacted.
+pub const NCT_COMPACTION_INTERVAL: u64 = 1000;
+
+/// Decodes the key of an entry in the TCT's log of updates.
+///
+/// Each entry in the `nct` column family holds the updates from one commit,
+/// keyed by its big-endian index in the log, so that iteration replays them in
+/// order.
+fn nct_index(key: &[u8]) -> Result<u64> {
+    let key: [u8; 8] = key.try_into().map_err(|_| {
+        anyhow::anyhow!(
+            "unrecognized TCT storage key {:?}; storage must be reset",
+            key
+        )
+    })?;
+    Ok(u64::from_be_bytes(key))
+}
+
+/// Returns the index of the last entry in the TCT's log of updates, if any.
+fn last_nct_index(db: &DB) -> Result<Option<u64>> {
+    let nct_cf = db.cf_handle("nct").expect("nct column family not found");
+    let mut iter = db.raw_iterator_cf(nct_cf);
+    iter.seek_to_last();
+    iter.key().map(nct_index).transpose()
+}
+
+/// Reads the TCT's log of updates, returning the index of the last entry in the
+/// log (if any) along with all the batches of updates, in order.
+fn nct_batches(db: &DB) -> Result<(Option<u64>, Vec<tct::storage::Batch>)> {
+    let nct_cf = db.cf_handle("nct").expect("nct column family not found");
+
+    let mut last = None;
+    let mut batches = Vec::new();
+    let mut size = 0;
+    for (key, value) in db.iterator_cf(nct_cf, IteratorMode::Start) {
+        last = Some(nct_index(&key)?);
+        size += value.len();
+        batches.push(tct::storage::Batch::decode(&value)?);
+    }
+    gauge!(metrics::TCT_SIZE_BYTES, size as f64);
+
+    Ok((last, batches))
+}
+
+/// Replaces the TCT's log of updates with a single compacted entry.
+fn compact_nct(db: &DB) -> Result<()> {
+    let nct_cf = db.cf_handle("nct").expect("nct column family not found");
+
+    let (last, batches) = nct_batches(db)?;
+    let updates: Vec<_> = batches
+        .into_iter()
+        .flat_map(|batch| batch.updates)
+        .collect();
+    let update_count = updates.len();
+    let compacted = tct::storage::compact(updates)?;
+    let compacted_data = compacted.encode();
+    tracing::debug!(
+        update_count,
+        compacted_count = compacted.updates.len(),
+        compacted_bytes = compacted_data.len(),
+        "compacted TCT updates"
+    );
+
+    let mut batch = WriteBatch::default();
+    for index in 1..=last.unwrap_or(0) {
+        batch.delete_cf(nct_cf, index.to_be_bytes());
+    }
+    batch.put_cf(nct_cf, 0u64.to_be_bytes(), &compacted_data);
+    db.write(batch)?;
+    gauge!(metrics::TCT_SIZE_BYTES, compacted_data.len() as f64);
+
+    Ok(())
+}
+
+/// The key of the `index`th transaction in the block at `height`.
+///
+/// Keys are big-endian, so that the transactions in a block are stored
+/// contiguously and in order.
+fn transaction_key(height: u64, index: u64) -> [u8; 16] {
+    let mut key = [0u8; 16];
+    key[..8].copy_from_slice(&height.to_be_bytes());
+    key[8..].copy_from_slice(&index.to_be_bytes());
+    key
+}
+
 impl TreeWriter for Storage {
     /// Writes a node batch into storage.
     //TODO: Change JMT traits to remove/simplify lifetimes & accept owned NodeBatch
@@ -122,8 +394,14 @@ impl TreeWriter for Storage {
         &'a mut self,
         node_batch: &'n NodeBatch,
     ) -> BoxFuture<'future, Result<()>> {
-        let db = self.0.clone();
+        let db = self.db.clone();
         let node_batch = node_batch.clone();
+        let transactions = std::mem::take(
+            &mut *self
+                .staged_transactions
+                .lock()
+                .expect("staged transactions lock is not poisoned"),
+        );
 
         // The writes have to happen on a separate spawn_blocking task, but we
         // want tracing events to occur in the context of the current span, so
@@ -135,15 +413,40 @@ impl TreeWriter for Storage {
                 .name("Storage::write_node_batch")
                 .spawn_blocking(move || {
                     span.in_scope(|| {
-                        for (node_key, node) in node_batch.clone() {
+                        let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
+                        let mut batch = WriteBatch::default();
+                        for (node_key, node) in node_batch.iter() {
                             let key_bytes = &node_key.encode()?;
                             let value_bytes = &node.encode()?;
                             tracing::trace!(?key_bytes, value_bytes = ?hex::encode(&value_bytes));
 
-                            let jmt_cf = db.cf_handle("jmt").expect("jmt column family not found");
-                            db.put_cf(jmt_cf, key_bytes, &value_bytes)?;
+                            batch.put_cf(jmt_cf, key_bytes, &value_bytes);
+                        }
+
+                        // The block's transactions go in the same write as its
+                        // tree nodes, so that either both are committed or neither is.
+                        if !transactions.is_empty() {
+                            let height =
+                                node_batch
+                                    .keys()
+                                    .map(NodeKey::version)
+                                    .max()
+                                    .ok_or_else(|| {
+                                        anyhow::anyhow!("no tree nodes to commit transactions with")
+                                    })?;
+                            let txs_cf = db
+                                .cf_handle("transactions")
+                                .expect("transactions column family not found");
+                            for (index, transaction) in transactions.into_iter().enumerate() {
+                                batch.put_cf(
+                                    txs_cf,
+                                    transaction_key(height, index as u64),
+                                    transaction,
+                                );
+                            }
                         }
 
+                        db.write(batch)?;
                         Ok(())
                     })
                 })
@@ -162,7 +465,7 @@ impl TreeReader for Storage {
         &'a self,
         node_key: &'n NodeKey,
     ) -> BoxFuture<'future, Result<Option<Node>>> {
-        let db = self.0.clone();
+        let db = self.db.clone();
         let node_key = node_key.clone();
 
         let span = Span::current();
@@ -191,7 +494,7 @@ impl TreeReader for Storage {
         &'a self,
     ) -> BoxFuture<'future, Result<Option<(NodeKey, jmt::storage::LeafNode)>>> {
         let span = Span::current();
-        let db = self.0.clone();
+        let db = self.db.clone();
 
         Box::pin(async {
             tokio::task::Builder::new()
@@ -221,3 +524,50 @@ impl TreeReader for Storage {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use jmt::KeyHash;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    // A block's transactions are only written when its tree nodes are, and
+    // are kept separate from every other block's.
+    #[tokio::test]
+    async fn block_transactions_are_committed_with_the_tree() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::load(dir.path().join("transactions-testing.db"))
+            .await
+            .unwrap();
+
+        let blocks = vec![
+            vec![b"first".to_vec(), b"second".to_vec()],
+            vec![],
+            vec![b"third".to_vec()],
+        ];
+        for (height, transactions) in blocks.iter().enumerate() {
+            let height = height as u64;
+            storage.stage_block_transactions(transactions.clone());
+            assert!(storage.block_transactions(height).await.unwrap().is_empty());
+
+            let state = storage.state().await.unwrap();
+            let key: KeyHash = format!("test/{}", height).into();
+            state.write().await.put(key, b"value".to_vec());
+            let (_, version) = state.write().await.commit(storage.clone()).await.unwrap();
+            assert_eq!(version, height);
+        }
+
+        for (height, transactions) in blocks.iter().enumerate() {
+            assert_eq!(
+                &storage.block_transactions(height as u64).await.unwrap(),
+                transactions
+            );
+        }
+        assert!(storage
+            .block_transactions(blocks.len() as u64)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+#[macro_use]
+extern crate proptest_derive;
+
+use proptest::{arbitrary::*, prelude::*};
+
+use penumbra_tct::{
+    proptest::CommitmentStrategy, validate, Commitment, Position, Root, Tree, Witness,
+};
+
+const MAX_USED_COMMITMENTS: usize = 3;
+const MAX_TIER_ACTIONS: usize = 20;
+
+#[derive(Debug, Copy, Clone, Arbitrary)]
+#[proptest(params("Vec<Commitment>"))]
+enum Action {
+    EndBlock,
+    EndEpoch,
+    Forget(#[proptest(strategy = "CommitmentStrategy::one_of(params.clone())")] Commitment),
+    Insert(
+        Witness,
+        #[proptest(strategy = "CommitmentStrategy::one_of(params)")] Commitment,
+    ),
+}
+
+impl Action {
+    fn apply(&self, tree: &mut Tree) {
+        match self {
+            Action::EndBlock => {
+                tree.end_block().unwrap();
+            }
+            Action::EndEpoch => {
+                tree.end_epoch().unwrap();
+            }
+            Action::Forget(commitment) => {
+                tree.forget(*commitment);
+            }
+            Action::Insert(witness, commitment) => {
+                tree.insert(*witness, *commitment).unwrap();
+            }
+        }
+    }
+}
+
+/// Everything observable about a tree, in a form that can be compared for equality: its root,
+/// position, count of forgotten commitments, and witnessed commitments in order of position.
+type Observation = (Root, Option<Position>, u64, Vec<(Commitment, Position)>);
+
+fn observe(tree: &Tree) -> Observation {
+    let mut commitments: Vec<_> = tree.commitments().collect();
+    commitments.sort_by_key(|(_, position)| *position);
+    (
+        tree.root(),
+        tree.position(),
+        tree.forgotten().into(),
+        commitments,
+    )
+}
+
+/// Generate a pair of action sequences over the same small set of commitments, so that actions
+/// after a checkpoint can forget or re-insert commitments inserted before it.
+fn actions() -> impl Strategy<Value = (Vec<Action>, Vec<Action>)> {
+    prop::collection::vec(any::<Commitment>(), 1..MAX_USED_COMMITMENTS).prop_flat_map(
+        |commitments| {
+            (
+                prop::collection::vec(any_with::<Action>(commitments.clone()), 0..MAX_TIER_ACTIONS),
+                prop::collection::vec(any_with::<Action>(commitments), 0..MAX_TIER_ACTIONS),
+            )
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn rollback_restores_checkpoint((before, after) in actions()) {
+        let mut tree = Tree::new();
+        for action in &before {
+            action.apply(&mut tree);
+        }
+
+        let expected = observe(&tree);
+        let checkpoint = tree.checkpoint();
+
+        for action in &after {
+            action.apply(&mut tree);
+        }
+        tree.rollback(checkpoint);
+
+        assert_eq!(expected, observe(&tree));
+        for (commitment, _) in tree.commitments() {
+            let proof = tree.witness(commitment).unwrap();
+            assert!(proof.verify(tree.root()).is_ok());
+        }
+        validate::index(&tree).unwrap();
+        validate::all_proofs(&tree).unwrap();
+        validate::cached_hashes(&tree).unwrap();
+        validate::forgotten(&tree).unwrap();
+    }
+
+    #[test]
+    fn checkpoint_does_not_affect_tree((before, after) in actions()) {
+        // One tree is checkpointed between the two sequences of actions, the other is not
+        let mut tree = Tree::new();
+        let mut unshared = Tree::new();
+        for action in &before {
+            action.apply(&mut tree);
+            action.apply(&mut unshared);
+        }
+
+        let checkpoint = tree.checkpoint();
+        let checkpointed = observe(&tree);
+
+        for action in &after {
+            action.apply(&mut tree);
+            action.apply(&mut unshared);
+        }
+
+        // Modifying the tree must not modify the checkpoint, and sharing structure with the
+        // checkpoint must not change the result of modifying the tree
+        assert_eq!(checkpoint.root(), checkpointed.0);
+        assert_eq!(checkpoint.position(), checkpointed.1);
+        assert_eq!(observe(&unshared), observe(&tree));
+        validate::all_proofs(&tree).unwrap();
+        validate::cached_hashes(&tree).unwrap();
+    }
+
+    #[test]
+    fn rollback_restores_pending_updates((before, after) in actions()) {
+        let mut tree = Tree::new();
+        for action in &before {
+            action.apply(&mut tree);
+        }
+
+        let checkpoint = tree.checkpoint();
+        for action in &after {
+            action.apply(&mut tree);
+        }
+        tree.rollback(checkpoint);
+
+        // Replaying the updates after rolling back must reproduce the tree as of the checkpoint
+        let expected = observe(&tree);
+        let replayed = Tree::from_updates(tree.take_updates()).unwrap();
+        assert_eq!(expected.0, replayed.root());
+        assert_eq!(expected.1, replayed.position());
+        let mut commitments: Vec<_> = replayed.commitments().collect();
+        commitments.sort_by_key(|(_, position)| *position);
+        assert_eq!(expected.3, commitments);
+    }
+}
@@ -0,0 +1,58 @@
+use proptest::{arbitrary::*, prelude::*};
+
+use penumbra_tct::{Commitment, Root, Tree, Witness};
+
+const MAX_BLOCKS: usize = 10;
+const MAX_BLOCK_SIZE: usize = 4;
+const WINDOW: usize = 4;
+
+proptest! {
+    #[test]
+    fn witness_at_past_roots(
+        blocks in prop::collection::vec(
+            prop::collection::vec(any::<Commitment>(), 0..MAX_BLOCK_SIZE),
+            1..MAX_BLOCKS,
+        ),
+        forget in any::<bool>(),
+    ) {
+        let mut tree = Tree::new();
+        tree.set_history_window(WINDOW);
+
+        // The root at the end of each block, along with the commitments witnessed in it
+        let mut roots: Vec<(Root, Vec<Commitment>)> = Vec::new();
+        let mut witnessed = Vec::new();
+
+        for block in blocks {
+            for commitment in block {
+                tree.insert(Witness::Keep, commitment).unwrap();
+                witnessed.push(commitment);
+            }
+            tree.end_block().unwrap();
+            roots.push((tree.root(), witnessed.clone()));
+
+            // Forgetting commitments in the current tree must not affect past versions
+            if forget {
+                for commitment in witnessed.drain(..) {
+                    tree.forget(commitment);
+                }
+            }
+        }
+
+        let historical: Vec<Root> = tree.historical_roots().collect();
+        assert!(historical.len() <= WINDOW);
+
+        for (root, commitments) in roots {
+            let kept = historical.contains(&root) || root == tree.root();
+            for commitment in commitments {
+                match tree.witness_at(commitment, root) {
+                    Some(proof) => {
+                        assert!(kept);
+                        assert_eq!(proof.commitment(), commitment);
+                        assert!(proof.verify(root).is_ok());
+                    }
+                    None => assert!(!kept),
+                }
+            }
+        }
+    }
+}
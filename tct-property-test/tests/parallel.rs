@@ -0,0 +1,69 @@
+use proptest::{arbitrary::*, prelude::*};
+
+use penumbra_tct::{block, proptest::CommitmentStrategy, validate, Commitment, Tree, Witness};
+
+const MAX_USED_COMMITMENTS: usize = 50;
+// Enough commitments to fill more than four levels of a block
+const MAX_BLOCK_SIZE: usize = 300;
+
+/// Generate a block's worth of commitments, drawn from a small set so that some are repeated.
+fn commitments() -> impl Strategy<Value = Vec<(Witness, Commitment)>> {
+    prop::collection::vec(any::<Commitment>(), 1..MAX_USED_COMMITMENTS).prop_flat_map(
+        |commitments| {
+            prop::collection::vec(
+                (
+                    any::<Witness>(),
+                    CommitmentStrategy::one_of(commitments.clone()),
+                ),
+                0..MAX_BLOCK_SIZE,
+            )
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn from_commitments_matches_builder(commitments in commitments()) {
+        let mut builder = block::Builder::new();
+        for &(witness, commitment) in &commitments {
+            builder.insert(witness, commitment).unwrap();
+        }
+        let serial = builder.finalize();
+        let parallel = block::Finalized::from_commitments(commitments).unwrap();
+        assert_eq!(serial.root(), parallel.root());
+
+        // Both blocks must witness the same commitments at the same positions
+        let mut serial_tree = Tree::new();
+        serial_tree.insert_block(serial).unwrap();
+        let mut parallel_tree = Tree::new();
+        parallel_tree.insert_block(parallel).unwrap();
+
+        let mut serial_commitments: Vec<_> = serial_tree.commitments().collect();
+        serial_commitments.sort_by_key(|(_, position)| *position);
+        let mut parallel_commitments: Vec<_> = parallel_tree.commitments().collect();
+        parallel_commitments.sort_by_key(|(_, position)| *position);
+        assert_eq!(serial_commitments, parallel_commitments);
+        assert_eq!(serial_tree.take_updates(), parallel_tree.take_updates());
+
+        validate::index(&parallel_tree).unwrap();
+        validate::all_proofs(&parallel_tree).unwrap();
+        validate::cached_hashes(&parallel_tree).unwrap();
+    }
+
+    #[test]
+    fn from_commitments_matches_end_block(commitments in commitments()) {
+        let mut serial = Tree::new();
+        for &(witness, commitment) in &commitments {
+            serial.insert(witness, commitment).unwrap();
+        }
+        serial.end_block().unwrap();
+
+        let mut parallel = Tree::new();
+        parallel
+            .insert_block(block::Finalized::from_commitments(commitments).unwrap())
+            .unwrap();
+
+        assert_eq!(serial.root(), parallel.root());
+        assert_eq!(serial.position(), parallel.position());
+    }
+}
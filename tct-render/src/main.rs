@@ -0,0 +1,100 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use clap::{ArgEnum, Parser, Subcommand};
+use penumbra_tct::{structure::export, Tree};
+
+#[derive(Debug, Parser)]
+#[clap(
+    name = "tct-render",
+    about = "Render the note commitment tree stored by pd or the view service, for debugging."
+)]
+struct Opt {
+    /// The format to render the tree in.
+    #[clap(short, long, arg_enum, default_value = "dot")]
+    format: Format,
+    /// Write the rendered tree to this file, rather than to stdout.
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+    /// Where to load the tree from.
+    #[clap(subcommand)]
+    source: Source,
+}
+
+#[derive(Debug, Clone, Copy, ArgEnum)]
+enum Format {
+    /// A Graphviz DOT graph, which can be rendered using `dot -Tsvg`.
+    Dot,
+    /// A JSON dump of every node in the tree.
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+enum Source {
+    /// Load the tree from pd's RocksDB database.
+    Pd {
+        /// The path to the RocksDB database, which is `rocksdb` within pd's home directory.
+        rocks_path: PathBuf,
+    },
+    /// Load the tree from the view service's SQLite database.
+    View {
+        /// The path to the SQLite database.
+        sqlite_path: Utf8PathBuf,
+    },
+}
+
+impl Source {
+    async fn load(self) -> Result<Tree> {
+        match self {
+            Source::Pd { rocks_path } => {
+                anyhow::ensure!(rocks_path.exists(), "{:?} does not exist", rocks_path);
+                penumbra_storage::Storage::load_read_only(rocks_path)
+                    .await
+                    .context("Unable to open RocksDB storage")?
+                    .get_nct()
+                    .await
+            }
+            Source::View { sqlite_path } => {
+                anyhow::ensure!(sqlite_path.exists(), "{} does not exist", sqlite_path);
+                penumbra_view::Storage::load_read_only(sqlite_path)
+                    .await
+                    .context("Unable to open SQLite storage")?
+                    .note_commitment_tree()
+                    .await
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+    let opt = Opt::parse();
+
+    let tree = opt.source.load().await?;
+
+    let mut out: Box<dyn Write> = if let Some(output) = opt.output {
+        Box::new(File::create(&output).with_context(|| format!("Unable to create {:?}", output))?)
+    } else {
+        Box::new(io::stdout())
+    };
+
+    match opt.format {
+        Format::Dot => export::dot(tree.structure(), &mut out)?,
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, &export::Dump::from(tree.structure()))?;
+            writeln!(out)?;
+        }
+    }
+    out.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use decaf377::Fq;
+use penumbra_tct::{builder::block, Commitment, Tree, Witness};
+
+fn commitments(count: u64) -> Vec<(Witness, Commitment)> {
+    (0..count)
+        .map(|i| {
+            // Keep one in every sixteen commitments, as a wallet or archive node might
+            let witness = if i % 16 == 0 {
+                Witness::Keep
+            } else {
+                Witness::Forget
+            };
+            (witness, Commitment(Fq::from(i)))
+        })
+        .collect()
+}
+
+fn insert_one_at_a_time(commitments: &[(Witness, Commitment)]) -> Tree {
+    let mut tree = Tree::new();
+    for &(witness, commitment) in commitments {
+        tree.insert(witness, commitment).unwrap();
+    }
+    tree.end_block().unwrap();
+    tree
+}
+
+fn insert_all_at_once(commitments: &[(Witness, Commitment)]) -> Tree {
+    let mut tree = Tree::new();
+    let block = block::Finalized::from_commitments(commitments.iter().copied()).unwrap();
+    tree.insert_block(block).unwrap();
+    tree
+}
+
+fn bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("end-block");
+    // Each iteration hashes an entire block, so we don't need as many runs
+    group.sample_size(10);
+    for count in [64, 1024, 16384] {
+        let commitments = commitments(count);
+        group.throughput(Throughput::Elements(count));
+        group.bench_with_input(
+            BenchmarkId::new("one-at-a-time", count),
+            &commitments,
+            |b, commitments| b.iter(|| insert_one_at_a_time(commitments)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("all-at-once", count),
+            &commitments,
+            |b, commitments| b.iter(|| insert_all_at_once(commitments)),
+        );
+    }
+    group.finish();
+
+    // Measure only the work done when a block ends, as `pd` does in `end_block`: either hashing a
+    // block whose commitments were already inserted one at a time, or hashing all of its
+    // commitments at once
+    let mut group = c.benchmark_group("finish-block");
+    group.sample_size(10);
+    for count in [64, 1024, 16384] {
+        let commitments = commitments(count);
+        group.throughput(Throughput::Elements(count));
+        group.bench_with_input(
+            BenchmarkId::new("end-block", count),
+            &commitments,
+            |b, commitments| {
+                b.iter_batched(
+                    || {
+                        let mut tree = Tree::new();
+                        for &(witness, commitment) in commitments {
+                            tree.insert(witness, commitment).unwrap();
+                        }
+                        tree
+                    },
+                    |mut tree| tree.end_block().unwrap(),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("insert-block", count),
+            &commitments,
+            |b, commitments| {
+                b.iter_batched(
+                    || (Tree::new(), commitments.clone()),
+                    |(mut tree, commitments)| {
+                        let block = block::Finalized::from_commitments(commitments).unwrap();
+                        tree.insert_block(block).unwrap()
+                    },
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
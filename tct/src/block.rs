@@ -6,7 +6,7 @@ use penumbra_proto::{crypto as pb, Protobuf};
 use serde::{Deserialize, Serialize};
 
 use crate::error::block::*;
-use crate::{prelude::*, Witness};
+use crate::{prelude::*, storage::Update, Witness};
 
 /// A sparse merkle tree to witness up to 65,536 individual [`Commitment`]s.
 ///
@@ -15,6 +15,7 @@ use crate::{prelude::*, Witness};
 pub struct Builder {
     index: HashedMap<Commitment, index::within::Block>,
     inner: frontier::Top<Item>,
+    updates: Vec<Update>,
 }
 
 impl Default for Builder {
@@ -22,6 +23,7 @@ impl Default for Builder {
         Self {
             index: HashedMap::default(),
             inner: frontier::Top::new(frontier::TrackForgotten::No),
+            updates: Vec::new(),
         }
     }
 }
@@ -32,6 +34,9 @@ impl Default for Builder {
 pub struct Finalized {
     pub(in super::super) index: HashedMap<Commitment, index::within::Block>,
     pub(in super::super) inner: Insert<complete::Top<complete::Item>>,
+    /// The updates made to the builder of this block, so that it can be persisted without
+    /// depending on its internal structure.
+    pub(in super::super) updates: Vec<Update>,
 }
 
 impl Default for Finalized {
@@ -52,6 +57,68 @@ impl Finalized {
     pub fn root(&self) -> Root {
         Root(self.inner.hash())
     }
+
+    /// Construct a finalized block all at once from a sequence of commitments, in the order they
+    /// would have been inserted into a [`block::Builder`](Builder).
+    ///
+    /// This is equivalent to inserting each commitment into a [`Builder`] and then finalizing it,
+    /// but can be much faster for large blocks: if the `rayon` feature is enabled, the commitments
+    /// are hashed in parallel, as are the nodes within each level of the block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertError`] if there are more commitments than fit in a block.
+    pub fn from_commitments(
+        commitments: impl IntoIterator<Item = (Witness, Commitment)>,
+    ) -> Result<Self, InsertError> {
+        let commitments: Vec<_> = commitments.into_iter().collect();
+        if commitments.len() > u16::MAX as usize + 1 {
+            return Err(InsertError);
+        }
+
+        // If the same commitment is kept more than once, only its last position is witnessed, just
+        // as when it is inserted again into a builder
+        let mut index: HashedMap<Commitment, index::within::Block> = HashedMap::default();
+        for (position, &(witness, commitment)) in commitments.iter().enumerate() {
+            if let Witness::Keep = witness {
+                index.insert(commitment, (position as u16).into());
+            }
+        }
+
+        let updates = commitments
+            .iter()
+            .map(|&(witness, commitment)| Update::Insert(witness, commitment))
+            .collect();
+
+        let items = crate::internal::parallel::map(
+            commitments.into_iter().enumerate().collect(),
+            |(position, (witness, commitment))| {
+                let hash = Hash::of(commitment);
+                let position: index::within::Block = (position as u16).into();
+                if witness == Witness::Keep && index.get(&commitment) == Some(&position) {
+                    Insert::Keep(complete::Item::new(hash, commitment))
+                } else {
+                    Insert::Hash(hash)
+                }
+            },
+        );
+
+        Ok(Self {
+            inner: complete::Top::from_items(items),
+            index,
+            updates,
+        })
+    }
+
+    /// The [`Update`] recording the insertion of this finalized block: just its root, if nothing in
+    /// it is witnessed.
+    pub(crate) fn to_update(&self) -> Update {
+        if self.index.is_empty() {
+            Update::BlockRoot(self.root())
+        } else {
+            Update::Block(self.updates.clone())
+        }
+    }
 }
 
 impl From<Root> for Finalized {
@@ -59,6 +126,7 @@ impl From<Root> for Finalized {
         Self {
             index: HashedMap::default(),
             inner: Insert::Hash(root.0),
+            updates: Vec::new(),
         }
     }
 }
@@ -157,6 +225,8 @@ impl Builder {
             }
         }
 
+        self.updates.push(Update::Insert(witness, commitment));
+
         Ok(())
     }
 
@@ -173,7 +243,12 @@ impl Builder {
         let this = std::mem::take(self);
         let inner = this.inner.finalize();
         let index = this.index;
-        Finalized { index, inner }
+        let updates = this.updates;
+        Finalized {
+            index,
+            inner,
+            updates,
+        }
     }
 }
 
@@ -6,7 +6,7 @@ use penumbra_proto::{crypto as pb, Protobuf};
 use serde::{Deserialize, Serialize};
 
 use crate::error::epoch::*;
-use crate::{prelude::*, Witness};
+use crate::{prelude::*, storage::Update, Witness};
 
 #[path = "block.rs"]
 pub(crate) mod block;
@@ -19,6 +19,7 @@ pub(crate) mod block;
 pub struct Builder {
     index: HashedMap<Commitment, index::within::Epoch>,
     inner: frontier::Top<frontier::Tier<frontier::Item>>,
+    updates: Vec<Update>,
 }
 
 impl Default for Builder {
@@ -26,6 +27,7 @@ impl Default for Builder {
         Self {
             index: HashedMap::default(),
             inner: frontier::Top::new(frontier::TrackForgotten::No),
+            updates: Vec::new(),
         }
     }
 }
@@ -35,6 +37,9 @@ impl Default for Builder {
 pub struct Finalized {
     pub(super) index: HashedMap<Commitment, index::within::Epoch>,
     pub(super) inner: Insert<complete::Top<complete::Tier<complete::Item>>>,
+    /// The updates made to the builder of this epoch, so that it can be persisted without
+    /// depending on its internal structure.
+    pub(super) updates: Vec<Update>,
 }
 
 impl Default for Finalized {
@@ -55,6 +60,16 @@ impl Finalized {
     pub fn root(&self) -> Root {
         Root(self.inner.hash())
     }
+
+    /// The [`Update`] recording the insertion of this finalized epoch: just its root, if nothing in
+    /// it is witnessed.
+    pub(crate) fn to_update(&self) -> Update {
+        if self.index.is_empty() {
+            Update::EpochRoot(self.root())
+        } else {
+            Update::Epoch(self.updates.clone())
+        }
+    }
 }
 
 impl From<Root> for Finalized {
@@ -62,6 +77,7 @@ impl From<Root> for Finalized {
         Self {
             index: HashedMap::default(),
             inner: Insert::Hash(root.0),
+            updates: Vec::new(),
         }
     }
 }
@@ -185,6 +201,8 @@ impl Builder {
             }
         }
 
+        self.updates.push(Update::Insert(witness, commitment));
+
         Ok(())
     }
 
@@ -196,11 +214,30 @@ impl Builder {
         &mut self,
         block: impl Into<block::Finalized>,
     ) -> Result<block::Root, InsertBlockError> {
-        let block::Finalized { inner, index } = block.into();
+        let block = block.into();
+        let update = block.to_update();
+        let block_root = self.insert_block_unrecorded(block)?;
+        self.updates.push(update);
+        Ok(block_root)
+    }
+
+    fn insert_block_unrecorded(
+        &mut self,
+        block: block::Finalized,
+    ) -> Result<block::Root, InsertBlockError> {
+        let block::Finalized {
+            inner,
+            index,
+            updates,
+        } = block;
 
         // If the insertion would fail, return an error
         if self.inner.is_full() {
-            return Err(InsertBlockError(block::Finalized { inner, index }));
+            return Err(InsertBlockError(block::Finalized {
+                inner,
+                index,
+                updates,
+            }));
         }
 
         // Convert the top level inside of the block to a tier that can be slotted into the epoch
@@ -265,8 +302,9 @@ impl Builder {
         // If the latest block was already finalized (i.e. we are at the start of an unfinalized
         // empty block), insert an empty finalized block
         if already_finalized {
-            self.insert_block(block::Finalized::default())?;
+            self.insert_block_unrecorded(block::Finalized::default())?;
         };
+        self.updates.push(Update::EndBlock);
 
         Ok(finalized_root)
     }
@@ -284,6 +322,11 @@ impl Builder {
         let this = std::mem::take(self);
         let inner = this.inner.finalize();
         let index = this.index;
-        Finalized { index, inner }
+        let updates = this.updates;
+        Finalized {
+            index,
+            inner,
+            updates,
+        }
     }
 }
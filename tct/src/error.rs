@@ -12,11 +12,37 @@ pub use crate::tree::RootDecodeError;
 
 pub mod proof {
     //! Errors from deserializing or verifying inclusion proofs.
+    use super::*;
+
     #[doc(inline)]
     pub use crate::internal::{
         path::PathDecodeError,
         proof::{ProofDecodeError as DecodeError, VerifyError},
     };
+
+    /// An error occurred when constructing, verifying, or splitting a
+    /// [`MultiProof`](crate::MultiProof).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+    pub enum MultiProofError {
+        /// The proofs being combined did not all witness commitments in the same tree.
+        #[error("proofs do not all witness commitments in the same tree")]
+        Inconsistent,
+        /// The multi-proof did not include enough hashes to compute its root.
+        #[error("multi-proof is missing hashes")]
+        MissingHashes,
+        /// The multi-proof included more hashes than were needed to compute its root.
+        #[error("multi-proof has {0} more hashes than needed")]
+        ExtraHashes(usize),
+        /// The multi-proof witnessed a commitment at a position outside the tree.
+        #[error("multi-proof witnesses a commitment at position {0}, outside the tree")]
+        OutOfRange(u64),
+        /// The multi-proof did not verify against the provided root.
+        #[error("invalid multi-proof for root {root}")]
+        Invalid {
+            /// The root against which the multi-proof failed to verify.
+            root: crate::Root,
+        },
+    }
 }
 
 pub mod block {
@@ -113,6 +139,70 @@ impl From<InsertEpochError> for builder::epoch::Finalized {
     }
 }
 
+/// An [`Update`](crate::storage::Update) could not be replayed against a [`Tree`].
+#[derive(Debug, Clone, Error)]
+pub enum ReplayError {
+    /// A [`Commitment`] could not be inserted.
+    #[error(transparent)]
+    Insert(#[from] InsertError),
+    /// A block could not be inserted or ended.
+    #[error(transparent)]
+    InsertBlock(#[from] InsertBlockError),
+    /// An epoch could not be inserted or ended.
+    #[error(transparent)]
+    InsertEpoch(#[from] InsertEpochError),
+    /// A [`Commitment`] could not be inserted into a block being replayed.
+    #[error(transparent)]
+    BlockInsert(#[from] block::InsertError),
+    /// A [`Commitment`] could not be inserted into an epoch being replayed.
+    #[error(transparent)]
+    EpochInsert(#[from] epoch::InsertError),
+    /// A block could not be inserted into or ended in an epoch being replayed.
+    #[error(transparent)]
+    EpochInsertBlock(#[from] epoch::InsertBlockError),
+    /// An update occurred within a block or epoch being replayed, where it is not possible.
+    #[error("unexpected update within block or epoch: {0:?}")]
+    Unexpected(crate::storage::Update),
+    /// After replaying a [`Batch`](crate::storage::Batch), the position of the tree differed from
+    /// the position recorded in it.
+    #[error("expected position {expected:?} after replaying batch, found {actual:?}")]
+    PositionMismatch {
+        /// The position recorded in the batch.
+        expected: crate::Position,
+        /// The position of the tree after replaying the batch.
+        actual: Option<crate::Position>,
+    },
+}
+
+/// An error occurred when decoding a [`Batch`](crate::storage::Batch) from bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BatchDecodeError {
+    /// The batch was encoded by a later version of the encoding.
+    #[error("unknown batch encoding version {0}")]
+    UnknownVersion(u16),
+    /// The bytes ended in the middle of the batch.
+    #[error("unexpected end of batch")]
+    UnexpectedEnd,
+    /// There were bytes left over after the end of the batch.
+    #[error("trailing bytes after batch")]
+    TrailingBytes,
+    /// An update had an unknown tag.
+    #[error("invalid update tag {0}")]
+    InvalidTag(u8),
+    /// A [`Commitment`] was not a valid field element.
+    #[error("invalid commitment")]
+    InvalidCommitment,
+    /// A root hash was not a valid field element.
+    #[error("invalid hash")]
+    InvalidHash,
+    /// A batch in the headerless version 0 of the encoding could not be decoded.
+    #[error("could not decode version 0 batch: {0}")]
+    Legacy(String),
+    /// Updates were nested more deeply than blocks within epochs.
+    #[error("updates nested too deeply")]
+    TooDeep,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -122,5 +212,8 @@ mod test {
         static_assertions::assert_impl_all!(InsertError: Sync, Send);
         static_assertions::assert_impl_all!(InsertBlockError: Sync, Send);
         static_assertions::assert_impl_all!(InsertEpochError: Sync, Send);
+        static_assertions::assert_impl_all!(ReplayError: Sync, Send);
+        static_assertions::assert_impl_all!(BatchDecodeError: Sync, Send);
+        static_assertions::assert_impl_all!(proof::MultiProofError: Sync, Send);
     }
 }
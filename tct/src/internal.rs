@@ -74,6 +74,7 @@ pub mod proof;
 pub mod three;
 
 mod insert;
+pub(crate) mod parallel;
 
 pub mod frontier {
     //! [`Frontier`] things can be inserted into and updated, always representing the rightmost
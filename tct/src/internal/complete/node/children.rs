@@ -5,12 +5,17 @@
 //!
 //! The reason for this enumeration is to save heap space in the case of many nodes: because
 //! different nodes can have different sizes, we save on average a few words of memory by placing
-//! the box inside each enum variant rather than outside the whole enum (which would end up
+//! the pointer inside each enum variant rather than outside the whole enum (which would end up
 //! occupying the space of its largest variant).
+//!
+//! The pointer is an [`Arc`] so that cloning a complete node is cheap and shares its whole subtree
+//! with the original, which is what makes [`Tree::checkpoint`](crate::Tree::checkpoint) cheap.
+//! Complete nodes are never mutated in place: forgetting within a shared node copies only the
+//! nodes along the path to the forgotten commitment.
 
 #![allow(non_camel_case_types, clippy::upper_case_acronyms)]
 
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::Arc};
 
 mod shape;
 pub use shape::*;
@@ -21,35 +26,35 @@ use crate::prelude::*;
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Children<Child> {
     /// Children of a node having children in the positions: 3.
-    ___C(Box<___C<Child>>),
+    ___C(Arc<___C<Child>>),
     /// Children of a node having children in the positions: 2.
-    __C_(Box<__C_<Child>>),
+    __C_(Arc<__C_<Child>>),
     /// Children of a node having children in the positions: 2, 3.
-    __CC(Box<__CC<Child>>),
+    __CC(Arc<__CC<Child>>),
     /// Children of a node having children in the positions: 1.
-    _C__(Box<_C__<Child>>),
+    _C__(Arc<_C__<Child>>),
     /// Children of a node having children in the positions: 1, 3.
-    _C_C(Box<_C_C<Child>>),
+    _C_C(Arc<_C_C<Child>>),
     /// Children of a node having children in the positions: 1, 2.
-    _CC_(Box<_CC_<Child>>),
+    _CC_(Arc<_CC_<Child>>),
     /// Children of a node having children in the positions: 1, 2, 3.
-    _CCC(Box<_CCC<Child>>),
+    _CCC(Arc<_CCC<Child>>),
     /// Children of a node having children in the positions: 0.
-    C___(Box<C___<Child>>),
+    C___(Arc<C___<Child>>),
     /// Children of a node having children in the positions: 0, 3.
-    C__C(Box<C__C<Child>>),
+    C__C(Arc<C__C<Child>>),
     /// Children of a node having children in the positions: 0, 2.
-    C_C_(Box<C_C_<Child>>),
+    C_C_(Arc<C_C_<Child>>),
     /// Children of a node having children in the positions: 0, 2, 3.
-    C_CC(Box<C_CC<Child>>),
+    C_CC(Arc<C_CC<Child>>),
     /// Children of a node having children in the positions: 0, 1.
-    CC__(Box<CC__<Child>>),
+    CC__(Arc<CC__<Child>>),
     /// Children of a node having children in the positions: 0, 1, 3.
-    CC_C(Box<CC_C<Child>>),
+    CC_C(Arc<CC_C<Child>>),
     /// Children of a node having children in the positions: 0, 1, 2.
-    CCC_(Box<CCC_<Child>>),
+    CCC_(Arc<CCC_<Child>>),
     /// Children of a node having children in the positions: 0, 1, 2, 3.
-    CCCC(Box<CCCC<Child>>),
+    CCCC(Arc<CCCC<Child>>),
 }
 
 impl<Child: Debug> Debug for Children<Child> {
@@ -88,21 +93,21 @@ where
             // hashes so the parent can implement pruning):
             [Hash(a), Hash(b), Hash(c), Hash(d)] => return Err([a, b, c, d]),
             // There is at least one witnessed child:
-            [Hash(a), Hash(b), Hash(c), Keep(d)] => Children::___C(Box::new(___C(a, b, c, d))),
-            [Hash(a), Hash(b), Keep(c), Hash(d)] => Children::__C_(Box::new(__C_(a, b, c, d))),
-            [Hash(a), Hash(b), Keep(c), Keep(d)] => Children::__CC(Box::new(__CC(a, b, c, d))),
-            [Hash(a), Keep(b), Hash(c), Hash(d)] => Children::_C__(Box::new(_C__(a, b, c, d))),
-            [Hash(a), Keep(b), Hash(c), Keep(d)] => Children::_C_C(Box::new(_C_C(a, b, c, d))),
-            [Hash(a), Keep(b), Keep(c), Hash(d)] => Children::_CC_(Box::new(_CC_(a, b, c, d))),
-            [Hash(a), Keep(b), Keep(c), Keep(d)] => Children::_CCC(Box::new(_CCC(a, b, c, d))),
-            [Keep(a), Hash(b), Hash(c), Hash(d)] => Children::C___(Box::new(C___(a, b, c, d))),
-            [Keep(a), Hash(b), Hash(c), Keep(d)] => Children::C__C(Box::new(C__C(a, b, c, d))),
-            [Keep(a), Hash(b), Keep(c), Hash(d)] => Children::C_C_(Box::new(C_C_(a, b, c, d))),
-            [Keep(a), Hash(b), Keep(c), Keep(d)] => Children::C_CC(Box::new(C_CC(a, b, c, d))),
-            [Keep(a), Keep(b), Hash(c), Hash(d)] => Children::CC__(Box::new(CC__(a, b, c, d))),
-            [Keep(a), Keep(b), Hash(c), Keep(d)] => Children::CC_C(Box::new(CC_C(a, b, c, d))),
-            [Keep(a), Keep(b), Keep(c), Hash(d)] => Children::CCC_(Box::new(CCC_(a, b, c, d))),
-            [Keep(a), Keep(b), Keep(c), Keep(d)] => Children::CCCC(Box::new(CCCC(a, b, c, d))),
+            [Hash(a), Hash(b), Hash(c), Keep(d)] => Children::___C(Arc::new(___C(a, b, c, d))),
+            [Hash(a), Hash(b), Keep(c), Hash(d)] => Children::__C_(Arc::new(__C_(a, b, c, d))),
+            [Hash(a), Hash(b), Keep(c), Keep(d)] => Children::__CC(Arc::new(__CC(a, b, c, d))),
+            [Hash(a), Keep(b), Hash(c), Hash(d)] => Children::_C__(Arc::new(_C__(a, b, c, d))),
+            [Hash(a), Keep(b), Hash(c), Keep(d)] => Children::_C_C(Arc::new(_C_C(a, b, c, d))),
+            [Hash(a), Keep(b), Keep(c), Hash(d)] => Children::_CC_(Arc::new(_CC_(a, b, c, d))),
+            [Hash(a), Keep(b), Keep(c), Keep(d)] => Children::_CCC(Arc::new(_CCC(a, b, c, d))),
+            [Keep(a), Hash(b), Hash(c), Hash(d)] => Children::C___(Arc::new(C___(a, b, c, d))),
+            [Keep(a), Hash(b), Hash(c), Keep(d)] => Children::C__C(Arc::new(C__C(a, b, c, d))),
+            [Keep(a), Hash(b), Keep(c), Hash(d)] => Children::C_C_(Arc::new(C_C_(a, b, c, d))),
+            [Keep(a), Hash(b), Keep(c), Keep(d)] => Children::C_CC(Arc::new(C_CC(a, b, c, d))),
+            [Keep(a), Keep(b), Hash(c), Hash(d)] => Children::CC__(Arc::new(CC__(a, b, c, d))),
+            [Keep(a), Keep(b), Hash(c), Keep(d)] => Children::CC_C(Arc::new(CC_C(a, b, c, d))),
+            [Keep(a), Keep(b), Keep(c), Hash(d)] => Children::CCC_(Arc::new(CCC_(a, b, c, d))),
+            [Keep(a), Keep(b), Keep(c), Keep(d)] => Children::CCCC(Arc::new(CCCC(a, b, c, d))),
         })
     }
 }
@@ -131,59 +136,160 @@ impl<Child> Children<Child> {
             CCCC(c) => [Keep(&c.0), Keep(&c.1), Keep(&c.2), Keep(&c.3)],
         }
     }
+}
 
-    /// Get an array of references to the children or hashes stored in this [`Children`].
+impl<Child: Clone> Children<Child> {
+    /// Get an array of mutable references to the children or hashes stored in this [`Children`].
+    ///
+    /// If these children are shared with a clone, they are copied first.
     pub fn children_mut(&mut self) -> [Insert<&mut Child>; 4] {
         use Children::*;
         use Insert::*;
 
         match self {
-            ___C(c) => [Hash(c.0), Hash(c.1), Hash(c.2), Keep(&mut c.3)],
-            __C_(c) => [Hash(c.0), Hash(c.1), Keep(&mut c.2), Hash(c.3)],
-            __CC(c) => [Hash(c.0), Hash(c.1), Keep(&mut c.2), Keep(&mut c.3)],
-            _C__(c) => [Hash(c.0), Keep(&mut c.1), Hash(c.2), Hash(c.3)],
-            _C_C(c) => [Hash(c.0), Keep(&mut c.1), Hash(c.2), Keep(&mut c.3)],
-            _CC_(c) => [Hash(c.0), Keep(&mut c.1), Keep(&mut c.2), Hash(c.3)],
-            _CCC(c) => [Hash(c.0), Keep(&mut c.1), Keep(&mut c.2), Keep(&mut c.3)],
-            C___(c) => [Keep(&mut c.0), Hash(c.1), Hash(c.2), Hash(c.3)],
-            C__C(c) => [Keep(&mut c.0), Hash(c.1), Hash(c.2), Keep(&mut c.3)],
-            C_C_(c) => [Keep(&mut c.0), Hash(c.1), Keep(&mut c.2), Hash(c.3)],
-            C_CC(c) => [Keep(&mut c.0), Hash(c.1), Keep(&mut c.2), Keep(&mut c.3)],
-            CC__(c) => [Keep(&mut c.0), Keep(&mut c.1), Hash(c.2), Hash(c.3)],
-            CC_C(c) => [Keep(&mut c.0), Keep(&mut c.1), Hash(c.2), Keep(&mut c.3)],
-            CCC_(c) => [Keep(&mut c.0), Keep(&mut c.1), Keep(&mut c.2), Hash(c.3)],
-            CCCC(c) => [
-                Keep(&mut c.0),
-                Keep(&mut c.1),
-                Keep(&mut c.2),
-                Keep(&mut c.3),
-            ],
+            ___C(c) => {
+                let c = Arc::make_mut(c);
+                [Hash(c.0), Hash(c.1), Hash(c.2), Keep(&mut c.3)]
+            }
+            __C_(c) => {
+                let c = Arc::make_mut(c);
+                [Hash(c.0), Hash(c.1), Keep(&mut c.2), Hash(c.3)]
+            }
+            __CC(c) => {
+                let c = Arc::make_mut(c);
+                [Hash(c.0), Hash(c.1), Keep(&mut c.2), Keep(&mut c.3)]
+            }
+            _C__(c) => {
+                let c = Arc::make_mut(c);
+                [Hash(c.0), Keep(&mut c.1), Hash(c.2), Hash(c.3)]
+            }
+            _C_C(c) => {
+                let c = Arc::make_mut(c);
+                [Hash(c.0), Keep(&mut c.1), Hash(c.2), Keep(&mut c.3)]
+            }
+            _CC_(c) => {
+                let c = Arc::make_mut(c);
+                [Hash(c.0), Keep(&mut c.1), Keep(&mut c.2), Hash(c.3)]
+            }
+            _CCC(c) => {
+                let c = Arc::make_mut(c);
+                [Hash(c.0), Keep(&mut c.1), Keep(&mut c.2), Keep(&mut c.3)]
+            }
+            C___(c) => {
+                let c = Arc::make_mut(c);
+                [Keep(&mut c.0), Hash(c.1), Hash(c.2), Hash(c.3)]
+            }
+            C__C(c) => {
+                let c = Arc::make_mut(c);
+                [Keep(&mut c.0), Hash(c.1), Hash(c.2), Keep(&mut c.3)]
+            }
+            C_C_(c) => {
+                let c = Arc::make_mut(c);
+                [Keep(&mut c.0), Hash(c.1), Keep(&mut c.2), Hash(c.3)]
+            }
+            C_CC(c) => {
+                let c = Arc::make_mut(c);
+                [Keep(&mut c.0), Hash(c.1), Keep(&mut c.2), Keep(&mut c.3)]
+            }
+            CC__(c) => {
+                let c = Arc::make_mut(c);
+                [Keep(&mut c.0), Keep(&mut c.1), Hash(c.2), Hash(c.3)]
+            }
+            CC_C(c) => {
+                let c = Arc::make_mut(c);
+                [Keep(&mut c.0), Keep(&mut c.1), Hash(c.2), Keep(&mut c.3)]
+            }
+            CCC_(c) => {
+                let c = Arc::make_mut(c);
+                [Keep(&mut c.0), Keep(&mut c.1), Keep(&mut c.2), Hash(c.3)]
+            }
+            CCCC(c) => {
+                let c = Arc::make_mut(c);
+                [
+                    Keep(&mut c.0),
+                    Keep(&mut c.1),
+                    Keep(&mut c.2),
+                    Keep(&mut c.3),
+                ]
+            }
         }
     }
 }
 
-impl<Child> From<Children<Child>> for [Insert<Child>; 4] {
-    /// Get an array of references to the children or hashes stored in this [`Children`].
+impl<Child: Clone> From<Children<Child>> for [Insert<Child>; 4] {
+    /// Get an array of the children or hashes stored in this [`Children`].
+    ///
+    /// If these children are shared with a clone, they are copied.
     fn from(children: Children<Child>) -> [Insert<Child>; 4] {
         use Children::*;
         use Insert::*;
 
         match children {
-            ___C(c) => [Hash(c.0), Hash(c.1), Hash(c.2), Keep(c.3)],
-            __C_(c) => [Hash(c.0), Hash(c.1), Keep(c.2), Hash(c.3)],
-            __CC(c) => [Hash(c.0), Hash(c.1), Keep(c.2), Keep(c.3)],
-            _C__(c) => [Hash(c.0), Keep(c.1), Hash(c.2), Hash(c.3)],
-            _C_C(c) => [Hash(c.0), Keep(c.1), Hash(c.2), Keep(c.3)],
-            _CC_(c) => [Hash(c.0), Keep(c.1), Keep(c.2), Hash(c.3)],
-            _CCC(c) => [Hash(c.0), Keep(c.1), Keep(c.2), Keep(c.3)],
-            C___(c) => [Keep(c.0), Hash(c.1), Hash(c.2), Hash(c.3)],
-            C__C(c) => [Keep(c.0), Hash(c.1), Hash(c.2), Keep(c.3)],
-            C_C_(c) => [Keep(c.0), Hash(c.1), Keep(c.2), Hash(c.3)],
-            C_CC(c) => [Keep(c.0), Hash(c.1), Keep(c.2), Keep(c.3)],
-            CC__(c) => [Keep(c.0), Keep(c.1), Hash(c.2), Hash(c.3)],
-            CC_C(c) => [Keep(c.0), Keep(c.1), Hash(c.2), Keep(c.3)],
-            CCC_(c) => [Keep(c.0), Keep(c.1), Keep(c.2), Hash(c.3)],
-            CCCC(c) => [Keep(c.0), Keep(c.1), Keep(c.2), Keep(c.3)],
+            ___C(c) => {
+                let c = unwrap_or_clone(c);
+                [Hash(c.0), Hash(c.1), Hash(c.2), Keep(c.3)]
+            }
+            __C_(c) => {
+                let c = unwrap_or_clone(c);
+                [Hash(c.0), Hash(c.1), Keep(c.2), Hash(c.3)]
+            }
+            __CC(c) => {
+                let c = unwrap_or_clone(c);
+                [Hash(c.0), Hash(c.1), Keep(c.2), Keep(c.3)]
+            }
+            _C__(c) => {
+                let c = unwrap_or_clone(c);
+                [Hash(c.0), Keep(c.1), Hash(c.2), Hash(c.3)]
+            }
+            _C_C(c) => {
+                let c = unwrap_or_clone(c);
+                [Hash(c.0), Keep(c.1), Hash(c.2), Keep(c.3)]
+            }
+            _CC_(c) => {
+                let c = unwrap_or_clone(c);
+                [Hash(c.0), Keep(c.1), Keep(c.2), Hash(c.3)]
+            }
+            _CCC(c) => {
+                let c = unwrap_or_clone(c);
+                [Hash(c.0), Keep(c.1), Keep(c.2), Keep(c.3)]
+            }
+            C___(c) => {
+                let c = unwrap_or_clone(c);
+                [Keep(c.0), Hash(c.1), Hash(c.2), Hash(c.3)]
+            }
+            C__C(c) => {
+                let c = unwrap_or_clone(c);
+                [Keep(c.0), Hash(c.1), Hash(c.2), Keep(c.3)]
+            }
+            C_C_(c) => {
+                let c = unwrap_or_clone(c);
+                [Keep(c.0), Hash(c.1), Keep(c.2), Hash(c.3)]
+            }
+            C_CC(c) => {
+                let c = unwrap_or_clone(c);
+                [Keep(c.0), Hash(c.1), Keep(c.2), Keep(c.3)]
+            }
+            CC__(c) => {
+                let c = unwrap_or_clone(c);
+                [Keep(c.0), Keep(c.1), Hash(c.2), Hash(c.3)]
+            }
+            CC_C(c) => {
+                let c = unwrap_or_clone(c);
+                [Keep(c.0), Keep(c.1), Hash(c.2), Keep(c.3)]
+            }
+            CCC_(c) => {
+                let c = unwrap_or_clone(c);
+                [Keep(c.0), Keep(c.1), Keep(c.2), Hash(c.3)]
+            }
+            CCCC(c) => {
+                let c = unwrap_or_clone(c);
+                [Keep(c.0), Keep(c.1), Keep(c.2), Keep(c.3)]
+            }
         }
     }
 }
+
+/// Take the contents of an [`Arc`], copying them if they are shared.
+fn unwrap_or_clone<T: Clone>(arc: Arc<T>) -> T {
+    Arc::try_unwrap(arc).unwrap_or_else(|arc| (*arc).clone())
+}
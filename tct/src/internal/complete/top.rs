@@ -2,12 +2,52 @@ use crate::prelude::*;
 
 use complete::Nested;
 
+use crate::internal::parallel::level;
+
 /// A complete top-level tier of the tiered commitment tree, being an 8-deep sparse quad-tree.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Top<Item: GetHash + Height> {
     pub(in super::super) inner: Nested<Item>,
 }
 
+impl<Item: GetHash + Height + Send + Sync> Top<Item> {
+    /// Construct a complete top-level tier all at once from its items, in order of position, or
+    /// return its hash if none of its items are witnessed.
+    ///
+    /// The result is identical to inserting each item into a [`frontier::Top`] and then finalizing
+    /// it, but the nodes within each level are hashed in parallel if the `rayon` feature is
+    /// enabled.
+    ///
+    /// # Panics
+    ///
+    /// If there are more items than fit in a single tier.
+    pub fn from_items(items: Vec<Insert<Item>>) -> Insert<Self> {
+        assert!(
+            items.len() <= 4usize.pow(8),
+            "more items than fit in a tier"
+        );
+
+        // The hash of an empty top-level tier is 1
+        if items.is_empty() {
+            return Insert::Hash(Hash::one());
+        }
+
+        let leaves = items
+            .into_iter()
+            .map(|item| item.map(complete::Leaf::new))
+            .collect();
+
+        // Build each of the eight levels of the tier from the one beneath it
+        let nodes = level(level(level(level(level(level(level(level(leaves))))))));
+
+        nodes
+            .into_iter()
+            .next()
+            .expect("a non-empty tier has exactly one root")
+            .map(|inner| Top { inner })
+    }
+}
+
 impl<Item: GetHash + Height> Height for Top<Item> {
     type Height = <Nested<Item> as Height>::Height;
 }
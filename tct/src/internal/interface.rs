@@ -88,7 +88,10 @@ pub trait Forget: Height {
 
 /// Forget about the authentication path to a given index, when forgetting can turn the entirety of
 /// `Self` into a hash.
-pub trait ForgetOwned: Height + Sized {
+///
+/// Complete subtrees may be shared between clones of a tree, so forgetting within one may need to
+/// copy it, hence the `Clone` bound.
+pub trait ForgetOwned: Height + Clone {
     /// Remove the witness for the given index and summarize the item as a single `Hash` if it now
     /// contains no more witnesses. If a forgotten version is specified, update the path
     /// down to the forgotten item to that version plus one.
@@ -0,0 +1,47 @@
+//! Helpers for hashing independent parts of the tree at once, which happens in parallel when the
+//! `rayon` feature is enabled, and serially otherwise.
+//!
+//! When commitments are inserted one at a time, each internal node is hashed as soon as it is
+//! complete, so there is never more than one hash ready to compute at once. When all of a block's
+//! commitments are known up front, as they are when a block ends, none of this needs to be done
+//! incrementally: the leaves are all independent of one another, as are the nodes within each
+//! level above them, so [`block::Finalized::from_commitments`](crate::builder::block::Finalized)
+//! uses these helpers to hash the whole block a level at a time.
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::prelude::*;
+
+/// Apply a function to every element of a vector, in parallel if the `rayon` feature is enabled.
+pub(crate) fn map<T: Send, U: Send>(items: Vec<T>, f: impl Fn(T) -> U + Send + Sync) -> Vec<U> {
+    #[cfg(feature = "rayon")]
+    {
+        items.into_par_iter().map(f).collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.into_iter().map(f).collect()
+    }
+}
+
+/// Construct the next level up of a complete tree from a level of children, in order.
+///
+/// Each group of four children becomes a node, with a final incomplete group being padded with the
+/// *ONE* hash, exactly as a [`frontier::Node`] is padded when it is finalized.
+pub(crate) fn level<Child>(children: Vec<Insert<Child>>) -> Vec<Insert<complete::Node<Child>>>
+where
+    Child: GetHash + Height + Send + Sync,
+{
+    let mut groups = Vec::with_capacity((children.len() + 3) / 4);
+    let mut children = children.into_iter();
+    while let Some(a) = children.next() {
+        let mut next = || children.next().unwrap_or_else(|| Insert::Hash(Hash::one()));
+        groups.push([a, next(), next(), next()]);
+    }
+
+    map(groups, |children| {
+        complete::Node::from_children_or_else_hash(Default::default(), children)
+    })
+}
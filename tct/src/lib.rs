@@ -48,16 +48,19 @@ extern crate tracing;
 
 mod commitment;
 mod index;
+mod multiproof;
 mod proof;
 mod serialize;
 mod tree;
 
 pub mod error;
+pub mod storage;
 pub mod structure;
 pub mod validate;
 pub use commitment::Commitment;
+pub use multiproof::MultiProof;
 pub use proof::Proof;
-pub use tree::{Position, Root, Tree};
+pub use tree::{Checkpoint, Position, Root, Tree};
 
 #[cfg(any(doc, feature = "internal"))]
 pub mod internal;
@@ -102,7 +105,7 @@ mod prelude {
 /// When inserting a [`Commitment`] into a [`Tree`], should we [`Keep`](Witness::Keep) it to allow
 /// it to be witnessed later, or [`Forget`](Witness::Forget) about it after updating the root
 /// hash of the tree?
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[cfg_attr(any(test, feature = "arbitrary"), derive(proptest_derive::Arbitrary))]
 pub enum Witness {
     /// When inserting a [`Commitment`] into a [`Tree`], this flag indicates that we should
@@ -132,7 +135,7 @@ mod test {
 
     #[test]
     fn check_eternity_size() {
-        static_assertions::assert_eq_size!(Tree, [u8; 896]);
+        static_assertions::assert_eq_size!(Tree, [u8; 952]);
     }
 
     #[test]
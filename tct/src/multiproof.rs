@@ -0,0 +1,413 @@
+use std::collections::{BTreeMap, HashMap};
+
+use decaf377::{FieldExt, Fq};
+use penumbra_proto::{crypto as pb, Protobuf};
+
+use crate::error::proof::{DecodeError, MultiProofError};
+use crate::prelude::*;
+
+/// The height of a [`Tree`]: eight levels for each of its three tiers.
+const HEIGHT: u8 = 24;
+
+/// The number of leaves in a [`Tree`], which bounds the positions of commitments.
+const CAPACITY: u64 = 1 << (2 * HEIGHT as u64);
+
+/// A proof of the inclusion of many [`Commitment`]s in a [`Tree`] with a particular [`Root`],
+/// which includes each hash needed to verify it only once.
+///
+/// The auth paths of commitments which are near each other in the tree share most of their
+/// hashes, and many of the hashes in one commitment's auth path can be computed from another
+/// commitment, so this is usually much smaller than the equivalent individual [`Proof`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiProof {
+    /// The witnessed commitments and their positions, in the order they were witnessed.
+    commitments: Vec<(Commitment, Position)>,
+    /// The hashes of the nodes which can't be computed from the commitments, in the order they
+    /// are needed by [`compute`].
+    hashes: Vec<Hash>,
+}
+
+impl MultiProof {
+    /// Combine individual [`Proof`]s of inclusion in the same [`Tree`] into a single
+    /// [`MultiProof`], which witnesses the same commitments in the same order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MultiProofError::Inconsistent`] if the proofs do not all share the same root.
+    pub fn from_proofs(
+        proofs: impl IntoIterator<Item = Proof>,
+    ) -> Result<MultiProof, MultiProofError> {
+        // Record the hash of every node mentioned by any of the proofs, checking that they agree
+        let mut nodes = Nodes::new();
+        let mut commitments = Vec::new();
+        for proof in proofs {
+            let commitment = proof.commitment();
+            let position = proof.position();
+            let index = u64::from(position);
+
+            let mut hash = Hash::of(commitment);
+            record(&mut nodes, 0, index, hash)?;
+
+            // The auth path is ordered from root to leaf, so we walk it in reverse
+            for (height, siblings) in (1..=HEIGHT).zip(proof.auth_path().into_iter().rev()) {
+                let child = index >> (2 * (height - 1));
+                let parent = child >> 2;
+                let mut siblings = siblings.iter().copied();
+                let mut children = [Hash::zero(); 4];
+                for (slot, hash_slot) in children.iter_mut().enumerate() {
+                    let node = parent << 2 | slot as u64;
+                    *hash_slot = if node == child {
+                        hash
+                    } else {
+                        let sibling = siblings.next().expect("auth paths have three siblings");
+                        record(&mut nodes, height - 1, node, sibling)?;
+                        sibling
+                    };
+                }
+                let [a, b, c, d] = children;
+                hash = Hash::node(height, a, b, c, d);
+                record(&mut nodes, height, parent, hash)?;
+            }
+
+            commitments.push((commitment, position));
+        }
+
+        // Pick out just the hashes that can't be computed from the commitments
+        let mut hashes = Vec::new();
+        compute(&commitments, |height, index| {
+            let hash = nodes[&(height, index)];
+            hashes.push(hash);
+            Ok(hash)
+        })?;
+
+        Ok(MultiProof {
+            commitments,
+            hashes,
+        })
+    }
+
+    /// Get the witnessed commitments and their positions, in the order they were witnessed.
+    pub fn commitments(&self) -> impl Iterator<Item = (Commitment, Position)> + '_ {
+        self.commitments.iter().copied()
+    }
+
+    /// Compute the [`Root`] of the tree in which this proof witnesses its commitments, or `None`
+    /// if it witnesses no commitments.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MultiProofError`] if the proof is malformed.
+    pub fn root(&self) -> Result<Option<Root>, MultiProofError> {
+        Ok(self.nodes()?.get(&(HEIGHT, 0)).map(|&hash| Root(hash)))
+    }
+
+    /// Verify this [`MultiProof`] of inclusion against the [`Root`] of a [`Tree`].
+    ///
+    /// A proof which witnesses no commitments is valid for any root.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MultiProofError`] if the proof is malformed or invalid for that root.
+    pub fn verify(&self, root: Root) -> Result<(), MultiProofError> {
+        match self.root()? {
+            Some(actual) if actual != root => Err(MultiProofError::Invalid { root }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Split this [`MultiProof`] into an individual [`Proof`] for each of its commitments, in the
+    /// order they were witnessed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MultiProofError`] if the proof is malformed.
+    pub fn proofs(&self) -> Result<Vec<Proof>, MultiProofError> {
+        let nodes = self.nodes()?;
+
+        Ok(self
+            .commitments
+            .iter()
+            .map(|&(commitment, position)| {
+                let index = u64::from(position);
+                let mut auth_path = [[Hash::zero(); 3]; HEIGHT as usize];
+                // The auth path is ordered from root to leaf
+                for (height, siblings) in (1..=HEIGHT).rev().zip(auth_path.iter_mut()) {
+                    let child = index >> (2 * (height - 1));
+                    let parent = child >> 2;
+                    let mut slots = (0..4)
+                        .map(|slot| parent << 2 | slot)
+                        .filter(|&node| node != child);
+                    for sibling in siblings.iter_mut() {
+                        let node = slots.next().expect("nodes have three siblings");
+                        *sibling = nodes[&(height - 1, node)];
+                    }
+                }
+                Proof::new(commitment, position, auth_path)
+            })
+            .collect())
+    }
+
+    /// Compute the hashes of all the nodes in the tree needed to verify this proof.
+    fn nodes(&self) -> Result<Nodes, MultiProofError> {
+        let mut hashes = self.hashes.iter().copied();
+        let nodes = compute(&self.commitments, |_, _| {
+            hashes.next().ok_or(MultiProofError::MissingHashes)
+        })?;
+        match hashes.len() {
+            0 => Ok(nodes),
+            extra => Err(MultiProofError::ExtraHashes(extra)),
+        }
+    }
+}
+
+/// The hashes of nodes in a tree, keyed by their height and their index among the nodes at that
+/// height, counting from the left.
+type Nodes = HashMap<(u8, u64), Hash>;
+
+/// Record the hash of a node, checking that it doesn't contradict any hash recorded before.
+fn record(nodes: &mut Nodes, height: u8, index: u64, hash: Hash) -> Result<(), MultiProofError> {
+    match nodes.insert((height, index), hash) {
+        Some(previous) if previous != hash => Err(MultiProofError::Inconsistent),
+        _ => Ok(()),
+    }
+}
+
+/// Compute the hashes of all the nodes needed to verify the inclusion of the given commitments,
+/// from the bottom of the tree to the top, using `sibling` to get the hash of each node (given its
+/// height and index) which can't be computed from the commitments.
+///
+/// At each height, `sibling` is asked for nodes from left to right, so the order in which it is
+/// called is the same for any set of commitments at the same positions.
+fn compute(
+    commitments: &[(Commitment, Position)],
+    mut sibling: impl FnMut(u8, u64) -> Result<Hash, MultiProofError>,
+) -> Result<Nodes, MultiProofError> {
+    let mut nodes = Nodes::new();
+
+    // The nodes at the current height whose hashes are known, in order from left to right
+    let mut known = BTreeMap::new();
+    for &(commitment, position) in commitments {
+        let index = u64::from(position);
+        if index >= CAPACITY {
+            return Err(MultiProofError::OutOfRange(index));
+        }
+        let hash = Hash::of(commitment);
+        match known.insert(index, hash) {
+            Some(previous) if previous != hash => return Err(MultiProofError::Inconsistent),
+            _ => {}
+        }
+    }
+
+    for height in 1..=HEIGHT {
+        let mut parents = BTreeMap::new();
+        let mut children = known.keys().map(|child| child >> 2).peekable();
+        while let Some(parent) = children.next() {
+            // Skip the other known children of the same parent
+            while children.next_if_eq(&parent).is_some() {}
+
+            let mut hashes = [Hash::zero(); 4];
+            for (slot, hash) in hashes.iter_mut().enumerate() {
+                let node = parent << 2 | slot as u64;
+                *hash = match known.get(&node) {
+                    Some(&hash) => hash,
+                    None => sibling(height - 1, node)?,
+                };
+            }
+            let [a, b, c, d] = hashes;
+            parents.insert(parent, Hash::node(height, a, b, c, d));
+
+            for (slot, hash) in hashes.into_iter().enumerate() {
+                nodes.insert((height - 1, parent << 2 | slot as u64), hash);
+            }
+        }
+        known = parents;
+    }
+
+    // Everything must be beneath the single root of the tree, or some commitment would go
+    // unchecked by verification against that root
+    match known.into_iter().collect::<Vec<_>>().as_slice() {
+        [] => {}
+        &[(0, root)] => {
+            nodes.insert((HEIGHT, 0), root);
+        }
+        &[.., (index, _)] => {
+            return Err(MultiProofError::OutOfRange(index << (2 * HEIGHT as u64)));
+        }
+    }
+    Ok(nodes)
+}
+
+impl Tree {
+    /// Get a [`MultiProof`] of inclusion for all the given commitments, in the order given.
+    ///
+    /// If any of the commitments is not witnessed in this tree, returns `None`.
+    #[instrument(skip(self, commitments))]
+    pub fn witness_many(
+        &self,
+        commitments: impl IntoIterator<Item = Commitment>,
+    ) -> Option<MultiProof> {
+        let proofs = commitments
+            .into_iter()
+            .map(|commitment| self.witness(commitment))
+            .collect::<Option<Vec<_>>>()?;
+        Some(MultiProof::from_proofs(proofs).expect("proofs from the same tree must be consistent"))
+    }
+
+    /// Get a [`MultiProof`] of inclusion for all the given commitments, in the order given,
+    /// relative to the given root (see [`Tree::witness_at`]).
+    ///
+    /// If the root is unknown, or any of the commitments was not witnessed in the version of the
+    /// tree with that root, returns `None`.
+    #[instrument(skip(self, commitments))]
+    pub fn witness_many_at(
+        &self,
+        commitments: impl IntoIterator<Item = Commitment>,
+        root: Root,
+    ) -> Option<MultiProof> {
+        let proofs = commitments
+            .into_iter()
+            .map(|commitment| self.witness_at(commitment, root))
+            .collect::<Option<Vec<_>>>()?;
+        Some(
+            MultiProof::from_proofs(proofs)
+                .expect("proofs from the same version of a tree must be consistent"),
+        )
+    }
+}
+
+impl Protobuf<pb::NoteCommitmentMultiProof> for MultiProof {}
+
+impl From<MultiProof> for pb::NoteCommitmentMultiProof {
+    fn from(proof: MultiProof) -> Self {
+        let (note_commitments, positions) = proof
+            .commitments
+            .into_iter()
+            .map(|(commitment, position)| (commitment.into(), u64::from(position)))
+            .unzip();
+        Self {
+            note_commitments,
+            positions,
+            hashes: proof
+                .hashes
+                .into_iter()
+                .map(|hash| hash.to_bytes().to_vec())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::NoteCommitmentMultiProof> for MultiProof {
+    type Error = DecodeError;
+
+    fn try_from(proof: pb::NoteCommitmentMultiProof) -> Result<Self, Self::Error> {
+        if proof.note_commitments.len() != proof.positions.len() {
+            return Err(DecodeError);
+        }
+
+        let commitments = proof
+            .note_commitments
+            .into_iter()
+            .zip(proof.positions)
+            .map(|(commitment, position)| {
+                let commitment = commitment.try_into().map_err(|_| DecodeError)?;
+                // Converting to a `Position` would silently truncate an out-of-range position
+                if position >= CAPACITY {
+                    return Err(DecodeError);
+                }
+                Ok((commitment, position.into()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let hashes = proof
+            .hashes
+            .into_iter()
+            .map(|bytes| {
+                let bytes: [u8; 32] = bytes.try_into().map_err(|_| DecodeError)?;
+                Ok(Hash::new(Fq::from_bytes(bytes).map_err(|_| DecodeError)?))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(MultiProof {
+            commitments,
+            hashes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Witness;
+
+    fn commitment(i: u64) -> Commitment {
+        Commitment(Fq::from(i))
+    }
+
+    #[test]
+    fn multi_proof_matches_individual_proofs() {
+        let mut tree = Tree::new();
+        for i in 0..20 {
+            tree.insert(Witness::Keep, commitment(i)).unwrap();
+            if i % 7 == 6 {
+                tree.end_block().unwrap();
+            }
+        }
+        tree.end_epoch().unwrap();
+        tree.insert(Witness::Keep, commitment(20)).unwrap();
+
+        let witnessed = [3, 0, 20, 4, 13, 3].map(commitment);
+        let multi_proof = tree.witness_many(witnessed).unwrap();
+        multi_proof.verify(tree.root()).unwrap();
+        assert_eq!(Some(tree.root()), multi_proof.root().unwrap());
+
+        let proofs = multi_proof.proofs().unwrap();
+        let expected: Vec<_> = witnessed
+            .iter()
+            .map(|&c| tree.witness(c).unwrap())
+            .collect();
+        assert_eq!(expected, proofs);
+
+        // Shared hashes are only included once
+        assert!(multi_proof.hashes.len() < 3 * HEIGHT as usize * witnessed.len());
+
+        let decoded = MultiProof::decode(multi_proof.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(multi_proof, decoded);
+    }
+
+    #[test]
+    fn malformed_multi_proofs_are_rejected() {
+        let mut tree = Tree::new();
+        for i in 0..4 {
+            tree.insert(Witness::Keep, commitment(i)).unwrap();
+        }
+        let mut multi_proof = tree.witness_many([0, 2].map(commitment)).unwrap();
+
+        let mut other = Tree::new();
+        other.insert(Witness::Keep, commitment(0)).unwrap();
+        assert!(multi_proof.verify(other.root()).is_err());
+
+        multi_proof.hashes.push(Hash::zero());
+        assert_eq!(
+            Err(MultiProofError::ExtraHashes(1)),
+            multi_proof.verify(tree.root())
+        );
+        multi_proof.hashes.truncate(1);
+        assert_eq!(
+            Err(MultiProofError::MissingHashes),
+            multi_proof.verify(tree.root())
+        );
+
+        let mut out_of_range = pb::NoteCommitmentMultiProof::from(multi_proof.clone());
+        out_of_range.positions[0] += CAPACITY;
+        assert_eq!(Err(DecodeError), MultiProof::try_from(out_of_range));
+
+        let inconsistent = [
+            tree.witness(commitment(0)).unwrap(),
+            other.witness(commitment(0)).unwrap(),
+        ];
+        assert_eq!(
+            Err(MultiProofError::Inconsistent),
+            MultiProof::from_proofs(inconsistent)
+        );
+    }
+}
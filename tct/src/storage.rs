@@ -0,0 +1,782 @@
+//! Incremental persistence of a [`Tree`].
+//!
+//! Rather than serializing an entire [`Tree`] every time it changes, each modification made to a
+//! [`Tree`] is recorded as an [`Update`]. The pending updates can be taken as a [`Batch`], encoded
+//! using [`Batch::encode`], and appended to persistent storage, and the [`Tree`] can later be
+//! reconstructed by replaying every stored batch, in order, using [`Tree::from_batches`].
+//!
+//! Because the log of updates grows with every block, it should periodically be [`compact`]ed,
+//! which replaces every finalized block or epoch that no longer contains any witnessed commitments
+//! with its root, and folds each forgotten commitment into its original insertion.
+//!
+//! # Encoding
+//!
+//! The encoding of a [`Batch`] depends only on the public API of the [`Tree`], never on its
+//! internal structure, so it remains stable across changes to the implementation. Every encoded
+//! batch begins with a header, whose integers (like all others in the encoding) are big-endian:
+//!
+//! - the 4 magic bytes `[0xff, b'T', b'C', b'T']`,
+//! - the version of the encoding, as a `u16` (currently [`VERSION`]), and
+//! - the [`Position`] of the tree after the batch, as a `u64`, or `u64::MAX` if it is unknown,
+//!
+//! followed by the [`Update`]s in the batch, as a `u32` count followed by each update in turn.
+//! Each update is a tag byte followed by its contents:
+//!
+//! | Tag | Update                             | Contents                                     |
+//! |-----|------------------------------------|----------------------------------------------|
+//! | `0` | [`Update::Insert`] (forgotten)     | the 32-byte commitment                       |
+//! | `1` | [`Update::Insert`] (kept)          | the 32-byte commitment                       |
+//! | `2` | [`Update::Forget`]                 | the 32-byte commitment                       |
+//! | `3` | [`Update::EndBlock`]               |                                              |
+//! | `4` | [`Update::EndEpoch`]               |                                              |
+//! | `5` | [`Update::BlockRoot`]              | the 32-byte root hash                        |
+//! | `6` | [`Update::EpochRoot`]              | the 32-byte root hash                        |
+//! | `7` | [`Update::Block`]                  | a `u32` count followed by the nested updates |
+//! | `8` | [`Update::Epoch`]                  | a `u32` count followed by the nested updates |
+//!
+//! Blocks may only be nested within epochs, so updates are nested at most two deep.
+//!
+//! Batches written by earlier versions are migrated to the current version when decoded, so
+//! storage backends can decode everything they have ever written, and need only re-encode it (for
+//! instance, when compacting) to upgrade it.
+
+use std::collections::{HashMap, HashSet};
+
+use decaf377::{FieldExt, Fq};
+
+use crate::{
+    builder::{block, epoch},
+    error::{BatchDecodeError, ReplayError},
+    structure::Hash,
+    Commitment, Position, Tree, Witness,
+};
+
+/// A single modification to a [`Tree`], which can be replayed to reproduce it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Update {
+    /// A [`Commitment`] was inserted using [`Tree::insert`].
+    Insert(Witness, Commitment),
+    /// A witnessed [`Commitment`] was forgotten using [`Tree::forget`].
+    Forget(Commitment),
+    /// The current block was ended using [`Tree::end_block`].
+    EndBlock,
+    /// The current epoch was ended using [`Tree::end_epoch`].
+    EndEpoch,
+    /// A block with no witnessed commitments was inserted using [`Tree::insert_block`].
+    BlockRoot(block::Root),
+    /// A block with witnessed commitments was inserted using [`Tree::insert_block`]: these are
+    /// the updates made to its [`block::Builder`].
+    Block(Vec<Update>),
+    /// An epoch with no witnessed commitments was inserted using [`Tree::insert_epoch`].
+    EpochRoot(epoch::Root),
+    /// An epoch with witnessed commitments was inserted using [`Tree::insert_epoch`]: these are
+    /// the updates made to its [`epoch::Builder`].
+    Epoch(Vec<Update>),
+}
+
+impl Update {
+    /// Apply this update to a [`Tree`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReplayError`] if the update could not be applied to the tree; this only happens
+    /// when updates are replayed out of order, or against a different tree than the one which
+    /// produced them.
+    pub fn apply(self, tree: &mut Tree) -> Result<(), ReplayError> {
+        match self {
+            Update::Insert(witness, commitment) => {
+                tree.insert(witness, commitment)?;
+            }
+            Update::Forget(commitment) => {
+                tree.forget(commitment);
+            }
+            Update::EndBlock => {
+                tree.end_block()?;
+            }
+            Update::EndEpoch => {
+                tree.end_epoch()?;
+            }
+            Update::BlockRoot(root) => {
+                tree.insert_block(root)?;
+            }
+            Update::Block(updates) => {
+                tree.insert_block(replay_block(updates)?)?;
+            }
+            Update::EpochRoot(root) => {
+                tree.insert_epoch(root)?;
+            }
+            Update::Epoch(updates) => {
+                tree.insert_epoch(replay_epoch(updates)?)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replay the updates made to a [`block::Builder`].
+///
+/// The block is constructed all at once, so that its hashing can be parallelized.
+fn replay_block(updates: Vec<Update>) -> Result<block::Finalized, ReplayError> {
+    let mut commitments = Vec::with_capacity(updates.len());
+    for update in updates {
+        match update {
+            Update::Insert(witness, commitment) => commitments.push((witness, commitment)),
+            update => return Err(ReplayError::Unexpected(update)),
+        }
+    }
+    Ok(block::Finalized::from_commitments(commitments)?)
+}
+
+/// Replay the updates made to an [`epoch::Builder`].
+fn replay_epoch(updates: Vec<Update>) -> Result<epoch::Builder, ReplayError> {
+    let mut epoch = epoch::Builder::new();
+    for update in updates {
+        match update {
+            Update::Insert(witness, commitment) => {
+                epoch.insert(witness, commitment)?;
+            }
+            Update::EndBlock => {
+                epoch.end_block()?;
+            }
+            Update::BlockRoot(root) => {
+                epoch.insert_block(root)?;
+            }
+            Update::Block(updates) => {
+                epoch.insert_block(replay_block(updates)?)?;
+            }
+            update => return Err(ReplayError::Unexpected(update)),
+        }
+    }
+    Ok(epoch)
+}
+
+/// The [`Update`]s taken from a [`Tree`] at once, along with the [`Position`] of the tree after
+/// them, which is checked when they are replayed.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Batch {
+    /// The position of the tree after these updates, or `None` if it is unknown (because the tree
+    /// was full, or because the batch was migrated from a version which did not record it).
+    pub position: Option<Position>,
+    /// The updates, in the order they were made.
+    pub updates: Vec<Update>,
+}
+
+impl Batch {
+    /// Take all the pending [`Update`]s from a [`Tree`].
+    pub fn take(tree: &mut Tree) -> Batch {
+        Batch {
+            updates: tree.take_updates(),
+            position: tree.position(),
+        }
+    }
+
+    /// Check whether this batch ends an epoch.
+    ///
+    /// Once an epoch has ended, [`compact`]ing the log replaces it with its root if nothing in it
+    /// remains witnessed, so a log which is compacted whenever a batch ends an epoch grows only
+    /// with the number of epochs and witnessed commitments, rather than with the number of blocks.
+    pub fn ends_epoch(&self) -> bool {
+        self.updates
+            .iter()
+            .any(|update| matches!(update, Update::EndEpoch))
+    }
+}
+
+impl Tree {
+    /// Reconstruct a [`Tree`] by replaying, in order, all the [`Update`]s ever taken from it using
+    /// [`Tree::take_updates`] (or a [`compact`]ed version of them).
+    ///
+    /// The reconstructed tree has the same root, positions, and witnessed commitments as the
+    /// original, and no pending updates of its own.  If the updates were compacted, its count of
+    /// [`forgotten`](Tree::forgotten) commitments may be smaller than the original's.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReplayError`] if any update could not be applied.
+    pub fn from_updates(updates: impl IntoIterator<Item = Update>) -> Result<Tree, ReplayError> {
+        let mut tree = Tree::new();
+        for update in updates {
+            update.apply(&mut tree)?;
+        }
+        // Everything we just replayed has already been persisted
+        tree.take_updates();
+        Ok(tree)
+    }
+
+    /// Reconstruct a [`Tree`] by replaying, in order, all the [`Batch`]es ever taken from it (or a
+    /// [`compact`]ed version of them), like [`Tree::from_updates`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReplayError`] if any update could not be applied, or if the position of the tree
+    /// after any batch differs from the position recorded in it, which means that batches are
+    /// missing or out of order.
+    pub fn from_batches(batches: impl IntoIterator<Item = Batch>) -> Result<Tree, ReplayError> {
+        let mut tree = Tree::new();
+        for Batch { position, updates } in batches {
+            for update in updates {
+                update.apply(&mut tree)?;
+            }
+            if let Some(expected) = position {
+                let actual = tree.position();
+                if actual != Some(expected) {
+                    return Err(ReplayError::PositionMismatch { expected, actual });
+                }
+            }
+        }
+        // Everything we just replayed has already been persisted
+        tree.take_updates();
+        Ok(tree)
+    }
+}
+
+/// Compact a log of [`Update`]s into a single [`Batch`], so that replaying it using
+/// [`Tree::from_batches`] produces the same tree as replaying the original log.
+///
+/// Commitments which were inserted and later forgotten are instead inserted as
+/// [`Witness::Forget`], and every finalized block or epoch that contains no witnessed commitments
+/// is replaced with its root.
+///
+/// # Errors
+///
+/// Returns [`ReplayError`] if the updates could not be replayed.
+pub fn compact(updates: impl IntoIterator<Item = Update>) -> Result<Batch, ReplayError> {
+    let updates: Vec<Update> = updates.into_iter().collect();
+
+    // First, find every kept insertion that is later forgotten (explicitly, or by being replaced
+    // by a subsequent insertion of the same commitment), and every forgetting that can be folded
+    // into its insertion
+    let mut witnessed = HashMap::new();
+    let mut forgotten = HashSet::new();
+    let mut folded = HashSet::new();
+    for (i, update) in updates.iter().enumerate() {
+        match update {
+            Update::Insert(Witness::Keep, commitment) => {
+                if let Some(replaced) = witnessed.insert(*commitment, i) {
+                    forgotten.insert(replaced);
+                }
+            }
+            Update::Forget(commitment) => {
+                if let Some(inserted) = witnessed.remove(commitment) {
+                    forgotten.insert(inserted);
+                    folded.insert(i);
+                }
+            }
+            // Commitments in inserted blocks and epochs replace any earlier insertions of the same
+            // commitments, but can't themselves be folded
+            Update::Block(nested) | Update::Epoch(nested) => {
+                let mut kept = Vec::new();
+                kept_commitments(nested, &mut kept);
+                for commitment in kept {
+                    if let Some(replaced) = witnessed.remove(&commitment) {
+                        forgotten.insert(replaced);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Then, replay the updates, replacing each block or epoch with its root when it is finalized,
+    // if nothing remains witnessed within it
+    let mut tree = Tree::new();
+    let mut compacted = Vec::new();
+    let (mut block_start, mut epoch_start) = (0, 0);
+    let (mut block_witnessed, mut epoch_witnessed) = (false, false);
+
+    for (i, update) in updates.into_iter().enumerate() {
+        let update = match update {
+            _ if folded.contains(&i) => continue,
+            Update::Insert(Witness::Keep, commitment) if forgotten.contains(&i) => {
+                Update::Insert(Witness::Forget, commitment)
+            }
+            update => update,
+        };
+
+        match update {
+            Update::Insert(witness, commitment) => {
+                tree.insert(witness, commitment)?;
+                block_witnessed |= witness == Witness::Keep;
+                compacted.push(update);
+            }
+            Update::Forget(commitment) => {
+                // A forgetting that couldn't be folded into its insertion must be kept (which
+                // means the enclosing block can't be compacted, or it would be lost), but one
+                // which forgets nothing can be dropped
+                if tree.forget(commitment) {
+                    block_witnessed = true;
+                    compacted.push(update);
+                }
+            }
+            Update::EndBlock => {
+                let root = tree.end_block()?;
+                if block_witnessed {
+                    compacted.push(Update::EndBlock);
+                } else {
+                    compacted.truncate(block_start);
+                    compacted.push(Update::BlockRoot(root));
+                }
+                epoch_witnessed |= block_witnessed;
+                block_witnessed = false;
+                block_start = compacted.len();
+            }
+            Update::EndEpoch => {
+                let root = tree.end_epoch()?;
+                if epoch_witnessed || block_witnessed {
+                    compacted.push(Update::EndEpoch);
+                } else {
+                    compacted.truncate(epoch_start);
+                    compacted.push(Update::EpochRoot(root));
+                }
+                epoch_witnessed = false;
+                block_witnessed = false;
+                epoch_start = compacted.len();
+                block_start = compacted.len();
+            }
+            Update::BlockRoot(_) | Update::Block(_) => {
+                let is_witnessed = matches!(update, Update::Block(_));
+                update.clone().apply(&mut tree)?;
+                compacted.push(update);
+                epoch_witnessed |= block_witnessed || is_witnessed;
+                block_witnessed = false;
+                block_start = compacted.len();
+            }
+            Update::EpochRoot(_) | Update::Epoch(_) => {
+                update.clone().apply(&mut tree)?;
+                compacted.push(update);
+                epoch_witnessed = false;
+                block_witnessed = false;
+                epoch_start = compacted.len();
+                block_start = compacted.len();
+            }
+        }
+    }
+
+    Ok(Batch {
+        position: tree.position(),
+        updates: compacted,
+    })
+}
+
+/// Collect every commitment inserted with [`Witness::Keep`] in a nested log of updates.
+fn kept_commitments(updates: &[Update], kept: &mut Vec<Commitment>) {
+    for update in updates {
+        match update {
+            Update::Insert(Witness::Keep, commitment) => kept.push(*commitment),
+            Update::Block(nested) | Update::Epoch(nested) => kept_commitments(nested, kept),
+            _ => {}
+        }
+    }
+}
+
+/// The magic bytes at the start of every encoded [`Batch`].
+const MAGIC: [u8; 4] = [0xff, b'T', b'C', b'T'];
+
+/// The current version of the encoding of a [`Batch`].
+pub const VERSION: u16 = 1;
+
+/// How deeply updates can be nested: an [`Update::Epoch`] can contain an [`Update::Block`], which
+/// can't contain either.
+const MAX_DEPTH: usize = 2;
+
+impl Batch {
+    /// Encode this batch in the current version of the stable encoding.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&VERSION.to_be_bytes());
+        let position = self.position.map(u64::from).unwrap_or(u64::MAX);
+        bytes.extend_from_slice(&position.to_be_bytes());
+        encode_updates(&self.updates, &mut bytes);
+        bytes
+    }
+
+    /// Decode a batch encoded by [`Batch::encode`], in the current or any earlier version of the
+    /// encoding, migrating it to the current version.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BatchDecodeError`] if the bytes are not a valid encoding of a batch, or were
+    /// encoded by a later version.
+    pub fn decode(bytes: &[u8]) -> Result<Batch, BatchDecodeError> {
+        if !bytes.starts_with(&MAGIC) {
+            // Version 0 had no header at all
+            return v0::decode(bytes);
+        }
+
+        let mut reader = Reader(&bytes[MAGIC.len()..]);
+        let batch = match reader.u16()? {
+            1 => {
+                let position = match reader.u64()? {
+                    u64::MAX => None,
+                    position => Some(position.into()),
+                };
+                let updates = reader.updates(0)?;
+                Batch { position, updates }
+            }
+            version => return Err(BatchDecodeError::UnknownVersion(version)),
+        };
+
+        if !reader.0.is_empty() {
+            return Err(BatchDecodeError::TrailingBytes);
+        }
+        Ok(batch)
+    }
+}
+
+fn encode_updates(updates: &[Update], bytes: &mut Vec<u8>) {
+    let count = u32::try_from(updates.len()).expect("batches have fewer than `u32::MAX` updates");
+    bytes.extend_from_slice(&count.to_be_bytes());
+
+    for update in updates {
+        match update {
+            Update::Insert(Witness::Forget, commitment) => {
+                bytes.push(0);
+                bytes.extend_from_slice(&commitment.0.to_bytes());
+            }
+            Update::Insert(Witness::Keep, commitment) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&commitment.0.to_bytes());
+            }
+            Update::Forget(commitment) => {
+                bytes.push(2);
+                bytes.extend_from_slice(&commitment.0.to_bytes());
+            }
+            Update::EndBlock => bytes.push(3),
+            Update::EndEpoch => bytes.push(4),
+            Update::BlockRoot(root) => {
+                bytes.push(5);
+                bytes.extend_from_slice(&root.0.to_bytes());
+            }
+            Update::EpochRoot(root) => {
+                bytes.push(6);
+                bytes.extend_from_slice(&root.0.to_bytes());
+            }
+            Update::Block(nested) => {
+                bytes.push(7);
+                encode_updates(nested, bytes);
+            }
+            Update::Epoch(nested) => {
+                bytes.push(8);
+                encode_updates(nested, bytes);
+            }
+        }
+    }
+}
+
+/// A cursor over the bytes of an encoded [`Batch`].
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], BatchDecodeError> {
+        if self.0.len() < N {
+            return Err(BatchDecodeError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.0.split_at(N);
+        self.0 = rest;
+        Ok(bytes.try_into().expect("slice has length `N`"))
+    }
+
+    fn u16(&mut self) -> Result<u16, BatchDecodeError> {
+        Ok(u16::from_be_bytes(self.bytes()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, BatchDecodeError> {
+        Ok(u32::from_be_bytes(self.bytes()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, BatchDecodeError> {
+        Ok(u64::from_be_bytes(self.bytes()?))
+    }
+
+    fn commitment(&mut self) -> Result<Commitment, BatchDecodeError> {
+        Commitment::try_from(self.bytes::<32>()?).map_err(|_| BatchDecodeError::InvalidCommitment)
+    }
+
+    fn hash(&mut self) -> Result<Hash, BatchDecodeError> {
+        Fq::from_bytes(self.bytes::<32>()?)
+            .map(Hash::new)
+            .map_err(|_| BatchDecodeError::InvalidHash)
+    }
+
+    /// Read a count followed by that many updates, which are nested `depth` deep.
+    fn updates(&mut self, depth: usize) -> Result<Vec<Update>, BatchDecodeError> {
+        let count = self.u32()?;
+        // Don't trust the count for the allocation: each update takes at least one byte
+        let mut updates = Vec::with_capacity((count as usize).min(self.0.len()));
+        for _ in 0..count {
+            let [tag] = self.bytes::<1>()?;
+            updates.push(match tag {
+                0 => Update::Insert(Witness::Forget, self.commitment()?),
+                1 => Update::Insert(Witness::Keep, self.commitment()?),
+                2 => Update::Forget(self.commitment()?),
+                3 => Update::EndBlock,
+                4 => Update::EndEpoch,
+                5 => Update::BlockRoot(block::Root(self.hash()?)),
+                6 => Update::EpochRoot(epoch::Root(self.hash()?)),
+                7 | 8 if depth >= MAX_DEPTH => return Err(BatchDecodeError::TooDeep),
+                7 => Update::Block(self.updates(depth + 1)?),
+                8 => Update::Epoch(self.updates(depth + 1)?),
+                tag => return Err(BatchDecodeError::InvalidTag(tag)),
+            });
+        }
+        Ok(updates)
+    }
+}
+
+mod v0 {
+    //! Version 0 of the encoding, which was the default `bincode` serialization of a
+    //! `Vec<Update>`, with no header.
+    //!
+    //! In this version, blocks and epochs with witnessed commitments were serialized using the
+    //! internal structure of the tree, so they can't be migrated, but they were never inserted by
+    //! any storage backend.
+
+    use serde::de::IgnoredAny;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    enum Update {
+        Insert(Witness, Commitment),
+        Forget(Commitment),
+        EndBlock,
+        EndEpoch,
+        BlockRoot(block::Root),
+        Block(IgnoredAny),
+        EpochRoot(epoch::Root),
+        Epoch(IgnoredAny),
+    }
+
+    pub(super) fn decode(bytes: &[u8]) -> Result<Batch, BatchDecodeError> {
+        let updates: Vec<Update> = bincode::deserialize(bytes)
+            .map_err(|error| BatchDecodeError::Legacy(error.to_string()))?;
+
+        let updates = updates
+            .into_iter()
+            .map(|update| match update {
+                Update::Insert(witness, commitment) => super::Update::Insert(witness, commitment),
+                Update::Forget(commitment) => super::Update::Forget(commitment),
+                Update::EndBlock => super::Update::EndBlock,
+                Update::EndEpoch => super::Update::EndEpoch,
+                Update::BlockRoot(root) => super::Update::BlockRoot(root),
+                Update::EpochRoot(root) => super::Update::EpochRoot(root),
+                Update::Block(_) | Update::Epoch(_) => {
+                    unreachable!("`bincode` can't deserialize `IgnoredAny`")
+                }
+            })
+            .collect();
+
+        Ok(Batch {
+            position: None,
+            updates,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn commitment(i: u64) -> Commitment {
+        Commitment(Fq::from(i))
+    }
+
+    fn assert_same_tree(expected: &Tree, actual: &Tree) {
+        assert_eq!(expected.root(), actual.root());
+        assert_eq!(expected.position(), actual.position());
+        let mut expected_commitments = expected.commitments().collect::<Vec<_>>();
+        let mut actual_commitments = actual.commitments().collect::<Vec<_>>();
+        expected_commitments.sort();
+        actual_commitments.sort();
+        assert_eq!(expected_commitments, actual_commitments);
+        for (commitment, _) in expected_commitments {
+            assert_eq!(expected.witness(commitment), actual.witness(commitment));
+        }
+    }
+
+    #[test]
+    fn replay_and_compact_reproduce_tree() {
+        let mut tree = Tree::new();
+        let mut batches = Vec::new();
+
+        for e in 0..3u64 {
+            for b in 0..4u64 {
+                for i in 0..5u64 {
+                    let c = commitment(e * 100 + b * 10 + i);
+                    let witness = if i % 2 == 0 {
+                        Witness::Keep
+                    } else {
+                        Witness::Forget
+                    };
+                    tree.insert(witness, c).unwrap();
+                }
+                // Forget most of what was witnessed in earlier epochs
+                if e > 0 && b == 0 {
+                    for i in (0..5).step_by(2) {
+                        tree.forget(commitment((e - 1) * 100 + i));
+                        tree.forget(commitment((e - 1) * 100 + 20 + i));
+                    }
+                }
+                tree.end_block().unwrap();
+                tree.insert_block(block::Root(Hash::of(commitment(b))))
+                    .unwrap();
+                // Persist the updates for each block separately
+                batches.push(Batch::take(&mut tree));
+            }
+            tree.end_epoch().unwrap();
+        }
+        // Include blocks and epochs with witnessed commitments built separately
+        let mut block = block::Builder::new();
+        block.insert(Witness::Keep, commitment(2000)).unwrap();
+        block.insert(Witness::Forget, commitment(2001)).unwrap();
+        let mut epoch = epoch::Builder::new();
+        epoch.insert_block(block.clone()).unwrap();
+        epoch.end_block().unwrap();
+        epoch.insert(Witness::Keep, commitment(3000)).unwrap();
+        tree.insert_epoch(epoch).unwrap();
+        tree.insert_block(block).unwrap();
+        // Leave some updates in an unfinalized block
+        tree.insert(Witness::Keep, commitment(1000)).unwrap();
+        batches.push(Batch::take(&mut tree));
+
+        assert_same_tree(&tree, &Tree::from_batches(batches.clone()).unwrap());
+
+        let log = batches
+            .iter()
+            .flat_map(|batch| batch.updates.clone())
+            .collect::<Vec<_>>();
+        let compacted = compact(log.clone()).unwrap();
+        assert!(compacted.updates.len() < log.len());
+        assert!(!compacted
+            .updates
+            .iter()
+            .any(|update| matches!(update, Update::Forget(_))));
+        assert_same_tree(&tree, &Tree::from_batches([compacted.clone()]).unwrap());
+
+        // Compaction is idempotent, and later batches can be appended to a compacted one
+        assert_eq!(compact(compacted.updates.clone()).unwrap(), compacted);
+        tree.forget(commitment(1000));
+        tree.end_block().unwrap();
+        let appended = vec![compacted, Batch::take(&mut tree)];
+        assert_same_tree(&tree, &Tree::from_batches(appended).unwrap());
+    }
+
+    #[test]
+    fn compacting_at_epoch_ends_collapses_spent_epochs() {
+        let mut tree = Tree::new();
+        let mut log = Vec::new();
+
+        for e in 0..4u64 {
+            // A note is created and then spent in a later block of the same epoch
+            tree.insert(Witness::Keep, commitment(e)).unwrap();
+            tree.insert(Witness::Forget, commitment(e + 100)).unwrap();
+            tree.end_block().unwrap();
+            let batch = Batch::take(&mut tree);
+            assert!(!batch.ends_epoch());
+            log.extend(batch.updates);
+
+            tree.forget(commitment(e));
+            tree.end_block().unwrap();
+            tree.end_epoch().unwrap();
+            let batch = Batch::take(&mut tree);
+            assert!(batch.ends_epoch());
+            log.extend(batch.updates);
+            log = compact(log).unwrap().updates;
+
+            // Every epoch so far has been collapsed into its root
+            assert_eq!(log.len() as u64, e + 1);
+            assert!(log
+                .iter()
+                .all(|update| matches!(update, Update::EpochRoot(_))));
+        }
+
+        assert_same_tree(&tree, &Tree::from_updates(log).unwrap());
+    }
+
+    #[test]
+    fn missing_batches_are_detected() {
+        let mut tree = Tree::new();
+        tree.insert(Witness::Keep, commitment(0)).unwrap();
+        let first = Batch::take(&mut tree);
+        tree.end_block().unwrap();
+        let second = Batch::take(&mut tree);
+
+        assert!(matches!(
+            Tree::from_batches([second]),
+            Err(ReplayError::PositionMismatch { .. })
+        ));
+        assert!(Tree::from_batches([first]).is_ok());
+    }
+
+    /// A batch exercising every kind of update, with hashes that are easy to write down.
+    fn golden_batch() -> Batch {
+        let hash = |i: u64| Hash::new(Fq::from(i));
+        Batch {
+            // The position of the tree after replaying these updates: the start of epoch 4
+            position: Some(Position::from(0x0004_0000_0000u64)),
+            updates: vec![
+                Update::Insert(Witness::Keep, commitment(1)),
+                Update::Insert(Witness::Forget, commitment(2)),
+                Update::Forget(commitment(1)),
+                Update::EndBlock,
+                Update::BlockRoot(block::Root(hash(3))),
+                Update::EndEpoch,
+                Update::EpochRoot(epoch::Root(hash(4))),
+                Update::Block(vec![Update::Insert(Witness::Keep, commitment(5))]),
+                Update::Epoch(vec![
+                    Update::Insert(Witness::Keep, commitment(6)),
+                    Update::EndBlock,
+                    Update::BlockRoot(block::Root(hash(7))),
+                    Update::Block(vec![]),
+                ]),
+            ],
+        }
+    }
+
+    fn golden_bytes(hex: &str) -> Vec<u8> {
+        hex::decode(hex.split_whitespace().collect::<String>()).unwrap()
+    }
+
+    #[test]
+    fn golden_v1() {
+        let bytes = golden_bytes(include_str!("../golden/batch-v1.hex"));
+        assert_eq!(golden_batch().encode(), bytes);
+        assert_eq!(Batch::decode(&bytes).unwrap(), golden_batch());
+        assert!(Tree::from_batches([golden_batch()]).is_ok());
+    }
+
+    #[test]
+    fn golden_v0_migrates() {
+        let bytes = golden_bytes(include_str!("../golden/batch-v0.hex"));
+        let batch = Batch::decode(&bytes).unwrap();
+        assert_eq!(batch.position, None);
+        // Version 0 couldn't represent blocks or epochs with witnessed commitments
+        assert_eq!(batch.updates, golden_batch().updates[..7]);
+
+        // Re-encoding migrates it to the current version
+        assert_eq!(Batch::decode(&batch.encode()), Ok(batch));
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let mut bytes = golden_batch().encode();
+        bytes[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&(VERSION + 1).to_be_bytes());
+        assert!(matches!(
+            Batch::decode(&bytes),
+            Err(BatchDecodeError::UnknownVersion(v)) if v == VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn deeply_nested_updates_are_rejected() {
+        // Each level is an `Update::Epoch` containing only the next level
+        let mut bytes = golden_batch().encode()[..MAGIC.len() + 2 + 8].to_vec();
+        for _ in 0..=MAX_DEPTH {
+            bytes.extend_from_slice(&1u32.to_be_bytes());
+            bytes.push(8);
+        }
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        assert_eq!(Batch::decode(&bytes), Err(BatchDecodeError::TooDeep));
+    }
+}
@@ -8,7 +8,7 @@ use std::{
 use crate::prelude::*;
 
 #[doc(inline)]
-pub use crate::internal::hash::{Forgotten, Hash};
+pub use crate::internal::hash::{Forgotten, Hash, DOMAIN_SEPARATOR};
 
 /// Every kind of node in the tree implements [`Node`], and its methods collectively describe every
 /// salient fact about each node, dynamically rather than statically as in the rest of the crate.
@@ -111,7 +111,8 @@ impl Display for Kind {
 ///
 /// This is redundant with the pair of (height, index) if the total size of the tree is known, but
 /// it is useful to reveal it directly.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Place {
     /// The node is not on the frontier.
     Complete,
@@ -356,6 +357,8 @@ impl Any for Node<'_> {
 #[doc(inline)]
 pub use traverse::{traverse, traverse_async};
 
+pub mod export;
+
 /// Functions to perform traversals of [`Node`]s in synchronous and asynchronous contexts.
 pub mod traverse {
     use std::{future::Future, pin::Pin};
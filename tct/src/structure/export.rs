@@ -0,0 +1,146 @@
+//! Export the structure of a tree for debugging, either as a [Graphviz](https://graphviz.org/) DOT
+//! graph or as a serializable [`Dump`].
+
+use std::io::{self, Write};
+
+use super::{Kind, Node, Place};
+
+/// A serializable description of a [`Node`] and everything beneath it, suitable for dumping as
+/// JSON.
+///
+/// Hashes and commitments are represented as hex strings, so that the dump is human-readable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dump {
+    /// The height of the node above the base of the tree.
+    pub height: u8,
+    /// The position of the node (the vertical extension of the position of commitments below).
+    pub position: u64,
+    /// Whether the node is on the frontier or is complete.
+    pub place: Place,
+    /// The most recent time something underneath this node was forgotten.
+    pub forgotten: u64,
+    /// The hash of the node.
+    pub hash: String,
+    /// The witnessed commitment at this node, if it is a leaf which has not been forgotten.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<String>,
+    /// The children of this node, which are absent if the node is a leaf, or if everything
+    /// beneath it was forgotten.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Dump>,
+}
+
+impl From<Node<'_>> for Dump {
+    fn from(node: Node) -> Self {
+        Dump {
+            height: node.height(),
+            position: node.position().into(),
+            place: node.place(),
+            forgotten: node.forgotten().into(),
+            hash: hex::encode(node.hash().to_bytes()),
+            commitment: witnessed(&node).map(|commitment| commitment.to_string()),
+            children: node.children().into_iter().map(Dump::from).collect(),
+        }
+    }
+}
+
+/// Write a [Graphviz](https://graphviz.org/) DOT graph of a [`Node`] and everything beneath it.
+///
+/// Witnessed leaves are filled in, while forgotten leaves and internal nodes everything beneath
+/// which was forgotten are dashed. Nodes on the frontier are outlined in blue.
+pub fn dot(node: Node, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "digraph tct {{")?;
+    writeln!(out, "  node [fontname = monospace];")?;
+    dot_node(node, &mut 0, out)?;
+    writeln!(out, "}}")
+}
+
+/// Write a single node and its children, returning the identifier used for the node.
+fn dot_node(node: Node, next_id: &mut u64, out: &mut impl Write) -> io::Result<u64> {
+    let id = *next_id;
+    *next_id += 1;
+
+    let children = node.children();
+    let mut label = match node.kind() {
+        Kind::Internal { height } => format!(
+            "height {}\\nposition {}",
+            height,
+            u64::from(node.position())
+        ),
+        Kind::Leaf { .. } => format!("position {}", u64::from(node.position())),
+    };
+    if let Some(commitment) = witnessed(&node) {
+        label.push_str(&format!("\\ncommitment {}", short(&commitment.to_string())));
+    }
+    label.push_str(&format!("\\nhash {}", short(&format!("{:?}", node.hash()))));
+    if node.forgotten() != Default::default() {
+        label.push_str(&format!("\\nforgotten {:?}", node.forgotten()));
+    }
+
+    let (shape, style) = match node.kind() {
+        Kind::Leaf {
+            commitment: Some(_),
+        } => ("ellipse", "filled"),
+        Kind::Leaf { commitment: None } => ("ellipse", "dashed"),
+        Kind::Internal { .. } if children.is_empty() => ("box", "dashed"),
+        Kind::Internal { .. } => ("box", "solid"),
+    };
+    let color = match node.place() {
+        Place::Frontier => "blue",
+        Place::Complete => "black",
+    };
+    writeln!(
+        out,
+        "  n{} [label = \"{}\", shape = {}, style = {}, color = {}, fillcolor = palegreen];",
+        id, label, shape, style, color
+    )?;
+
+    for child in children {
+        let child_id = dot_node(child, next_id, out)?;
+        writeln!(out, "  n{} -> n{};", id, child_id)?;
+    }
+
+    Ok(id)
+}
+
+/// The witnessed commitment at a node, if it is a leaf which has not been forgotten.
+fn witnessed(node: &Node) -> Option<crate::Commitment> {
+    if let Kind::Leaf { commitment } = node.kind() {
+        commitment
+    } else {
+        None
+    }
+}
+
+/// Abbreviate a hex string for display in a graph.
+fn short(hex: &str) -> &str {
+    &hex[..hex.len().min(8)]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Commitment, Tree, Witness};
+
+    #[test]
+    fn export_styles_witnessed_and_forgotten_leaves() {
+        let mut tree = Tree::new();
+        let kept = Commitment(1u64.into());
+        tree.insert(Witness::Keep, kept).unwrap();
+        tree.insert(Witness::Forget, Commitment(2u64.into()))
+            .unwrap();
+
+        let mut out = Vec::new();
+        dot(tree.structure(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("digraph tct {"));
+        assert_eq!(out.matches("shape = ellipse, style = filled").count(), 1);
+        assert_eq!(out.matches("shape = ellipse, style = dashed").count(), 1);
+
+        let dump = Dump::from(tree.structure());
+        assert_eq!(dump.hash, hex::encode(tree.root().0.to_bytes()));
+        let json = serde_json::to_string(&dump).unwrap();
+        assert_eq!(dump, serde_json::from_str(&json).unwrap());
+        assert!(json.contains(&kept.to_string()));
+    }
+}
@@ -1,13 +1,17 @@
-use std::fmt::{Debug, Display};
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Display},
+};
 
 use decaf377::{FieldExt, Fq};
-use hash_hasher::HashedMap;
+use hash_hasher::HashBuildHasher;
 use penumbra_proto::{crypto as pb, Protobuf};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::error::*;
 use crate::prelude::{Witness as _, *};
+use crate::storage::Update;
 use crate::Witness;
 
 #[path = "epoch.rs"]
@@ -16,21 +20,59 @@ pub(crate) use epoch::block;
 
 /// A sparse merkle tree witnessing up to 65,536 epochs of up to 65,536 blocks of up to 65,536
 /// [`Commitment`]s.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Cloning a [`Tree`] is cheap, because clones share all their structure until they are modified;
+/// see [`Tree::checkpoint`].
+#[derive(Derivative, Clone, Serialize, Deserialize)]
+#[derivative(Debug)]
 pub struct Tree {
-    index: HashedMap<Commitment, index::within::Tree>,
+    index: im::HashMap<Commitment, index::within::Tree, HashBuildHasher>,
     inner: frontier::Top<frontier::Tier<frontier::Tier<frontier::Item>>>,
+    #[serde(skip)]
+    updates: Vec<Update>,
+    #[serde(skip)]
+    #[derivative(Debug = "ignore")]
+    history: History,
 }
 
 impl Default for Tree {
     fn default() -> Self {
         Self {
-            index: HashedMap::default(),
+            index: im::HashMap::default(),
             inner: frontier::Top::new(frontier::TrackForgotten::Yes),
+            updates: Vec::new(),
+            history: History::default(),
         }
     }
 }
 
+/// A bounded window of past versions of a [`Tree`], oldest first, each paired with its root.
+///
+/// Versions share their structure with each other and with the current tree, so each one costs
+/// only as much memory as the parts of the tree which changed after it.
+#[derive(Clone, Default)]
+struct History {
+    window: usize,
+    versions: VecDeque<(Root, Tree)>,
+}
+
+/// A snapshot of the state of a [`Tree`], taken using [`Tree::checkpoint`], which the tree can be
+/// restored to using [`Tree::rollback`].
+#[derive(Debug, Clone)]
+pub struct Checkpoint(Tree);
+
+impl Checkpoint {
+    /// The position of the tree when this checkpoint was taken.
+    pub fn position(&self) -> Option<Position> {
+        self.0.position()
+    }
+
+    /// The root of the tree when this checkpoint was taken.
+    pub fn root(&self) -> Root {
+        self.0.root()
+    }
+}
+
 /// The root hash of a [`Tree`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(try_from = "pb::MerkleRoot", into = "pb::MerkleRoot")]
@@ -214,6 +256,8 @@ impl Tree {
             }
         }
 
+        self.updates.push(Update::Insert(witness, commitment));
+
         let position = Position(position);
         trace!(?position);
         Ok(position)
@@ -267,6 +311,7 @@ impl Tree {
             debug_assert!(forgotten);
             // Remove this entry from the index
             self.index.remove(&commitment);
+            self.updates.push(Update::Forget(commitment));
         }
 
         trace!(?forgotten);
@@ -311,12 +356,17 @@ impl Tree {
         &mut self,
         block: impl Into<block::Finalized>,
     ) -> Result<block::Root, InsertBlockError> {
+        let block = block.into();
+        let update = block.to_update();
+
         // We split apart the inside so that we get the right instrumention when this is called as
         // an inner function in `end_block`
         let block_root = self.insert_block_uninstrumented(block).map_err(|error| {
             error!(%error);
             error
         })?;
+        self.updates.push(update);
+        self.record_version();
         trace!(?block_root);
         Ok(block_root)
     }
@@ -325,7 +375,11 @@ impl Tree {
         &mut self,
         block: impl Into<block::Finalized>,
     ) -> Result<block::Root, InsertBlockError> {
-        let block::Finalized { inner, index } = block.into();
+        let block::Finalized {
+            inner,
+            index,
+            updates,
+        } = block.into();
 
         // Convert the top level inside of the block to a tier that can be slotted into the epoch
         // We have this be an `Option` because we need to `take` out of it inside closures
@@ -334,8 +388,9 @@ impl Tree {
             Insert::Hash(hash) => hash.into(),
         });
 
-        // We have this be an `Option` because we need to `take` out of it in closures
+        // We have these be `Option`s because we need to `take` out of them in closures
         let mut index = Some(index);
+        let mut updates = Some(updates);
 
         // Finalize the latest block, if it exists and is not yet finalized -- this means that
         // position calculations will be correct, since they will start at the next block
@@ -360,6 +415,7 @@ impl Tree {
                     return Some(Err(InsertBlockError::EpochFull(block::Finalized {
                         inner: inner.take().unwrap().finalize_owned().map(Into::into),
                         index: index.take().unwrap(),
+                        updates: updates.take().unwrap(),
                     })));
                 }
 
@@ -381,6 +437,7 @@ impl Tree {
                     return Err(InsertBlockError::Full(block::Finalized {
                         inner: inner.take().unwrap().finalize_owned().map(Into::into),
                         index: index.take().unwrap(),
+                        updates: updates.take().unwrap(),
                     }));
                 }
 
@@ -454,6 +511,8 @@ impl Tree {
                     error
                 })?;
         };
+        self.updates.push(Update::EndBlock);
+        self.record_version();
 
         trace!(finalized_block_root = ?finalized_root);
         Ok(finalized_root)
@@ -509,12 +568,17 @@ impl Tree {
         &mut self,
         epoch: impl Into<epoch::Finalized>,
     ) -> Result<epoch::Root, InsertEpochError> {
+        let epoch = epoch.into();
+        let update = epoch.to_update();
+
         // We split apart the inside so that we get the right instrumention when this is called as
         // an inner function in `end_epoch`
         let epoch_root = self.insert_epoch_uninstrumented(epoch).map_err(|error| {
             error!(%error);
             error
         })?;
+        self.updates.push(update);
+        self.record_version();
         trace!(?epoch_root);
         Ok(epoch_root)
     }
@@ -523,12 +587,20 @@ impl Tree {
         &mut self,
         epoch: impl Into<epoch::Finalized>,
     ) -> Result<epoch::Root, InsertEpochError> {
-        let epoch::Finalized { inner, index } = epoch.into();
+        let epoch::Finalized {
+            inner,
+            index,
+            updates,
+        } = epoch.into();
 
         // If the insertion would fail, return an error
         if self.inner.is_full() {
             // There is no room for another epoch to be inserted into the tree
-            return Err(InsertEpochError(epoch::Finalized { inner, index }));
+            return Err(InsertEpochError(epoch::Finalized {
+                inner,
+                index,
+                updates,
+            }));
         }
 
         // Convert the top level inside of the epoch to a tier that can be slotted into the tree
@@ -602,6 +674,8 @@ impl Tree {
                     error
                 })?;
         };
+        self.updates.push(Update::EndEpoch);
+        self.record_version();
 
         trace!(finalized_epoch_root = ?finalized_root);
         Ok(finalized_root)
@@ -686,6 +760,117 @@ impl Tree {
         self.index.iter().map(|(c, p)| (*c, Position(*p)))
     }
 
+    /// Take all the [`Update`]s made to this [`Tree`] since the last time they were taken (or since
+    /// it was created).
+    ///
+    /// Appending these to persistent storage after every block, and later replaying them using
+    /// [`Tree::from_updates`], persists the tree incrementally, rather than serializing it in its
+    /// entirety every time it changes.
+    pub fn take_updates(&mut self) -> Vec<Update> {
+        std::mem::take(&mut self.updates)
+    }
+
+    /// Keep the versions of this [`Tree`] as of the end of each of the `window` most recent blocks
+    /// (or epochs), so that commitments can be witnessed relative to their roots using
+    /// [`Tree::witness_at`].
+    ///
+    /// Only versions from after this is called are kept, and they are not included in the
+    /// [`Update`]s taken from the tree, so a tree reconstructed from storage starts with no
+    /// history. Setting the window to zero discards all past versions.
+    #[instrument(skip(self))]
+    pub fn set_history_window(&mut self, window: usize) {
+        self.history.window = window;
+        while self.history.versions.len() > window {
+            self.history.versions.pop_front();
+        }
+    }
+
+    /// Record the current version of this [`Tree`] in its history, if it is keeping one.
+    fn record_version(&mut self) {
+        if self.history.window == 0 {
+            return;
+        }
+
+        let root = self.root();
+
+        // Ending an epoch right after ending a block (or ending an empty block) does not change the
+        // root, and the only thing that can have changed since the version with that root was
+        // recorded is that commitments were forgotten, so the earlier version is kept instead
+        if matches!(self.history.versions.back(), Some((last, _)) if *last == root) {
+            return;
+        }
+
+        let version = Tree {
+            index: self.index.clone(),
+            inner: self.inner.clone(),
+            updates: Vec::new(),
+            history: History::default(),
+        };
+        self.history.versions.push_back((root, version));
+        if self.history.versions.len() > self.history.window {
+            self.history.versions.pop_front();
+        }
+    }
+
+    /// Get a [`Proof`] of inclusion for the given [`Commitment`] relative to the given root, which
+    /// must be either the current root of this [`Tree`] or the root of one of the past versions
+    /// kept in its history (see [`Tree::set_history_window`]).
+    ///
+    /// If the root is unknown, or the commitment was not witnessed in the version of the tree
+    /// with that root, returns `None`.
+    #[instrument(skip(self))]
+    pub fn witness_at(&self, commitment: Commitment, root: Root) -> Option<Proof> {
+        if root == self.root() {
+            if let Some(proof) = self.witness(commitment) {
+                return Some(proof);
+            }
+        }
+
+        let (_, version) = self
+            .history
+            .versions
+            .iter()
+            .rev()
+            .find(|(version_root, _)| *version_root == root)?;
+        version.witness(commitment)
+    }
+
+    /// Get the roots of the past versions of this [`Tree`] kept in its history, oldest first.
+    ///
+    /// Commitments can be witnessed relative to any of these, or to the current root, using
+    /// [`Tree::witness_at`].
+    pub fn historical_roots(&self) -> impl Iterator<Item = Root> + '_ {
+        self.history.versions.iter().map(|(root, _)| *root)
+    }
+
+    /// Take a [`Checkpoint`] of the current state of this [`Tree`], which it can later be restored
+    /// to using [`Tree::rollback`].
+    ///
+    /// This is cheap: the checkpoint shares its structure with the tree, and subsequent
+    /// modifications to the tree copy only the parts of it which they change.
+    ///
+    /// This is meant for callers which may need to abandon a block partway through, like a client
+    /// whose scan of a block fails after it has started inserting into the tree. A full node never
+    /// needs to: invalid transactions are rejected before they touch the tree, and a failure later
+    /// in a block is fatal, so the partially-built tree is never persisted.
+    #[instrument(skip(self))]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.clone())
+    }
+
+    /// Restore this [`Tree`] to the state it was in when the given [`Checkpoint`] was taken,
+    /// undoing every change made since then.
+    ///
+    /// This restores the position, root, witnessed commitments, and history of the tree, as well
+    /// as its pending [`Update`]s. If [`Tree::take_updates`] was called after the checkpoint was taken,
+    /// rolling back will cause the updates which were taken to be returned again by the next call,
+    /// so callers which persist updates should only roll back to checkpoints taken since they last
+    /// did so.
+    #[instrument(skip(self, checkpoint))]
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        *self = checkpoint.0;
+    }
+
     /// Get a dynamic representation of the internal structure of the tree, which can be traversed
     /// and inspected arbitrarily.
     pub fn structure(&self) -> structure::Node {
@@ -0,0 +1,53 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use decaf377_rdsa::{
+    batch, Signature, SigningKey, SpendAuth, VerificationKey, VerificationKeyBytes,
+};
+use rand_core::OsRng;
+
+fn create_signatures(
+    count: usize,
+) -> Vec<(VerificationKey<SpendAuth>, Signature<SpendAuth>, [u8; 64])> {
+    (0..count)
+        .map(|i| {
+            let sk = SigningKey::<SpendAuth>::new(OsRng);
+            let msg = [i as u8; 64];
+            let sig = sk.sign(OsRng, &msg);
+            (VerificationKey::from(&sk), sig, msg)
+        })
+        .collect()
+}
+
+fn verify_individually(sigs: &[(VerificationKey<SpendAuth>, Signature<SpendAuth>, [u8; 64])]) {
+    for (vk, sig, msg) in sigs {
+        vk.verify(msg, sig).unwrap();
+    }
+}
+
+fn verify_batch(sigs: &[(VerificationKey<SpendAuth>, Signature<SpendAuth>, [u8; 64])]) {
+    let mut verifier = batch::Verifier::new();
+    for (vk, sig, msg) in sigs {
+        verifier.queue((VerificationKeyBytes::from(*vk), *sig, msg));
+    }
+    verifier.verify(OsRng).unwrap();
+}
+
+fn bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spend-auth-signatures");
+    // We're already benchmarking batches of signatures, so we don't need as many runs
+    group.sample_size(10);
+    for count in [8, 64, 512] {
+        let sigs = create_signatures(count);
+        group.throughput(Throughput::Elements(count as u64));
+
+        group.bench_function(format!("verify_individually_{}", count).as_str(), |b| {
+            b.iter(|| verify_individually(&sigs))
+        });
+        group.bench_function(format!("verify_batch_{}", count).as_str(), |b| {
+            b.iter(|| verify_batch(&sigs))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -7,11 +7,13 @@ mod delegate;
 pub mod output;
 pub mod spend;
 mod undelegate;
+mod undelegate_claim;
 
 pub use delegate::Delegate;
 pub use output::Output;
 pub use spend::Spend;
 pub use undelegate::Undelegate;
+pub use undelegate_claim::UndelegateClaim;
 
 /// An action performed by a Penumbra transaction.
 #[derive(Clone, Debug)]
@@ -20,6 +22,7 @@ pub enum Action {
     Spend(spend::Spend),
     Delegate(Delegate),
     Undelegate(Undelegate),
+    UndelegateClaim(UndelegateClaim),
     ValidatorDefinition(pbs::ValidatorDefinition),
     IBCAction(pb_ibc::IbcAction),
 }
@@ -33,6 +36,7 @@ impl Action {
             Action::Spend(spend) => spend.body.value_commitment,
             Action::Delegate(delegate) => delegate.value_commitment(),
             Action::Undelegate(undelegate) => undelegate.value_commitment(),
+            Action::UndelegateClaim(claim) => claim.value_commitment(),
             // These actions just post data to the chain, and leave the value balance
             // unchanged.
             Action::ValidatorDefinition(_) => value::Commitment::default(),
@@ -58,6 +62,9 @@ impl From<Action> for pb::Action {
             Action::Undelegate(inner) => pb::Action {
                 action: Some(pb::action::Action::Undelegate(inner.into())),
             },
+            Action::UndelegateClaim(inner) => pb::Action {
+                action: Some(pb::action::Action::UndelegateClaim(inner.into())),
+            },
             Action::ValidatorDefinition(inner) => pb::Action {
                 action: Some(pb::action::Action::ValidatorDefinition(inner)),
             },
@@ -81,6 +88,9 @@ impl TryFrom<pb::Action> for Action {
             pb::action::Action::Spend(inner) => Ok(Action::Spend(inner.try_into()?)),
             pb::action::Action::Delegate(inner) => Ok(Action::Delegate(inner.try_into()?)),
             pb::action::Action::Undelegate(inner) => Ok(Action::Undelegate(inner.try_into()?)),
+            pb::action::Action::UndelegateClaim(inner) => {
+                Ok(Action::UndelegateClaim(inner.try_into()?))
+            }
             pb::action::Action::ValidatorDefinition(inner) => {
                 Ok(Action::ValidatorDefinition(inner))
             }
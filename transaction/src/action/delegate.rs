@@ -22,6 +22,12 @@ pub struct Delegate {
     /// (and should be checked in transaction validation!), but including it allows
     /// stateless verification that the transaction is internally consistent.
     pub delegation_amount: u64,
+    /// Whether this delegation adds to the validator's self-bond.
+    ///
+    /// The delegation tokens of a self-bond are held by the chain, rather than
+    /// produced by the transaction, so that only the validator can withdraw
+    /// them, with a self-bond [`Undelegate`](crate::action::Undelegate).
+    pub self_bond: bool,
 }
 
 impl Delegate {
@@ -32,6 +38,10 @@ impl Delegate {
             asset_id: STAKING_TOKEN_ASSET_ID.clone(),
         }
         .commit(Fr::zero());
+        if self.self_bond {
+            // The delegation tokens of a self-bond are held by the chain.
+            return -stake;
+        }
         let delegation = Value {
             amount: self.delegation_amount,
             asset_id: DelegationToken::new(self.validator_identity.clone()).id(),
@@ -52,6 +62,7 @@ impl From<Delegate> for pb::Delegate {
             epoch_index: d.epoch_index,
             unbonded_amount: d.unbonded_amount,
             delegation_amount: d.delegation_amount,
+            self_bond: d.self_bond,
         }
     }
 }
@@ -67,6 +78,7 @@ impl TryFrom<pb::Delegate> for Delegate {
             epoch_index: d.epoch_index,
             unbonded_amount: d.unbonded_amount,
             delegation_amount: d.delegation_amount,
+            self_bond: d.self_bond,
         })
     }
 }
@@ -3,7 +3,10 @@ use std::convert::{TryFrom, TryInto};
 use anyhow::Error;
 use bytes::Bytes;
 use penumbra_crypto::{
-    memo::MemoCiphertext, note, proofs::transparent::OutputProof, value, NotePayload,
+    memo::MemoCiphertext,
+    note,
+    proofs::{groth16, transparent, OutputProof},
+    value, NotePayload,
 };
 use penumbra_proto::{transaction as pb, Protobuf};
 
@@ -25,10 +28,17 @@ impl Protobuf<pb::Output> for Output {}
 
 impl From<Output> for pb::Output {
     fn from(output: Output) -> Self {
-        let proof: Vec<u8> = output.proof.into();
+        let proof = match output.proof {
+            OutputProof::Transparent(proof) => {
+                pb::output::Proof::TransparentProof(Vec::<u8>::from(proof).into())
+            }
+            OutputProof::Groth16(proof) => {
+                pb::output::Proof::Groth16Proof(Vec::<u8>::from(proof).into())
+            }
+        };
         pb::Output {
             body: Some(output.body.into()),
-            zkproof: proof.into(),
+            proof: Some(proof),
         }
     }
 }
@@ -37,14 +47,26 @@ impl TryFrom<pb::Output> for Output {
     type Error = Error;
 
     fn try_from(proto: pb::Output) -> anyhow::Result<Self, Self::Error> {
+        let proof = match proto
+            .proof
+            .ok_or_else(|| anyhow::anyhow!("missing output proof"))?
+        {
+            pb::output::Proof::TransparentProof(bytes) => {
+                transparent::OutputProof::try_from(&bytes[..])
+                    .map_err(|_| anyhow::anyhow!("output proof malformed"))?
+                    .into()
+            }
+            pb::output::Proof::Groth16Proof(bytes) => groth16::OutputProof::try_from(&bytes[..])
+                .map_err(|_| anyhow::anyhow!("output proof malformed"))?
+                .into(),
+        };
+
         Ok(Output {
             body: proto
                 .body
                 .ok_or_else(|| anyhow::anyhow!("missing output body"))?
                 .try_into()?,
-            proof: proto.zkproof[..]
-                .try_into()
-                .map_err(|_| anyhow::anyhow!("output body malformed"))?,
+            proof,
         })
     }
 }
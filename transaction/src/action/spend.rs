@@ -3,7 +3,7 @@ use std::convert::{TryFrom, TryInto};
 use anyhow::Error;
 use bytes::Bytes;
 use penumbra_crypto::{
-    proofs::transparent::SpendProof,
+    proofs::{groth16, transparent, SpendProof},
     rdsa::{Signature, SpendAuth, VerificationKey},
     value, Nullifier,
 };
@@ -20,11 +20,18 @@ impl Protobuf<transaction::Spend> for Spend {}
 
 impl From<Spend> for transaction::Spend {
     fn from(msg: Spend) -> Self {
-        let proof: Vec<u8> = msg.proof.into();
+        let proof = match msg.proof {
+            SpendProof::Transparent(proof) => {
+                transaction::spend::Proof::TransparentProof(Vec::<u8>::from(proof).into())
+            }
+            SpendProof::Groth16(proof) => {
+                transaction::spend::Proof::Groth16Proof(Vec::<u8>::from(proof).into())
+            }
+        };
         transaction::Spend {
             body: Some(msg.body.into()),
             auth_sig: Some(msg.auth_sig.into()),
-            zkproof: proof.into(),
+            proof: Some(proof),
         }
     }
 }
@@ -42,9 +49,21 @@ impl TryFrom<transaction::Spend> for Spend {
             .ok_or_else(|| anyhow::anyhow!("spend body malformed"))?
             .try_into()?;
 
-        let proof = (proto.zkproof[..])
-            .try_into()
-            .map_err(|_| anyhow::anyhow!("spend body malformed"))?;
+        let proof = match proto
+            .proof
+            .ok_or_else(|| anyhow::anyhow!("missing spend proof"))?
+        {
+            transaction::spend::Proof::TransparentProof(bytes) => {
+                transparent::SpendProof::try_from(&bytes[..])
+                    .map_err(|_| anyhow::anyhow!("spend proof malformed"))?
+                    .into()
+            }
+            transaction::spend::Proof::Groth16Proof(bytes) => {
+                groth16::SpendProof::try_from(&bytes[..])
+                    .map_err(|_| anyhow::anyhow!("spend proof malformed"))?
+                    .into()
+            }
+        };
 
         Ok(Spend {
             body,
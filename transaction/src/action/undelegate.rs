@@ -1,6 +1,4 @@
-use penumbra_crypto::{
-    value, DelegationToken, Fr, IdentityKey, Value, Zero, STAKING_TOKEN_ASSET_ID,
-};
+use penumbra_crypto::{value, DelegationToken, Fr, IdentityKey, UnbondingToken, Value, Zero};
 use penumbra_proto::{stake as pb, Protobuf};
 use serde::{Deserialize, Serialize};
 
@@ -10,10 +8,15 @@ use serde::{Deserialize, Serialize};
 pub struct Undelegate {
     /// The identity key of the validator to undelegate from.
     pub validator_identity: IdentityKey,
-    /// The index of the epoch in which this undelegation was performed.
+    /// The index of the epoch after the one in which this undelegation was
+    /// performed, whose rate determines the unbonded amount.
     /// The undelegation takes effect after the unbonding period.
     pub epoch_index: u64,
     /// The amount to undelegate, in units of unbonded stake.
+    ///
+    /// This amount is produced as unbonding tokens, which can be claimed as
+    /// staking tokens with an [`UndelegateClaim`](crate::action::UndelegateClaim)
+    /// after the unbonding period.
     pub unbonded_amount: u64,
     /// The amount of delegation tokens produced by this action.
     ///
@@ -21,24 +24,45 @@ pub struct Undelegate {
     /// (and should be checked in transaction validation!), but including it allows
     /// stateless verification that the transaction is internally consistent.
     pub delegation_amount: u64,
+    /// Whether this undelegation withdraws from the validator's self-bond.
+    ///
+    /// The delegation tokens are taken from those held by the chain for the
+    /// self-bond, rather than consumed from the transaction.
+    pub self_bond: bool,
 }
 
 impl Undelegate {
     /// Compute a commitment to the value contributed to a transaction by this undelegation.
     pub fn value_commitment(&self) -> value::Commitment {
-        let stake = Value {
+        let unbonding = Value {
             amount: self.unbonded_amount,
-            asset_id: STAKING_TOKEN_ASSET_ID.clone(),
+            asset_id: self.unbonding_token().id(),
         }
         .commit(Fr::zero());
+        if self.self_bond {
+            // The delegation tokens of a self-bond are held by the chain.
+            return unbonding;
+        }
         let delegation = Value {
             amount: self.delegation_amount,
             asset_id: DelegationToken::new(self.validator_identity.clone()).id(),
         }
         .commit(Fr::zero());
 
-        // We consume the delegation tokens and produce the staking tokens.
-        stake - delegation
+        // We consume the delegation tokens and produce the unbonding tokens.
+        unbonding - delegation
+    }
+
+    /// The unbonding token produced by this undelegation.
+    ///
+    /// Unbonding starts in the epoch the undelegation is included in, which is
+    /// the one before `epoch_index`, because the undelegated stake is still at
+    /// risk of being slashed until that epoch ends.
+    pub fn unbonding_token(&self) -> UnbondingToken {
+        UnbondingToken::new(
+            self.validator_identity.clone(),
+            self.epoch_index.saturating_sub(1),
+        )
     }
 }
 
@@ -51,6 +75,7 @@ impl From<Undelegate> for pb::Undelegate {
             epoch_index: d.epoch_index,
             unbonded_amount: d.unbonded_amount,
             delegation_amount: d.delegation_amount,
+            self_bond: d.self_bond,
         }
     }
 }
@@ -66,6 +91,7 @@ impl TryFrom<pb::Undelegate> for Undelegate {
             epoch_index: d.epoch_index,
             unbonded_amount: d.unbonded_amount,
             delegation_amount: d.delegation_amount,
+            self_bond: d.self_bond,
         })
     }
 }
@@ -0,0 +1,87 @@
+use penumbra_crypto::{
+    value, Fr, IdentityKey, UnbondingToken, Value, Zero, STAKING_TOKEN_ASSET_ID,
+};
+use penumbra_proto::{stake as pb, Protobuf};
+use serde::{Deserialize, Serialize};
+
+/// A transaction action converting unbonding tokens into staking tokens, once
+/// the unbonding period of an undelegation has elapsed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "pb::UndelegateClaim", into = "pb::UndelegateClaim")]
+pub struct UndelegateClaim {
+    /// The identity key of the validator the undelegation was from.
+    pub validator_identity: IdentityKey,
+    /// The index of the epoch in which unbonding began.
+    pub start_epoch_index: u64,
+    /// The slashing penalty applied to the validator during the unbonding
+    /// period, expressed in basis points of basis points.
+    ///
+    /// This is determined by the chain state (and should be checked in
+    /// transaction validation!), but including it allows stateless
+    /// verification that the transaction is internally consistent.
+    pub penalty: u64,
+    /// The amount of unbonding tokens consumed by this action.
+    pub unbonding_amount: u64,
+}
+
+impl UndelegateClaim {
+    /// The unbonding token consumed by this claim.
+    pub fn unbonding_token(&self) -> UnbondingToken {
+        UnbondingToken::new(self.validator_identity.clone(), self.start_epoch_index)
+    }
+
+    /// The amount of staking tokens produced by this claim, after applying the
+    /// slashing penalty.
+    pub fn unbonded_amount(&self) -> u64 {
+        // penalty is in bps of bps, so the remaining fraction is (1e8 - penalty) / 1e8.
+        let remaining = 1_0000_0000u128.saturating_sub(self.penalty as u128);
+        ((self.unbonding_amount as u128 * remaining) / 1_0000_0000)
+            .try_into()
+            .expect("unbonded amount is at most the unbonding amount")
+    }
+
+    /// Compute a commitment to the value contributed to a transaction by this claim.
+    pub fn value_commitment(&self) -> value::Commitment {
+        let stake = Value {
+            amount: self.unbonded_amount(),
+            asset_id: STAKING_TOKEN_ASSET_ID.clone(),
+        }
+        .commit(Fr::zero());
+        let unbonding = Value {
+            amount: self.unbonding_amount,
+            asset_id: self.unbonding_token().id(),
+        }
+        .commit(Fr::zero());
+
+        // We consume the unbonding tokens and produce the staking tokens.
+        stake - unbonding
+    }
+}
+
+impl Protobuf<pb::UndelegateClaim> for UndelegateClaim {}
+
+impl From<UndelegateClaim> for pb::UndelegateClaim {
+    fn from(c: UndelegateClaim) -> Self {
+        pb::UndelegateClaim {
+            validator_identity: Some(c.validator_identity.into()),
+            start_epoch_index: c.start_epoch_index,
+            penalty: c.penalty,
+            unbonding_amount: c.unbonding_amount,
+        }
+    }
+}
+
+impl TryFrom<pb::UndelegateClaim> for UndelegateClaim {
+    type Error = anyhow::Error;
+    fn try_from(c: pb::UndelegateClaim) -> Result<Self, Self::Error> {
+        Ok(Self {
+            validator_identity: c
+                .validator_identity
+                .ok_or_else(|| anyhow::anyhow!("missing validator identity"))?
+                .try_into()?,
+            start_epoch_index: c.start_epoch_index,
+            penalty: c.penalty,
+            unbonding_amount: c.unbonding_amount,
+        })
+    }
+}
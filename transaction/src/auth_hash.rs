@@ -4,7 +4,7 @@ use penumbra_crypto::FullViewingKey;
 use penumbra_proto::{transaction as pb, Message, Protobuf};
 
 use crate::{
-    action::{output, spend, Delegate, Undelegate},
+    action::{output, spend, Delegate, Undelegate, UndelegateClaim},
     plan::TransactionPlan,
     Action, Fee, Transaction, TransactionBody,
 };
@@ -110,6 +110,9 @@ impl TransactionPlan {
         for undelegation in self.undelegations() {
             state.update(undelegation.auth_hash().as_bytes());
         }
+        for claim in self.undelegate_claims() {
+            state.update(claim.auth_hash().as_bytes());
+        }
         // These are data payloads, so just hash them directly,
         // since we consider them authorizing data.
         for payload in self.validator_definitions() {
@@ -150,6 +153,7 @@ impl Action {
             Action::Spend(spend) => spend.body.auth_hash(),
             Action::Delegate(delegate) => delegate.auth_hash(),
             Action::Undelegate(undelegate) => undelegate.auth_hash(),
+            Action::UndelegateClaim(claim) => claim.auth_hash(),
             // These are data payloads, so just hash them directly,
             // since we consider them authorizing data.
             Action::ValidatorDefinition(payload) => Params::default()
@@ -209,6 +213,7 @@ impl Delegate {
         state.update(&self.epoch_index.to_le_bytes());
         state.update(&self.unbonded_amount.to_le_bytes());
         state.update(&self.delegation_amount.to_le_bytes());
+        state.update(&[self.self_bond as u8]);
 
         state.finalize()
     }
@@ -226,6 +231,24 @@ impl Undelegate {
         state.update(&self.epoch_index.to_le_bytes());
         state.update(&self.unbonded_amount.to_le_bytes());
         state.update(&self.delegation_amount.to_le_bytes());
+        state.update(&[self.self_bond as u8]);
+
+        state.finalize()
+    }
+}
+
+impl UndelegateClaim {
+    fn auth_hash(&self) -> Hash {
+        let mut state = blake2b_simd::Params::default()
+            .personal(b"PAH:udlgt_claim")
+            .to_state();
+
+        // All of these fields are fixed-length, so we can just throw them
+        // in the hash one after the other.
+        state.update(&self.validator_identity.0.to_bytes());
+        state.update(&self.start_epoch_index.to_le_bytes());
+        state.update(&self.penalty.to_le_bytes());
+        state.update(&self.unbonding_amount.to_le_bytes());
 
         state.finalize()
     }
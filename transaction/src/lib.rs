@@ -4,6 +4,8 @@ mod auth_data;
 mod auth_hash;
 mod error;
 mod transaction;
+mod verify;
+mod view;
 mod witness_data;
 
 pub mod action;
@@ -14,4 +16,5 @@ pub use auth_data::AuthorizationData;
 pub use auth_hash::AuthHash;
 pub use error::Error;
 pub use transaction::{Fee, Transaction, TransactionBody};
+pub use view::{ActionView, OutputView, SpendView, TransactionPerspective, TransactionView};
 pub use witness_data::WitnessData;
@@ -3,10 +3,12 @@
 
 use anyhow::Result;
 use penumbra_proto::{ibc as pb_ibc, stake as pb_stake, transaction as pb, Protobuf};
+use rand::seq::SliceRandom;
+use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    action::{Delegate, Undelegate},
+    action::{Delegate, Undelegate, UndelegateClaim},
     Fee,
 };
 
@@ -80,6 +82,16 @@ impl TransactionPlan {
         })
     }
 
+    pub fn undelegate_claims(&self) -> impl Iterator<Item = &UndelegateClaim> {
+        self.actions.iter().filter_map(|action| {
+            if let ActionPlan::UndelegateClaim(c) = action {
+                Some(c)
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn ibc_actions(&self) -> impl Iterator<Item = &pb_ibc::IbcAction> {
         self.actions.iter().filter_map(|action| {
             if let ActionPlan::IBCAction(ibc_action) = action {
@@ -99,6 +111,24 @@ impl TransactionPlan {
             }
         })
     }
+
+    /// Pad this plan with [dummy spends](SpendPlan::dummy) and [dummy
+    /// outputs](OutputPlan::dummy), so that it has at least `spends` spends and
+    /// at least `outputs` outputs.
+    ///
+    /// The actions are shuffled afterwards, so that the dummies aren't always
+    /// the last spends and outputs in the transaction.
+    pub fn pad<R: RngCore + CryptoRng>(&mut self, rng: &mut R, spends: usize, outputs: usize) {
+        let spend_count = self.spend_plans().count();
+        for _ in spend_count..spends {
+            self.actions.push(SpendPlan::dummy(rng).into());
+        }
+        let output_count = self.output_plans().count();
+        for _ in output_count..outputs {
+            self.actions.push(OutputPlan::dummy(rng).into());
+        }
+        self.actions.shuffle(rng);
+    }
 }
 
 impl Protobuf<pb::TransactionPlan> for TransactionPlan {}
@@ -132,3 +162,74 @@ impl TryFrom<pb::TransactionPlan> for TransactionPlan {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::{
+        keys::{SeedPhrase, SpendKey},
+        memo::MemoPlaintext,
+        Note, Value, STAKING_TOKEN_ASSET_ID,
+    };
+    use penumbra_tct as tct;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{Action, WitnessData};
+
+    #[test]
+    fn padded_plan_builds_valid_transaction() {
+        let rng = OsRng;
+        let seed_phrase = SeedPhrase::generate(rng);
+        let sk = SpendKey::from_seed_phrase(seed_phrase, 0);
+        let fvk = sk.full_viewing_key();
+        let (addr, _dtk) = fvk.incoming().payment_address(0u64.into());
+
+        let value = Value {
+            amount: 10000,
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        };
+        let note = Note::generate(&mut OsRng, &addr, value);
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, note.commit()).unwrap();
+
+        let mut plan = TransactionPlan {
+            chain_id: "penumbra-test".to_string(),
+            actions: vec![
+                SpendPlan::new(&mut OsRng, note, 0u64.into()).into(),
+                OutputPlan::new(&mut OsRng, value, addr, MemoPlaintext::default()).into(),
+            ],
+            ..Default::default()
+        };
+        plan.pad(&mut OsRng, 4, 4);
+        assert_eq!(plan.spend_plans().count(), 4);
+        assert_eq!(plan.output_plans().count(), 4);
+
+        let auth_data = plan.authorize(rng, &sk);
+        let witness_data = WitnessData {
+            anchor: nct.root(),
+            note_commitment_proofs: plan
+                .spend_plans()
+                .filter(|spend| !spend.is_dummy())
+                .map(|spend| nct.witness(spend.note.commit()).unwrap())
+                .collect(),
+        };
+        let transaction = plan
+            .build(&mut OsRng, fvk, auth_data, witness_data)
+            .unwrap();
+
+        transaction.verify_signatures().unwrap();
+        for action in transaction.transaction_body().actions {
+            if let Action::Spend(spend) = action {
+                spend
+                    .proof
+                    .verify(
+                        transaction.anchor,
+                        spend.body.value_commitment,
+                        spend.body.nullifier,
+                        spend.body.rk,
+                    )
+                    .unwrap();
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@ mod spend;
 pub use output::OutputPlan;
 pub use spend::SpendPlan;
 
-use crate::action::{Delegate, Undelegate};
+use crate::action::{Delegate, Undelegate, UndelegateClaim};
 
 /// A declaration of a planned [`Action`], for use in transaction creation.
 ///
@@ -27,6 +27,9 @@ pub enum ActionPlan {
     /// We don't need any extra information (yet) to understand undelegations,
     /// because we don't yet use flow encryption.
     Undelegate(Undelegate),
+    /// We don't need any extra information (yet) to understand undelegation
+    /// claims, because the claimed amount is public.
+    UndelegateClaim(UndelegateClaim),
     ValidatorDefinition(pb_stake::ValidatorDefinition),
     IBCAction(pb_ibc::IbcAction),
 }
@@ -57,6 +60,12 @@ impl From<Undelegate> for ActionPlan {
     }
 }
 
+impl From<UndelegateClaim> for ActionPlan {
+    fn from(inner: UndelegateClaim) -> ActionPlan {
+        ActionPlan::UndelegateClaim(inner)
+    }
+}
+
 impl From<pb_stake::ValidatorDefinition> for ActionPlan {
     fn from(inner: pb_stake::ValidatorDefinition) -> ActionPlan {
         ActionPlan::ValidatorDefinition(inner)
@@ -86,6 +95,9 @@ impl From<ActionPlan> for pb_t::ActionPlan {
             ActionPlan::Undelegate(inner) => pb_t::ActionPlan {
                 action: Some(pb_t::action_plan::Action::Undelegate(inner.into())),
             },
+            ActionPlan::UndelegateClaim(inner) => pb_t::ActionPlan {
+                action: Some(pb_t::action_plan::Action::UndelegateClaim(inner.into())),
+            },
             ActionPlan::ValidatorDefinition(inner) => pb_t::ActionPlan {
                 action: Some(pb_t::action_plan::Action::ValidatorDefinition(inner)),
             },
@@ -113,6 +125,9 @@ impl TryFrom<pb_t::ActionPlan> for ActionPlan {
             pb_t::action_plan::Action::Undelegate(inner) => {
                 Ok(ActionPlan::Undelegate(inner.try_into()?))
             }
+            pb_t::action_plan::Action::UndelegateClaim(inner) => {
+                Ok(ActionPlan::UndelegateClaim(inner.try_into()?))
+            }
             pb_t::action_plan::Action::ValidatorDefinition(inner) => {
                 Ok(ActionPlan::ValidatorDefinition(inner))
             }
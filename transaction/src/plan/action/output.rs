@@ -1,10 +1,10 @@
 use ark_ff::UniformRand;
 use penumbra_crypto::{
     ka,
-    keys::{IncomingViewingKey, OutgoingViewingKey},
+    keys::{IncomingViewingKey, OutgoingViewingKey, SpendKey},
     memo::MemoPlaintext,
-    proofs::transparent::OutputProof,
-    Address, FieldExt, Fq, Fr, Note, NotePayload, Value,
+    proofs::{groth16, transparent::OutputProof},
+    Address, FieldExt, Fq, Fr, Note, NotePayload, Value, STAKING_TOKEN_ASSET_ID,
 };
 use penumbra_proto::{transaction as pb, Protobuf};
 use rand_core::{CryptoRng, RngCore};
@@ -46,13 +46,53 @@ impl OutputPlan {
         }
     }
 
+    /// Create a new [`OutputPlan`] for a dummy output, used to pad a
+    /// transaction.
+    ///
+    /// A dummy output sends a zero-value note to a random address, so nobody
+    /// will ever scan it.
+    pub fn dummy<R: RngCore + CryptoRng>(rng: &mut R) -> OutputPlan {
+        let (dest_address, _dtk) = SpendKey::generate(rng)
+            .incoming_viewing_key()
+            .payment_address(0u64.into());
+        OutputPlan::new(
+            rng,
+            Value {
+                amount: 0,
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            },
+            dest_address,
+            MemoPlaintext::default(),
+        )
+    }
+
     /// Convenience method to construct the [`Output`] described by this
     /// [`OutputPlan`].
-    pub fn output(&self, ovk: &OutgoingViewingKey) -> Output {
-        Output {
-            body: self.output_body(ovk),
-            proof: self.output_proof(),
-        }
+    ///
+    /// If Groth16 proving keys have been [installed](groth16::install_proving_keys),
+    /// the output carries a Groth16 proof; otherwise it carries a transparent proof.
+    pub fn output<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+        ovk: &OutgoingViewingKey,
+    ) -> anyhow::Result<Output> {
+        let body = self.output_body(ovk);
+        let transparent_proof = self.output_proof();
+
+        let proof = match groth16::proving_keys() {
+            Some(keys) => {
+                let circuit = groth16::OutputCircuit::new(
+                    body.value_commitment,
+                    body.note_payload.note_commitment,
+                    body.note_payload.ephemeral_key,
+                    transparent_proof,
+                );
+                groth16::OutputProof::prove(rng, &keys.output, circuit)?.into()
+            }
+            None => transparent_proof.into(),
+        };
+
+        Ok(Output { body, proof })
     }
 
     pub fn output_note(&self) -> Note {
@@ -1,6 +1,10 @@
 use ark_ff::UniformRand;
 use decaf377_rdsa::{Signature, SpendAuth};
-use penumbra_crypto::{proofs::transparent::SpendProof, FieldExt, Fr, FullViewingKey, Note};
+use penumbra_crypto::{
+    keys::SpendKey,
+    proofs::{groth16, transparent::SpendProof},
+    FieldExt, Fr, FullViewingKey, Note, Value, STAKING_TOKEN_ASSET_ID,
+};
 use penumbra_proto::{transaction as pb, Protobuf};
 use penumbra_tct as tct;
 use rand_core::{CryptoRng, RngCore};
@@ -16,6 +20,8 @@ pub struct SpendPlan {
     pub position: tct::Position,
     pub randomizer: Fr,
     pub value_blinding: Fr,
+    /// For a dummy spend, the one-off key that owns the dummy note.
+    pub dummy_key: Option<SpendKey>,
 }
 
 impl SpendPlan {
@@ -30,25 +36,94 @@ impl SpendPlan {
             position,
             randomizer: Fr::rand(rng),
             value_blinding: Fr::rand(rng),
+            dummy_key: None,
         }
     }
 
+    /// Create a new [`SpendPlan`] for a dummy spend, used to pad a transaction.
+    ///
+    /// A dummy spend spends a zero-value note owned by a fresh, random key, so
+    /// its nullifier can't be linked to anything.  Zero-value notes don't need
+    /// to be in the note commitment tree, so the dummy note never is, and the
+    /// spend is authorized with the random key rather than the user's.
+    pub fn dummy<R: CryptoRng + RngCore>(rng: &mut R) -> SpendPlan {
+        let dummy_key = SpendKey::generate(rng);
+        let (address, _dtk) = dummy_key
+            .incoming_viewing_key()
+            .payment_address(0u64.into());
+        let note = Note::generate(
+            rng,
+            &address,
+            Value {
+                amount: 0,
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            },
+        );
+        SpendPlan {
+            dummy_key: Some(dummy_key),
+            ..SpendPlan::new(rng, note, 0u64.into())
+        }
+    }
+
+    /// Whether this is a [dummy](Self::dummy) spend.
+    pub fn is_dummy(&self) -> bool {
+        self.dummy_key.is_some()
+    }
+
+    /// The authentication path for a [dummy](Self::dummy) spend's note.
+    ///
+    /// The dummy note isn't in the note commitment tree, so this is a proof of
+    /// its inclusion in a throwaway tree instead.  The spend proof doesn't check
+    /// it against the anchor, because the note has zero value.
+    pub fn dummy_auth_path(&self) -> tct::Proof {
+        let commitment = self.note.commit();
+        let mut tree = tct::Tree::new();
+        tree.insert(tct::Witness::Keep, commitment)
+            .expect("can insert into an empty tree");
+        tree.witness(commitment)
+            .expect("commitment was just inserted")
+    }
+
     /// Convenience method to construct the [`Spend`] described by this [`SpendPlan`].
-    pub fn spend(
+    ///
+    /// If Groth16 proving keys have been [installed](groth16::install_proving_keys),
+    /// the spend carries a Groth16 proof against the given `anchor`; otherwise it
+    /// carries a transparent proof.
+    pub fn spend<R: CryptoRng + RngCore>(
         &self,
+        rng: &mut R,
         fvk: &FullViewingKey,
         auth_sig: Signature<SpendAuth>,
         auth_path: tct::Proof,
-    ) -> Spend {
-        Spend {
-            body: self.spend_body(fvk),
+        anchor: tct::Root,
+    ) -> anyhow::Result<Spend> {
+        let body = self.spend_body(fvk);
+        let transparent_proof = self.spend_proof(fvk, auth_path);
+
+        let proof = match groth16::proving_keys() {
+            Some(keys) => {
+                let circuit = groth16::SpendCircuit::new(
+                    anchor,
+                    body.value_commitment,
+                    body.nullifier,
+                    body.rk,
+                    transparent_proof,
+                );
+                groth16::SpendProof::prove(rng, &keys.spend, circuit)?.into()
+            }
+            None => transparent_proof.into(),
+        };
+
+        Ok(Spend {
+            body,
             auth_sig,
-            proof: self.spend_proof(fvk, auth_path),
-        }
+            proof,
+        })
     }
 
     /// Construct the [`spend::Body`] described by this [`SpendPlan`].
     pub fn spend_body(&self, fvk: &FullViewingKey) -> spend::Body {
+        let fvk = self.full_viewing_key(fvk);
         spend::Body {
             value_commitment: self.note.value().commit(self.value_blinding),
             nullifier: fvk.derive_nullifier(self.position, &self.note.commit()),
@@ -62,6 +137,7 @@ impl SpendPlan {
         fvk: &FullViewingKey,
         note_commitment_proof: tct::Proof,
     ) -> SpendProof {
+        let fvk = self.full_viewing_key(fvk);
         SpendProof {
             note_commitment_proof,
             g_d: self.note.diversified_generator(),
@@ -74,6 +150,15 @@ impl SpendPlan {
             nk: *fvk.nullifier_key(),
         }
     }
+
+    /// The full viewing key of the spent note's owner: the dummy key for a
+    /// dummy spend, and otherwise the given `fvk`.
+    fn full_viewing_key<'a>(&'a self, fvk: &'a FullViewingKey) -> &'a FullViewingKey {
+        match &self.dummy_key {
+            Some(dummy_key) => dummy_key.full_viewing_key(),
+            None => fvk,
+        }
+    }
 }
 
 impl Protobuf<pb::SpendPlan> for SpendPlan {}
@@ -85,6 +170,7 @@ impl From<SpendPlan> for pb::SpendPlan {
             position: u64::from(msg.position),
             randomizer: msg.randomizer.to_bytes().to_vec().into(),
             value_blinding: msg.value_blinding.to_bytes().to_vec().into(),
+            dummy_key: msg.dummy_key.map(Into::into),
         }
     }
 }
@@ -100,6 +186,7 @@ impl TryFrom<pb::SpendPlan> for SpendPlan {
             position: msg.position.into(),
             randomizer: Fr::from_bytes(msg.randomizer.as_ref().try_into()?)?,
             value_blinding: Fr::from_bytes(msg.value_blinding.as_ref().try_into()?)?,
+            dummy_key: msg.dummy_key.map(TryInto::try_into).transpose()?,
         })
     }
 }
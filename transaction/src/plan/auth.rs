@@ -15,6 +15,8 @@ impl TransactionPlan {
         let auth_hash = self.auth_hash(sk.full_viewing_key());
         let mut spend_auths = Vec::new();
         for spend_plan in self.spend_plans() {
+            // Dummy spends are authorized by their own one-off keys.
+            let sk = spend_plan.dummy_key.as_ref().unwrap_or(sk);
             let rsk = sk.spend_auth_key().randomize(&spend_plan.randomizer);
             let auth_sig = rsk.sign(&mut rng, auth_hash.as_ref());
             spend_auths.push(auth_sig);
@@ -21,8 +21,10 @@ impl TransactionPlan {
         auth_data: AuthorizationData,
         witness_data: WitnessData,
     ) -> Result<Transaction> {
-        // Do some basic input sanity-checking.
+        // Do some basic input sanity-checking.  Dummy spends aren't of notes
+        // in the note commitment tree, so they don't have witness data.
         let spend_count = self.spend_plans().count();
+        let real_spend_count = self.spend_plans().filter(|spend| !spend.is_dummy()).count();
         if auth_data.spend_auths.len() != spend_count {
             return Err(anyhow::anyhow!(
                 "expected {} spend auths but got {}",
@@ -30,10 +32,10 @@ impl TransactionPlan {
                 auth_data.spend_auths.len()
             ));
         }
-        if witness_data.note_commitment_proofs.len() != spend_count {
+        if witness_data.note_commitment_proofs.len() != real_spend_count {
             return Err(anyhow::anyhow!(
                 "expected {} auth paths but got {}",
-                spend_count,
+                real_spend_count,
                 witness_data.note_commitment_proofs.len()
             ));
         }
@@ -47,21 +49,29 @@ impl TransactionPlan {
         // transaction we'll build here without actually building it.
 
         // Build the transaction's spends.
-        for ((spend_plan, auth_sig), auth_path) in self
-            .spend_plans()
-            .zip(auth_data.spend_auths.into_iter())
-            .zip(witness_data.note_commitment_proofs.into_iter())
-        {
+        let mut auth_paths = witness_data.note_commitment_proofs.into_iter();
+        for (spend_plan, auth_sig) in self.spend_plans().zip(auth_data.spend_auths.into_iter()) {
+            let auth_path = if spend_plan.is_dummy() {
+                spend_plan.dummy_auth_path()
+            } else {
+                auth_paths.next().expect("checked the number of auth paths")
+            };
             // Spends add to the transaction's value balance.
             synthetic_blinding_factor += spend_plan.value_blinding;
-            actions.push(Action::Spend(spend_plan.spend(fvk, auth_sig, auth_path)));
+            actions.push(Action::Spend(spend_plan.spend(
+                rng,
+                fvk,
+                auth_sig,
+                auth_path,
+                witness_data.anchor,
+            )?));
         }
 
         // Build the transaction's outputs.
         for output_plan in self.output_plans() {
             // Outputs subtract from the transaction's value balance.
             synthetic_blinding_factor -= output_plan.value_blinding;
-            actions.push(Action::Output(output_plan.output(fvk.outgoing())));
+            actions.push(Action::Output(output_plan.output(rng, fvk.outgoing())?));
         }
 
         // We don't have anything more to build, but iterate through the rest of
@@ -78,6 +88,9 @@ impl TransactionPlan {
         for undelegation in self.undelegations().cloned() {
             actions.push(Action::Undelegate(undelegation))
         }
+        for claim in self.undelegate_claims().cloned() {
+            actions.push(Action::UndelegateClaim(claim))
+        }
         for vd in self.validator_definitions().cloned() {
             actions.push(Action::ValidatorDefinition(vd))
         }
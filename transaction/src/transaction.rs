@@ -11,7 +11,7 @@ use penumbra_proto::{ibc as pb_ibc, stake as pbs, transaction as pbt, Message, P
 use penumbra_tct as tct;
 
 use crate::{
-    action::{Delegate, Undelegate},
+    action::{Delegate, Output, Spend, Undelegate, UndelegateClaim},
     Action,
 };
 
@@ -38,6 +38,26 @@ impl Transaction {
         self.transaction_body.actions.iter()
     }
 
+    pub fn outputs(&self) -> impl Iterator<Item = &Output> {
+        self.actions().filter_map(|action| {
+            if let Action::Output(output) = action {
+                Some(output)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn spends(&self) -> impl Iterator<Item = &Spend> {
+        self.actions().filter_map(|action| {
+            if let Action::Spend(spend) = action {
+                Some(spend)
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn delegations(&self) -> impl Iterator<Item = &Delegate> {
         self.actions().filter_map(|action| {
             if let Action::Delegate(d) = action {
@@ -58,6 +78,16 @@ impl Transaction {
         })
     }
 
+    pub fn undelegate_claims(&self) -> impl Iterator<Item = &UndelegateClaim> {
+        self.actions().filter_map(|action| {
+            if let Action::UndelegateClaim(c) = action {
+                Some(c)
+            } else {
+                None
+            }
+        })
+    }
+
     pub fn ibc_actions(&self) -> impl Iterator<Item = &pb_ibc::IbcAction> {
         self.actions().filter_map(|action| {
             if let Action::IBCAction(ibc_action) = action {
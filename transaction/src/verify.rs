@@ -0,0 +1,87 @@
+use anyhow::{anyhow, Result};
+use penumbra_crypto::rdsa::{batch, VerificationKeyBytes};
+use rand_core::OsRng;
+
+use crate::{Action, Transaction};
+
+impl Transaction {
+    /// Queue all of this transaction's signatures (the spend authorization
+    /// signatures and the binding signature) into the given batch verifier.
+    pub fn queue_signatures(&self, verifier: &mut batch::Verifier) {
+        let auth_hash = self.auth_hash();
+
+        for action in self.actions() {
+            if let Action::Spend(spend) = action {
+                verifier.queue((
+                    VerificationKeyBytes::from(spend.body.rk),
+                    spend.auth_sig,
+                    &auth_hash,
+                ));
+            }
+        }
+
+        verifier.queue((
+            VerificationKeyBytes::from(self.binding_verification_key()),
+            self.binding_sig,
+            &auth_hash,
+        ));
+    }
+
+    /// Verify all of this transaction's signatures in a single batch.
+    ///
+    /// If the batch fails to verify, the signatures are checked one by one to
+    /// identify the failing action.
+    pub fn verify_signatures(&self) -> Result<()> {
+        let mut verifier = batch::Verifier::new();
+        self.queue_signatures(&mut verifier);
+
+        if verifier.verify(OsRng).is_ok() {
+            return Ok(());
+        }
+
+        self.verify_signatures_individually()
+    }
+
+    /// Verify the signatures of all of the given transactions in a single
+    /// batch, returning the result for each transaction, in order.
+    ///
+    /// If the batch fails to verify, each transaction is checked individually,
+    /// so that one invalid transaction does not cause the others to be rejected.
+    pub fn verify_signatures_batch(transactions: &[&Transaction]) -> Vec<Result<()>> {
+        let mut verifier = batch::Verifier::new();
+        for transaction in transactions {
+            transaction.queue_signatures(&mut verifier);
+        }
+
+        if verifier.verify(OsRng).is_ok() {
+            return transactions.iter().map(|_| Ok(())).collect();
+        }
+
+        transactions
+            .iter()
+            .map(|transaction| transaction.verify_signatures_individually())
+            .collect()
+    }
+
+    fn verify_signatures_individually(&self) -> Result<()> {
+        let auth_hash = self.auth_hash();
+
+        for (i, action) in self.actions().enumerate() {
+            if let Action::Spend(spend) = action {
+                spend
+                    .body
+                    .rk
+                    .verify(auth_hash.as_ref(), &spend.auth_sig)
+                    .map_err(|_| {
+                        anyhow!("spend auth signature for action {} failed to verify", i)
+                    })?;
+            }
+        }
+
+        self.binding_verification_key()
+            .verify(auth_hash.as_ref(), self.binding_sig())
+            .map_err(|_| anyhow!("binding signature failed to verify"))?;
+
+        Ok(())
+    }
+}
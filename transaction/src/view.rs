@@ -0,0 +1,344 @@
+//! Views of transactions from the perspective of their participants.
+
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use penumbra_crypto::{memo::MemoPlaintext, note, FullViewingKey, Note, Nullifier, PayloadKey};
+use penumbra_proto::{transaction as pb, Protobuf};
+
+use crate::{
+    action::{Output, Spend},
+    Action, Fee, Transaction,
+};
+
+/// The data needed to view a [`Transaction`] from the perspective of one of
+/// its participants, beyond what their [`FullViewingKey`] can recover by
+/// itself.
+///
+/// Spent notes can't be recovered from a transaction alone, since spends only
+/// reveal nullifiers, so they have to be supplied by whoever scanned the chain
+/// for the participant's notes.  Payload keys allow decrypting outputs that
+/// weren't sent to or from the viewing key, e.g. ones disclosed by a third party.
+#[derive(Clone, Debug, Default)]
+pub struct TransactionPerspective {
+    /// Payload keys for outputs, identified by their note commitments.
+    pub payload_keys: BTreeMap<note::Commitment, PayloadKey>,
+    /// Notes spent by the participant, identified by their nullifiers.
+    pub spent_notes: BTreeMap<Nullifier, Note>,
+}
+
+impl TransactionPerspective {
+    /// Compute the payload keys for all of the outputs of `transaction` that
+    /// were sent either to or from `fvk`.
+    pub fn from_fvk(transaction: &Transaction, fvk: &FullViewingKey) -> Self {
+        let payload_keys = transaction
+            .actions()
+            .filter_map(|action| match action {
+                Action::Output(output) => output_payload_key(output, fvk)
+                    .map(|key| (output.body.note_payload.note_commitment, key)),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            payload_keys,
+            spent_notes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Recover the payload key for an output sent to or from `fvk`, if any.
+fn output_payload_key(output: &Output, fvk: &FullViewingKey) -> Option<PayloadKey> {
+    let payload = &output.body.note_payload;
+    let incoming = PayloadKey::from_incoming(fvk.incoming(), &payload.ephemeral_key).ok();
+    let outgoing = PayloadKey::from_outgoing(
+        fvk.outgoing(),
+        &output.body.ovk_wrapped_key,
+        output.body.value_commitment,
+        payload.note_commitment,
+        &payload.ephemeral_key,
+    )
+    .ok();
+
+    incoming
+        .into_iter()
+        .chain(outgoing)
+        .find(|key| decrypt_output(output, key).is_ok())
+}
+
+/// Decrypt an output's note and memo with `payload_key`, checking that the note
+/// matches the output's note commitment.
+fn decrypt_output(
+    output: &Output,
+    payload_key: &PayloadKey,
+) -> anyhow::Result<(Note, MemoPlaintext)> {
+    let payload = &output.body.note_payload;
+    let note = payload_key.decrypt_note(&payload.encrypted_note)?;
+    if note.commit() != payload.note_commitment {
+        return Err(anyhow!("decrypted note does not match note commitment"));
+    }
+    let memo = payload_key.decrypt_memo(&output.body.encrypted_memo)?;
+
+    Ok((note, memo))
+}
+
+/// A [`Transaction`], with the parts of it visible to one of its participants
+/// decrypted.
+#[derive(Clone, Debug)]
+pub struct TransactionView {
+    /// The transaction's hash.
+    pub id: [u8; 32],
+    pub actions: Vec<ActionView>,
+    pub expiry_height: u64,
+    pub chain_id: String,
+    pub fee: Fee,
+}
+
+/// An [`Action`], as seen by a participant in its transaction.
+#[derive(Clone, Debug)]
+pub enum ActionView {
+    Spend(SpendView),
+    Output(OutputView),
+    /// Any other action, all of whose contents are public.
+    Public(Action),
+}
+
+/// A [`Spend`], along with the spent note, if it was ours.
+#[derive(Clone, Debug)]
+pub enum SpendView {
+    Visible { spend: Spend, note: Note },
+    Opaque { spend: Spend },
+}
+
+/// An [`Output`], along with its decrypted contents, if we could decrypt it.
+#[derive(Clone, Debug)]
+pub enum OutputView {
+    Visible {
+        output: Output,
+        note: Note,
+        memo: MemoPlaintext,
+        payload_key: PayloadKey,
+    },
+    Opaque {
+        output: Output,
+    },
+}
+
+impl TransactionView {
+    /// View `transaction` from the perspective of `fvk`.
+    ///
+    /// Outputs are decrypted with the payload keys in `perspective` if it has
+    /// them, and otherwise with `fvk`; spends are identified using the spent
+    /// notes in `perspective`.
+    pub fn new(
+        transaction: Transaction,
+        fvk: &FullViewingKey,
+        perspective: &TransactionPerspective,
+    ) -> Self {
+        let id = transaction.id();
+        let body = transaction.transaction_body;
+
+        let actions = body
+            .actions
+            .into_iter()
+            .map(|action| match action {
+                Action::Spend(spend) => {
+                    ActionView::Spend(match perspective.spent_notes.get(&spend.body.nullifier) {
+                        Some(note) => SpendView::Visible {
+                            spend,
+                            note: note.clone(),
+                        },
+                        None => SpendView::Opaque { spend },
+                    })
+                }
+                Action::Output(output) => {
+                    let payload_key = perspective
+                        .payload_keys
+                        .get(&output.body.note_payload.note_commitment)
+                        .copied()
+                        .or_else(|| output_payload_key(&output, fvk));
+                    let decrypted = payload_key.and_then(|payload_key| {
+                        decrypt_output(&output, &payload_key)
+                            .ok()
+                            .map(|(note, memo)| (note, memo, payload_key))
+                    });
+
+                    ActionView::Output(match decrypted {
+                        Some((note, memo, payload_key)) => OutputView::Visible {
+                            output,
+                            note,
+                            memo,
+                            payload_key,
+                        },
+                        None => OutputView::Opaque { output },
+                    })
+                }
+                action => ActionView::Public(action),
+            })
+            .collect();
+
+        Self {
+            id,
+            actions,
+            expiry_height: body.expiry_height,
+            chain_id: body.chain_id,
+            fee: body.fee,
+        }
+    }
+}
+
+impl Protobuf<pb::TransactionPerspective> for TransactionPerspective {}
+
+impl From<TransactionPerspective> for pb::TransactionPerspective {
+    fn from(msg: TransactionPerspective) -> Self {
+        Self {
+            payload_keys: msg
+                .payload_keys
+                .into_iter()
+                .map(|(commitment, payload_key)| pb::PayloadKeyWithCommitment {
+                    payload_key: Some(payload_key.into()),
+                    commitment: Some(commitment.into()),
+                })
+                .collect(),
+            spent_notes: msg
+                .spent_notes
+                .into_iter()
+                .map(|(nullifier, note)| pb::NullifierWithNote {
+                    nullifier: Some(nullifier.into()),
+                    note: Some(note.into()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::TransactionPerspective> for TransactionPerspective {
+    type Error = anyhow::Error;
+
+    fn try_from(msg: pb::TransactionPerspective) -> Result<Self, Self::Error> {
+        Ok(Self {
+            payload_keys: msg
+                .payload_keys
+                .into_iter()
+                .map(|entry| {
+                    Ok((
+                        entry
+                            .commitment
+                            .ok_or_else(|| anyhow!("missing note commitment"))?
+                            .try_into()?,
+                        entry
+                            .payload_key
+                            .ok_or_else(|| anyhow!("missing payload key"))?
+                            .try_into()?,
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            spent_notes: msg
+                .spent_notes
+                .into_iter()
+                .map(|entry| {
+                    Ok((
+                        entry
+                            .nullifier
+                            .ok_or_else(|| anyhow!("missing nullifier"))?
+                            .try_into()?,
+                        entry
+                            .note
+                            .ok_or_else(|| anyhow!("missing note"))?
+                            .try_into()?,
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use penumbra_crypto::{
+        keys::{SeedPhrase, SpendKey},
+        Value, STAKING_TOKEN_ASSET_ID,
+    };
+    use penumbra_tct as tct;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::{
+        plan::{OutputPlan, SpendPlan, TransactionPlan},
+        WitnessData,
+    };
+
+    #[test]
+    fn sender_sees_spends_outputs_and_memos() {
+        let sk = SpendKey::from_seed_phrase(SeedPhrase::generate(&mut OsRng), 0);
+        let fvk = sk.full_viewing_key();
+        let (addr, _dtk) = fvk.incoming().payment_address(0u64.into());
+        let other_sk = SpendKey::from_seed_phrase(SeedPhrase::generate(&mut OsRng), 0);
+        let (other_addr, _dtk) = other_sk
+            .full_viewing_key()
+            .incoming()
+            .payment_address(0u64.into());
+
+        let value = Value {
+            amount: 10000,
+            asset_id: *STAKING_TOKEN_ASSET_ID,
+        };
+        let note = Note::generate(&mut OsRng, &addr, value);
+        let mut nct = tct::Tree::new();
+        nct.insert(tct::Witness::Keep, note.commit()).unwrap();
+
+        let memo = MemoPlaintext::try_from(&b"hello"[..]).unwrap();
+        let plan = TransactionPlan {
+            chain_id: "penumbra-test".to_string(),
+            actions: vec![
+                SpendPlan::new(&mut OsRng, note.clone(), 0u64.into()).into(),
+                OutputPlan::new(&mut OsRng, value, other_addr, memo.clone()).into(),
+            ],
+            ..Default::default()
+        };
+        let auth_data = plan.authorize(OsRng, &sk);
+        let witness_data = WitnessData {
+            anchor: nct.root(),
+            note_commitment_proofs: vec![nct.witness(note.commit()).unwrap()],
+        };
+        let transaction = plan
+            .build(&mut OsRng, fvk, auth_data, witness_data)
+            .unwrap();
+
+        let mut perspective = TransactionPerspective::from_fvk(&transaction, fvk);
+        assert_eq!(perspective.payload_keys.len(), 1);
+        perspective.spent_notes.insert(
+            fvk.derive_nullifier(0u64.into(), &note.commit()),
+            note.clone(),
+        );
+
+        // The recipient can see the output, but not the spend.
+        let recipient_view = TransactionView::new(
+            transaction.clone(),
+            other_sk.full_viewing_key(),
+            &Default::default(),
+        );
+        // The sender sees everything.
+        let sender_view = TransactionView::new(transaction, fvk, &perspective);
+
+        for action in sender_view.actions {
+            match action {
+                ActionView::Spend(SpendView::Visible { note: spent, .. }) => {
+                    assert_eq!(spent, note)
+                }
+                ActionView::Output(OutputView::Visible { note, memo: m, .. }) => {
+                    assert_eq!(note.value(), value);
+                    assert_eq!(m, memo);
+                }
+                action => panic!("unexpected action view {:?}", action),
+            }
+        }
+        for action in recipient_view.actions {
+            match action {
+                ActionView::Spend(SpendView::Opaque { .. }) => {}
+                ActionView::Output(OutputView::Visible { memo: m, .. }) => assert_eq!(m, memo),
+                action => panic!("unexpected action view {:?}", action),
+            }
+        }
+    }
+}
@@ -11,13 +11,23 @@ impl Protobuf<pb::WitnessData> for WitnessData {}
 
 impl From<WitnessData> for pb::WitnessData {
     fn from(msg: WitnessData) -> Self {
-        Self {
-            anchor: Some(msg.anchor.into()),
-            note_commitment_proofs: msg
-                .note_commitment_proofs
-                .into_iter()
-                .map(Into::into)
-                .collect(),
+        // Deduplicate the auth paths into a multi-proof, unless they don't share a root (in
+        // which case the witness data is invalid, but should still round-trip)
+        match tct::MultiProof::from_proofs(msg.note_commitment_proofs.iter().cloned()) {
+            Ok(multi_proof) => Self {
+                anchor: Some(msg.anchor.into()),
+                note_commitment_proofs: Vec::new(),
+                note_commitment_multi_proof: Some(multi_proof.into()),
+            },
+            Err(_) => Self {
+                anchor: Some(msg.anchor.into()),
+                note_commitment_proofs: msg
+                    .note_commitment_proofs
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                note_commitment_multi_proof: None,
+            },
         }
     }
 }
@@ -26,16 +36,22 @@ impl TryFrom<pb::WitnessData> for WitnessData {
     type Error = anyhow::Error;
 
     fn try_from(msg: pb::WitnessData) -> Result<Self, Self::Error> {
+        let mut note_commitment_proofs: Vec<tct::Proof> = msg
+            .note_commitment_proofs
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()?;
+        if let Some(multi_proof) = msg.note_commitment_multi_proof {
+            let multi_proof = tct::MultiProof::try_from(multi_proof)?;
+            note_commitment_proofs.extend(multi_proof.proofs()?);
+        }
+
         Ok(Self {
             anchor: msg
                 .anchor
                 .ok_or_else(|| anyhow::anyhow!("missing anchor"))?
                 .try_into()?,
-            note_commitment_proofs: msg
-                .note_commitment_proofs
-                .into_iter()
-                .map(TryInto::try_into)
-                .collect::<Result<_, _>>()?,
+            note_commitment_proofs,
         })
     }
 }
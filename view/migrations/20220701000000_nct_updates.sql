@@ -0,0 +1,11 @@
+-- The note commitment tree is now stored as a log of the updates made to it,
+-- rather than as a single serialized blob.
+DROP TABLE note_commitment_tree;
+CREATE TABLE nct_updates (
+    id    INTEGER PRIMARY KEY NOT NULL,
+    bytes BLOB NOT NULL
+);
+
+-- The existing tree can't be converted into updates, so rescan from genesis.
+DELETE FROM notes;
+UPDATE sync_height SET height = -1;
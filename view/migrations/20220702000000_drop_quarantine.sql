@@ -0,0 +1,7 @@
+-- Undelegations now produce unbonding tokens rather than quarantined notes,
+-- so the quarantine tables are no longer used.
+DROP INDEX identity_key_idx;
+DROP TABLE quarantined_nullifiers;
+
+DROP INDEX quarantined_notes_idx;
+DROP TABLE quarantined_notes;
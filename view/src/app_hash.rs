@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Context};
+use jmt::{RootHash, Version};
+
+/// Fetches the app hash committed to by tendermint for the JMT at `version`, or at the latest
+/// version for which there is one if `version` is `None`, returning that version along with its
+/// root hash.
+///
+/// The app hash resulting from a block is only recorded in the header of the *next* block, so the
+/// latest version with a committed root is always one behind the latest block, and the root for
+/// the latest block is not available until the next block has been produced.
+///
+/// The header is fetched from the tendermint RPC at `tendermint_url`, rather than from pd, so the
+/// root can be used to check proofs served by pd using
+/// [`verify_key_value`](penumbra_storage::verify_key_value). The signatures on the header are not
+/// checked, so this is only as trustworthy as the tendermint node it is fetched from.
+pub async fn committed_root(
+    tendermint_url: &str,
+    version: Option<Version>,
+) -> anyhow::Result<(Version, RootHash)> {
+    let client = reqwest::Client::new();
+
+    let tendermint_url = tendermint_url.trim_end_matches('/');
+    let url = match version {
+        Some(version) => format!("{}/commit?height={}", tendermint_url, version + 1),
+        None => format!("{}/commit", tendermint_url),
+    };
+    let rsp: serde_json::Value = client.get(url).send().await?.json().await?;
+
+    tracing::debug!("{}", rsp);
+
+    if let Some(error) = rsp.get("error") {
+        return Err(anyhow!(
+            "could not fetch the header committing to version {:?}: {}",
+            version,
+            error
+        ));
+    }
+
+    let header = rsp
+        .get("result")
+        .and_then(|r| r.get("signed_header"))
+        .and_then(|h| h.get("header"))
+        .ok_or_else(|| anyhow!("could not parse header in JSON response"))?;
+
+    let height: u64 = header
+        .get("height")
+        .and_then(|h| h.as_str())
+        .ok_or_else(|| anyhow!("could not parse height in JSON response"))?
+        .parse()?;
+
+    let app_hash = header
+        .get("app_hash")
+        .and_then(|h| h.as_str())
+        .ok_or_else(|| anyhow!("could not parse app_hash in JSON response"))?;
+    let app_hash: [u8; 32] = hex::decode(app_hash)
+        .context("app hash is not hex")?
+        .try_into()
+        .map_err(|_| anyhow!("app hash must be 32 bytes"))?;
+
+    let committed_version = height
+        .checked_sub(1)
+        .ok_or_else(|| anyhow!("the genesis header does not commit to any version"))?;
+
+    Ok((committed_version, RootHash(app_hash)))
+}
@@ -3,12 +3,14 @@
 use anyhow::{Context, Result};
 use camino::Utf8PathBuf;
 use clap::{Parser, Subcommand};
+use metrics_exporter_prometheus::PrometheusBuilder;
 use penumbra_crypto::FullViewingKey;
 use penumbra_proto::client::oblivious::oblivious_query_client::ObliviousQueryClient;
 use penumbra_proto::client::oblivious::ChainParamsRequest;
 use penumbra_proto::view::view_protocol_server::ViewProtocolServer;
 use penumbra_view::ViewService;
 use std::env;
+use std::net::SocketAddr;
 use std::str::FromStr;
 use tonic::transport::Server;
 
@@ -51,6 +53,9 @@ enum Command {
         /// Bind the view gRPC server to this port.
         #[clap(long, default_value = "8081")]
         view_port: u16,
+        /// Bind the metrics endpoint to this port.
+        #[clap(long, default_value = "9001")]
+        metrics_port: u16,
     },
 }
 #[tokio::main]
@@ -81,8 +86,24 @@ async fn main() -> Result<()> {
             .await?;
             Ok(())
         }
-        Command::Start { host, view_port } => {
-            tracing::info!(?opt.sqlite_path, ?host, ?view_port, ?opt.node, ?opt.tendermint_port, ?opt.pd_port, "starting pviewd");
+        Command::Start {
+            host,
+            view_port,
+            metrics_port,
+        } => {
+            tracing::info!(?opt.sqlite_path, ?host, ?view_port, ?metrics_port, ?opt.node, ?opt.tendermint_port, ?opt.pd_port, "starting pviewd");
+
+            // Configure a Prometheus recorder and exporter, which serves
+            // metrics from a task on this runtime.
+            PrometheusBuilder::new()
+                .with_http_listener(
+                    format!("{}:{}", host, metrics_port)
+                        .parse::<SocketAddr>()
+                        .expect("this is a valid address"),
+                )
+                .install()
+                .context("failed to install prometheus exporter")?;
+            penumbra_view::register_metrics();
 
             let storage = penumbra_view::Storage::load(opt.sqlite_path).await?;
 
@@ -7,11 +7,11 @@ use penumbra_crypto::keys::FullViewingKeyHash;
 use penumbra_crypto::{asset, keys::DiversifierIndex, note, Asset};
 use penumbra_proto::view as pb;
 use penumbra_proto::view::view_protocol_client::ViewProtocolClient;
-use penumbra_transaction::WitnessData;
+use penumbra_transaction::{Transaction, TransactionPerspective, WitnessData};
 use tonic::async_trait;
 use tracing::instrument;
 
-use crate::{NoteRecord, QuarantinedNoteRecord, StatusStreamResponse};
+use crate::{DelegationPosition, NoteRecord, StatusStreamResponse};
 
 /// The view protocol is used by a view client, who wants to do some
 /// transaction-related actions, to request data from a view service, which is
@@ -42,12 +42,6 @@ pub trait ViewClient {
     /// Queries for notes.
     async fn notes(&mut self, request: pb::NotesRequest) -> Result<Vec<NoteRecord>>;
 
-    /// Queries for quarantined notes.
-    async fn quarantined_notes(
-        &mut self,
-        request: pb::QuarantinedNotesRequest,
-    ) -> Result<Vec<QuarantinedNoteRecord>>;
-
     /// Queries for a specific note by commitment, returning immediately if it is not found.
     async fn note_by_commitment(
         &mut self,
@@ -75,6 +69,19 @@ pub trait ViewClient {
     /// Queries for all known assets.
     async fn assets(&mut self) -> Result<asset::Cache>;
 
+    /// Queries for the staking position with each validator.
+    async fn delegations(
+        &mut self,
+        fvk_hash: FullViewingKeyHash,
+    ) -> Result<Vec<DelegationPosition>>;
+
+    /// Queries for a transaction, along with the data needed to view it from
+    /// our perspective.
+    async fn transaction_perspective(
+        &mut self,
+        request: pb::TransactionPerspectiveRequest,
+    ) -> Result<(Transaction, TransactionPerspective)>;
+
     /// Return unspent notes, grouped by diversifier index and then by asset id.
     #[instrument(skip(self, fvk_hash))]
     async fn unspent_notes_by_address_and_asset(
@@ -134,62 +141,6 @@ pub trait ViewClient {
 
         Ok(notes_by_asset_and_address)
     }
-
-    /// Return quarantined notes, grouped by diversifier index and then by asset id.
-    #[instrument(skip(self, fvk_hash))]
-    async fn quarantined_notes_by_address_and_asset(
-        &mut self,
-        fvk_hash: FullViewingKeyHash,
-    ) -> Result<BTreeMap<DiversifierIndex, BTreeMap<asset::Id, Vec<QuarantinedNoteRecord>>>> {
-        let notes = self
-            .quarantined_notes(pb::QuarantinedNotesRequest {
-                fvk_hash: Some(fvk_hash.into()),
-            })
-            .await?;
-        tracing::trace!(?notes);
-
-        let mut notes_by_address_and_asset = BTreeMap::new();
-
-        for note_record in notes {
-            notes_by_address_and_asset
-                .entry(note_record.diversifier_index)
-                .or_insert_with(BTreeMap::new)
-                .entry(note_record.note.asset_id())
-                .or_insert_with(Vec::new)
-                .push(note_record);
-        }
-        tracing::trace!(?notes_by_address_and_asset);
-
-        Ok(notes_by_address_and_asset)
-    }
-
-    /// Return quarantined notes, grouped by denom and then by diversifier index.
-    #[instrument(skip(self, fvk_hash))]
-    async fn quarantined_notes_by_asset_and_address(
-        &mut self,
-        fvk_hash: FullViewingKeyHash,
-    ) -> Result<BTreeMap<asset::Id, BTreeMap<DiversifierIndex, Vec<QuarantinedNoteRecord>>>> {
-        let notes = self
-            .quarantined_notes(pb::QuarantinedNotesRequest {
-                fvk_hash: Some(fvk_hash.into()),
-            })
-            .await?;
-        tracing::trace!(?notes);
-
-        let mut notes_by_asset_and_address = BTreeMap::new();
-
-        for note_record in notes {
-            notes_by_asset_and_address
-                .entry(note_record.note.asset_id())
-                .or_insert_with(BTreeMap::new)
-                .entry(note_record.diversifier_index)
-                .or_insert_with(Vec::new)
-                .push(note_record);
-        }
-        tracing::trace!(?notes_by_asset_and_address);
-
-        Ok(notes_by_asset_and_address)
-    }
 }
 
 // We need to tell `async_trait` not to add a `Send` bound to the boxed
@@ -257,20 +208,6 @@ where
         pb_notes.into_iter().map(TryInto::try_into).collect()
     }
 
-    async fn quarantined_notes(
-        &mut self,
-        request: pb::QuarantinedNotesRequest,
-    ) -> Result<Vec<QuarantinedNoteRecord>> {
-        let pb_notes: Vec<_> = self
-            .quarantined_notes(tonic::Request::new(request))
-            .await?
-            .into_inner()
-            .try_collect()
-            .await?;
-
-        pb_notes.into_iter().map(TryInto::try_into).collect()
-    }
-
     async fn note_by_commitment(
         &mut self,
         fvk_hash: FullViewingKeyHash,
@@ -337,4 +274,45 @@ where
 
         Ok(assets.into_iter().map(|asset| asset.denom).collect())
     }
+
+    async fn delegations(
+        &mut self,
+        fvk_hash: FullViewingKeyHash,
+    ) -> Result<Vec<DelegationPosition>> {
+        let pb_positions: Vec<_> = ViewProtocolClient::delegations(
+            self,
+            tonic::Request::new(pb::DelegationsRequest {
+                fvk_hash: Some(fvk_hash.into()),
+            }),
+        )
+        .await?
+        .into_inner()
+        .try_collect()
+        .await?;
+
+        pb_positions.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn transaction_perspective(
+        &mut self,
+        request: pb::TransactionPerspectiveRequest,
+    ) -> Result<(Transaction, TransactionPerspective)> {
+        // We have to manually invoke the method on the type, because it has the
+        // same name as the one we're implementing.
+        let response =
+            ViewProtocolClient::transaction_perspective(self, tonic::Request::new(request))
+                .await?
+                .into_inner();
+
+        let transaction = response
+            .tx
+            .ok_or_else(|| anyhow::anyhow!("missing transaction in response"))?
+            .try_into()?;
+        let perspective = response
+            .perspective
+            .ok_or_else(|| anyhow::anyhow!("missing perspective in response"))?
+            .try_into()?;
+
+        Ok((transaction, perspective))
+    }
 }
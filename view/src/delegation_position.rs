@@ -0,0 +1,90 @@
+use penumbra_component::stake::rate::RateData;
+use penumbra_crypto::{IdentityKey, Value};
+use penumbra_proto::{view as pb, Protobuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Corresponds to the DelegationPosition proto
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "pb::DelegationPosition", into = "pb::DelegationPosition")]
+pub struct DelegationPosition {
+    pub identity_key: IdentityKey,
+    pub delegation_amount: u64,
+    pub unbonded_amount: u64,
+    pub accrued_rewards: i64,
+    pub rate_data: RateData,
+    pub pending_undelegations: Vec<PendingUndelegation>,
+}
+
+/// Corresponds to the PendingUndelegation proto
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "pb::PendingUndelegation", into = "pb::PendingUndelegation")]
+pub struct PendingUndelegation {
+    pub value: Value,
+    pub unbonding_epoch: u64,
+}
+
+impl Protobuf<pb::DelegationPosition> for DelegationPosition {}
+impl From<DelegationPosition> for pb::DelegationPosition {
+    fn from(v: DelegationPosition) -> Self {
+        pb::DelegationPosition {
+            identity_key: Some(v.identity_key.into()),
+            delegation_amount: v.delegation_amount,
+            unbonded_amount: v.unbonded_amount,
+            accrued_rewards: v.accrued_rewards,
+            rate_data: Some(v.rate_data.into()),
+            pending_undelegations: v
+                .pending_undelegations
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<pb::DelegationPosition> for DelegationPosition {
+    type Error = anyhow::Error;
+    fn try_from(v: pb::DelegationPosition) -> Result<Self, Self::Error> {
+        Ok(DelegationPosition {
+            identity_key: v
+                .identity_key
+                .ok_or_else(|| anyhow::anyhow!("missing identity key"))?
+                .try_into()?,
+            delegation_amount: v.delegation_amount,
+            unbonded_amount: v.unbonded_amount,
+            accrued_rewards: v.accrued_rewards,
+            rate_data: v
+                .rate_data
+                .ok_or_else(|| anyhow::anyhow!("missing rate data"))?
+                .try_into()?,
+            pending_undelegations: v
+                .pending_undelegations
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl Protobuf<pb::PendingUndelegation> for PendingUndelegation {}
+impl From<PendingUndelegation> for pb::PendingUndelegation {
+    fn from(v: PendingUndelegation) -> Self {
+        pb::PendingUndelegation {
+            value: Some(v.value.into()),
+            unbonding_epoch: v.unbonding_epoch,
+        }
+    }
+}
+
+impl TryFrom<pb::PendingUndelegation> for PendingUndelegation {
+    type Error = anyhow::Error;
+    fn try_from(v: pb::PendingUndelegation) -> Result<Self, Self::Error> {
+        Ok(PendingUndelegation {
+            value: v
+                .value
+                .ok_or_else(|| anyhow::anyhow!("missing value"))?
+                .try_into()?,
+            unbonding_epoch: v.unbonding_epoch,
+        })
+    }
+}
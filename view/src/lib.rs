@@ -1,10 +1,11 @@
 // Required because of NCT type size
 #![recursion_limit = "256"]
 
+mod app_hash;
 mod client;
+mod delegation_position;
 mod metrics;
 mod note_record;
-mod quarantined_note_record;
 mod service;
 mod status;
 mod storage;
@@ -14,9 +15,10 @@ mod worker;
 use worker::Worker;
 
 pub use crate::metrics::register_metrics;
+pub use app_hash::committed_root;
 pub use client::ViewClient;
+pub use delegation_position::{DelegationPosition, PendingUndelegation};
 pub use note_record::NoteRecord;
-pub use quarantined_note_record::QuarantinedNoteRecord;
 pub use service::ViewService;
 pub use status::StatusStreamResponse;
 pub use storage::Storage;
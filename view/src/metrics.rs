@@ -15,15 +15,25 @@ pub use metrics::*;
 
 /// Registers all metrics used by this crate.
 pub fn register_metrics() {
-    /*
-    // Sample code for reference -- delete when adding the first metric
-    register_counter!(MEMPOOL_CHECKTX_TOTAL);
+    register_gauge!(NCT_SIZE_BYTES);
+    describe_gauge!(
+        NCT_SIZE_BYTES,
+        Unit::Bytes,
+        "The size of the stored log of NCT updates in bytes"
+    );
+    register_counter!(NCT_COMPACTED_BYTES_TOTAL);
+    describe_counter!(
+        NCT_COMPACTED_BYTES_TOTAL,
+        Unit::Bytes,
+        "The total number of bytes removed from the stored log of NCT updates by compaction"
+    );
+    register_counter!(NCT_FORGOTTEN_TOTAL);
     describe_counter!(
-        MEMPOOL_CHECKTX_TOTAL,
-        "The total number of checktx requests made to the mempool"
+        NCT_FORGOTTEN_TOTAL,
+        "The total number of spent note commitments forgotten from the NCT"
     );
-     */
 }
 
-// Sample code for reference -- delete when adding the first metric
-// pub const MEMPOOL_CHECKTX_TOTAL: &str = "penumbra_pd_mempool_checktx_total";
+pub const NCT_SIZE_BYTES: &str = "penumbra_view_nct_size_bytes";
+pub const NCT_COMPACTED_BYTES_TOTAL: &str = "penumbra_view_nct_compacted_bytes_total";
+pub const NCT_FORGOTTEN_TOTAL: &str = "penumbra_view_nct_forgotten_total";
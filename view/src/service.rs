@@ -1,4 +1,5 @@
 use std::{
+    collections::{BTreeMap, BTreeSet},
     pin::Pin,
     sync::{Arc, Mutex},
 };
@@ -7,24 +8,35 @@ use anyhow::anyhow;
 use async_stream::try_stream;
 use camino::Utf8Path;
 use futures::stream::{StreamExt, TryStreamExt};
+use penumbra_chain::{Epoch, TransactionLocation};
+use penumbra_component::stake::rate::RateData;
 use penumbra_crypto::{
     asset,
     keys::{DiversifierIndex, FullViewingKey, FullViewingKeyHash},
+    DelegationToken, IdentityKey, UnbondingToken,
 };
 use penumbra_proto::{
     chain as pbp,
+    client::{
+        explorer::{block_explorer_client::BlockExplorerClient, TransactionsInBlockRequest},
+        oblivious::{oblivious_query_client::ObliviousQueryClient, ValidAnchorRangeRequest},
+        specific::{
+            specific_query_client::SpecificQueryClient, TransactionByHashRequest,
+            UnbondingPenaltyRequest, ValidatorRateHistoryRequest,
+        },
+    },
     crypto::{self as pbc},
     transaction as pbt,
     view::{self as pb, view_protocol_server::ViewProtocol, StatusResponse},
 };
-use penumbra_tct::{Commitment, Proof};
-use penumbra_transaction::WitnessData;
+use penumbra_tct::{Commitment, Root};
+use penumbra_transaction::{Transaction, TransactionPerspective};
 use tokio::sync::{watch, RwLock};
 use tokio_stream::wrappers::WatchStream;
 use tonic::async_trait;
 use tracing::instrument;
 
-use crate::{Storage, Worker};
+use crate::{DelegationPosition, NoteRecord, PendingUndelegation, Storage, Worker};
 
 /// A service that synchronizes private chain state and responds to queries
 /// about it.
@@ -45,6 +57,8 @@ pub struct ViewService {
     note_commitment_tree: Arc<RwLock<penumbra_tct::Tree>>,
     // The address of the pd+tendermint node.
     node: String,
+    // The port to use to speak to pd's gRPC server.
+    pd_port: u16,
     // The port to use to speak to tendermint's RPC server.
     tendermint_port: u16,
     /// Used to watch for changes to the sync height.
@@ -79,7 +93,7 @@ impl ViewService {
         tendermint_port: u16,
     ) -> Result<Self, anyhow::Error> {
         let (worker, nct, error_slot, sync_height_rx) =
-            Worker::new(storage.clone(), node.clone(), pd_port).await?;
+            Worker::new(storage.clone(), node.clone(), pd_port, tendermint_port).await?;
 
         tokio::spawn(worker.run());
 
@@ -93,6 +107,7 @@ impl ViewService {
             sync_height_rx,
             note_commitment_tree: nct,
             node,
+            pd_port,
             tendermint_port,
         })
     }
@@ -137,6 +152,46 @@ impl ViewService {
         Ok(())
     }
 
+    /// Checks that we've synced far enough for the anchor of our NCT to be
+    /// accepted by the chain.
+    ///
+    /// Since our NCT only ever advances, if its current anchor is valid, any
+    /// anchor we read after this check will be too.
+    async fn check_anchor_window(&self) -> Result<(), tonic::Status> {
+        let sync_height = self
+            .storage
+            .last_sync_height()
+            .await
+            .map_err(|e| tonic::Status::unavailable(format!("error getting sync height: {}", e)))?
+            .unwrap_or(0);
+        let chain_id = self
+            .storage
+            .chain_params()
+            .await
+            .map_err(|e| tonic::Status::unavailable(format!("error getting chain params: {}", e)))?
+            .chain_id;
+
+        let mut client =
+            ObliviousQueryClient::connect(format!("http://{}:{}", self.node, self.pd_port))
+                .await
+                .map_err(|e| {
+                    tonic::Status::unavailable(format!("error connecting to pd: {}", e))
+                })?;
+        let range = client
+            .valid_anchor_range(ValidAnchorRangeRequest { chain_id })
+            .await?
+            .into_inner();
+
+        if sync_height < range.start_height {
+            return Err(tonic::Status::failed_precondition(format!(
+                "synced to height {}, but anchors before height {} are too old to use; wait for sync to catch up",
+                sync_height, range.start_height
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Return the latest block height known by the fullnode or its peers, as
     /// well as whether the fullnode is caught up with that height.
     #[instrument(skip(self))]
@@ -218,20 +273,201 @@ impl ViewService {
             catching_up,
         })
     }
+
+    /// Compute the staking position with each validator that has been
+    /// delegated to, or that has pending undelegations.
+    #[instrument(skip(self))]
+    pub async fn delegation_positions(&self) -> Result<Vec<DelegationPosition>, anyhow::Error> {
+        let chain_params = self.storage.chain_params().await?;
+
+        // Delegation and unbonding tokens are just notes, so we identify them by their denom.
+        let assets = self.storage.assets().await?;
+        let delegation_tokens = assets
+            .iter()
+            .filter_map(|asset| DelegationToken::try_from(asset.denom.clone()).ok())
+            .map(|token| (token.id(), token.validator()))
+            .collect::<BTreeMap<_, _>>();
+        let unbonding_tokens = assets
+            .into_iter()
+            .filter_map(|asset| UnbondingToken::try_from(asset.denom).ok())
+            .map(|token| (token.id(), (token.validator(), token.start_epoch_index())))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut delegation_notes = BTreeMap::<IdentityKey, Vec<NoteRecord>>::new();
+        let mut unbonding_notes = BTreeMap::<IdentityKey, Vec<(u64, NoteRecord)>>::new();
+        for record in self.storage.notes(false, None, None, 0).await? {
+            if let Some(identity_key) = delegation_tokens.get(&record.note.asset_id()) {
+                delegation_notes
+                    .entry(identity_key.clone())
+                    .or_default()
+                    .push(record);
+            } else if let Some((identity_key, start_epoch_index)) =
+                unbonding_tokens.get(&record.note.asset_id())
+            {
+                unbonding_notes
+                    .entry(identity_key.clone())
+                    .or_default()
+                    .push((*start_epoch_index, record));
+            }
+        }
+
+        let validators = delegation_notes
+            .keys()
+            .chain(unbonding_notes.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+
+        let mut client =
+            SpecificQueryClient::connect(format!("http://{}:{}", self.node, self.pd_port)).await?;
+
+        let mut positions = Vec::new();
+        for identity_key in validators {
+            let rate_data: RateData = client
+                .next_validator_rate(tonic::Request::new(identity_key.clone().into()))
+                .await?
+                .into_inner()
+                .try_into()?;
+
+            let notes = delegation_notes.remove(&identity_key).unwrap_or_default();
+            let delegation_amount = notes.iter().map(|n| n.note.amount()).sum::<u64>();
+            let unbonded_amount = rate_data.unbonded_amount(delegation_amount);
+
+            // Value each note at the rate in effect when it was received.  A
+            // delegation made during some epoch uses the following epoch's
+            // rate, so look up the rate for the epoch after the note's creation.
+            let mut rates_by_epoch = BTreeMap::<u64, RateData>::new();
+            let mut received_amount = 0u64;
+            for note in &notes {
+                let epoch_index =
+                    Epoch::from_height(note.height_created, chain_params.epoch_duration).index + 1;
+
+                if !rates_by_epoch.contains_key(&epoch_index) {
+                    let historical_rate = client
+                        .validator_rate_history(ValidatorRateHistoryRequest {
+                            chain_id: chain_params.chain_id.clone(),
+                            identity_key: Some(identity_key.clone().into()),
+                            start_epoch_index: epoch_index,
+                            end_epoch_index: epoch_index,
+                        })
+                        .await?
+                        .into_inner()
+                        .try_collect::<Vec<_>>()
+                        .await?
+                        .into_iter()
+                        .map(RateData::try_from)
+                        .collect::<Result<Vec<_>, _>>()?
+                        .into_iter()
+                        .find(|rate| rate.epoch_index == epoch_index)
+                        // If the epoch hasn't started yet, its rate is the next rate.
+                        .unwrap_or_else(|| rate_data.clone());
+                    rates_by_epoch.insert(epoch_index, historical_rate);
+                }
+
+                received_amount += rates_by_epoch[&epoch_index].unbonded_amount(note.note.amount());
+            }
+
+            let mut pending_undelegations = Vec::new();
+            for (start_epoch_index, record) in
+                unbonding_notes.remove(&identity_key).unwrap_or_default()
+            {
+                let unbonding_epoch = client
+                    .unbonding_penalty(UnbondingPenaltyRequest {
+                        chain_id: chain_params.chain_id.clone(),
+                        identity_key: Some(identity_key.clone().into()),
+                        start_epoch_index,
+                    })
+                    .await?
+                    .into_inner()
+                    .claimable_epoch_index;
+
+                pending_undelegations.push(PendingUndelegation {
+                    value: record.note.value(),
+                    unbonding_epoch,
+                });
+            }
+
+            positions.push(DelegationPosition {
+                identity_key,
+                delegation_amount,
+                unbonded_amount,
+                accrued_rewards: unbonded_amount as i64 - received_amount as i64,
+                rate_data,
+                pending_undelegations,
+            });
+        }
+
+        Ok(positions)
+    }
+
+    /// Fetch a committed transaction from pd by its hash.
+    #[instrument(skip(self))]
+    pub async fn transaction_by_hash(
+        &self,
+        tx_hash: Vec<u8>,
+    ) -> Result<Transaction, anyhow::Error> {
+        let chain_id = self.storage.chain_params().await?.chain_id;
+
+        let mut client =
+            SpecificQueryClient::connect(format!("http://{}:{}", self.node, self.pd_port)).await?;
+        let location: TransactionLocation = client
+            .transaction_by_hash(TransactionByHashRequest { chain_id, tx_hash })
+            .await?
+            .into_inner()
+            .try_into()?;
+
+        let mut client =
+            BlockExplorerClient::connect(format!("http://{}:{}", self.node, self.pd_port)).await?;
+        let info = client
+            .transactions_in_block(TransactionsInBlockRequest {
+                height: location.height,
+                offset: location.index,
+                limit: 1,
+            })
+            .await?
+            .into_inner()
+            .transactions
+            .pop()
+            .ok_or_else(|| anyhow!("transaction missing from block {}", location.height))?;
+
+        let transaction = Transaction::try_from(&info.raw[..])?;
+        if transaction.id() != location.id {
+            return Err(anyhow!("pd returned the wrong transaction"));
+        }
+
+        Ok(transaction)
+    }
+
+    /// Compute our perspective on `transaction`: the payload keys for the
+    /// outputs we can decrypt, and the notes of ours it spends.
+    #[instrument(skip(self, transaction))]
+    pub async fn transaction_perspective(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<TransactionPerspective, anyhow::Error> {
+        let fvk = self.storage.full_viewing_key().await?;
+        let mut perspective = TransactionPerspective::from_fvk(transaction, &fvk);
+
+        for nullifier in transaction.spent_nullifiers() {
+            if let Some(record) = self.storage.note_by_nullifier(nullifier).await? {
+                perspective.spent_notes.insert(nullifier, record.note);
+            }
+        }
+
+        Ok(perspective)
+    }
 }
 
 #[async_trait]
 impl ViewProtocol for ViewService {
     type NotesStream =
         Pin<Box<dyn futures::Stream<Item = Result<pb::NoteRecord, tonic::Status>> + Send>>;
-    type QuarantinedNotesStream = Pin<
-        Box<dyn futures::Stream<Item = Result<pb::QuarantinedNoteRecord, tonic::Status>> + Send>,
-    >;
     type AssetsStream =
         Pin<Box<dyn futures::Stream<Item = Result<pbc::Asset, tonic::Status>> + Send>>;
     type StatusStreamStream = Pin<
         Box<dyn futures::Stream<Item = Result<pb::StatusStreamResponse, tonic::Status>> + Send>,
     >;
+    type DelegationsStream =
+        Pin<Box<dyn futures::Stream<Item = Result<pb::DelegationPosition, tonic::Status>> + Send>>;
 
     async fn note_by_commitment(
         &self,
@@ -350,29 +586,27 @@ impl ViewProtocol for ViewService {
         ))
     }
 
-    async fn quarantined_notes(
+    async fn delegations(
         &self,
-        request: tonic::Request<pb::QuarantinedNotesRequest>,
-    ) -> Result<tonic::Response<Self::QuarantinedNotesStream>, tonic::Status> {
+        request: tonic::Request<pb::DelegationsRequest>,
+    ) -> Result<tonic::Response<Self::DelegationsStream>, tonic::Status> {
         self.check_worker().await?;
         self.check_fvk(request.get_ref().fvk_hash.as_ref()).await?;
 
-        let notes = self
-            .storage
-            .quarantined_notes()
-            .await
-            .map_err(|e| tonic::Status::unavailable(format!("database error: {}", e)))?;
+        let positions = self.delegation_positions().await.map_err(|e| {
+            tonic::Status::unavailable(format!("error computing delegations: {}", e))
+        })?;
 
         let stream = try_stream! {
-            for note in notes {
-                yield note.into()
+            for position in positions {
+                yield position.into()
             }
         };
 
         Ok(tonic::Response::new(
             stream
                 .map_err(|e: anyhow::Error| {
-                    tonic::Status::unavailable(format!("database error: {}", e))
+                    tonic::Status::unavailable(format!("error getting delegations: {}", e))
                 })
                 .boxed(),
         ))
@@ -412,13 +646,36 @@ impl ViewProtocol for ViewService {
     ) -> Result<tonic::Response<pbt::WitnessData>, tonic::Status> {
         self.check_worker().await?;
         self.check_fvk(request.get_ref().fvk_hash.as_ref()).await?;
+        self.check_anchor_window().await?;
+
+        // Parse the requested anchor, if any
+        let requested_anchor = request
+            .get_ref()
+            .anchor
+            .clone()
+            .map(Root::try_from)
+            .transpose()
+            .map_err(|_| {
+                tonic::Status::new(tonic::Code::InvalidArgument, "Unable to deserialize anchor")
+            })?;
 
         // Acquire a read lock for the NCT that will live for the entire request,
         // so that all auth paths are relative to the same NCT root.
         let nct = self.note_commitment_tree.read().await;
 
-        // Read the NCT root
-        let anchor = nct.root();
+        // Use the requested anchor, or else the current NCT root
+        let anchor = match requested_anchor {
+            Some(anchor) => {
+                if anchor != nct.root() && !nct.historical_roots().any(|root| root == anchor) {
+                    return Err(tonic::Status::not_found(format!(
+                        "anchor {} is not a recent root of the note commitment tree",
+                        anchor
+                    )));
+                }
+                anchor
+            }
+            None => nct.root(),
+        };
 
         // Obtain an auth path for each requested note commitment
         let requested_note_commitments = request
@@ -433,23 +690,56 @@ impl ViewProtocol for ViewService {
                     "Unable to deserialize note commitment",
                 )
             })?;
-        let auth_paths: Vec<Proof> = requested_note_commitments
-            .iter()
-            .map(|nc| {
-                nct.witness(*nc).ok_or_else(|| {
-                    tonic::Status::new(tonic::Code::InvalidArgument, "Note commitment missing")
-                })
-            })
-            .collect::<Result<Vec<Proof>, tonic::Status>>()?;
+        // Obtain a single multi-proof for all the requested note commitments, which is much
+        // smaller than an individual auth path for each
+        let multi_proof = nct
+            .witness_many_at(requested_note_commitments, anchor)
+            .ok_or_else(|| {
+                tonic::Status::new(tonic::Code::InvalidArgument, "Note commitment missing")
+            })?;
 
         // Release the read lock on the NCT
         drop(nct);
 
-        let witness_data = WitnessData {
-            anchor,
-            note_commitment_proofs: auth_paths,
+        let witness_data = pbt::WitnessData {
+            anchor: Some(anchor.into()),
+            note_commitment_proofs: Vec::new(),
+            note_commitment_multi_proof: Some(multi_proof.into()),
         };
-        Ok(tonic::Response::new(witness_data.into()))
+        Ok(tonic::Response::new(witness_data))
+    }
+
+    async fn transaction_perspective(
+        &self,
+        request: tonic::Request<pb::TransactionPerspectiveRequest>,
+    ) -> Result<tonic::Response<pb::TransactionPerspectiveResponse>, tonic::Status> {
+        self.check_worker().await?;
+        self.check_fvk(request.get_ref().fvk_hash.as_ref()).await?;
+
+        let request = request.into_inner();
+        let transaction = match request.tx {
+            Some(tx) => tx.try_into().map_err(|e| {
+                tonic::Status::invalid_argument(format!("invalid transaction: {}", e))
+            })?,
+            None => self
+                .transaction_by_hash(request.tx_hash)
+                .await
+                .map_err(|e| {
+                    tonic::Status::unavailable(format!("error fetching transaction: {}", e))
+                })?,
+        };
+
+        let perspective = self
+            .transaction_perspective(&transaction)
+            .await
+            .map_err(|e| {
+                tonic::Status::unavailable(format!("error computing perspective: {}", e))
+            })?;
+
+        Ok(tonic::Response::new(pb::TransactionPerspectiveResponse {
+            tx: Some(transaction.into()),
+            perspective: Some(perspective.into()),
+        }))
     }
 
     async fn chain_params(
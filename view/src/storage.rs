@@ -5,19 +5,21 @@ use parking_lot::Mutex;
 use penumbra_chain::params::ChainParams;
 use penumbra_crypto::{
     asset::{self, Id},
-    Asset, FieldExt, FullViewingKey,
+    Asset, FieldExt, FullViewingKey, Nullifier,
 };
 use penumbra_proto::{
     client::oblivious::{oblivious_query_client::ObliviousQueryClient, ChainParamsRequest},
     Protobuf,
 };
 use penumbra_tct as tct;
-use sqlx::{migrate::MigrateDatabase, query, Pool, Sqlite};
+use sqlx::{
+    migrate::MigrateDatabase, query, sqlite::SqliteConnectOptions, Pool, Sqlite, SqliteConnection,
+};
 use std::{num::NonZeroU64, sync::Arc};
 use tct::Commitment;
 use tokio::sync::broadcast;
 
-use crate::{sync::ScanResult, NoteRecord, QuarantinedNoteRecord};
+use crate::{metrics, sync::ScanResult, NoteRecord};
 
 #[derive(Clone)]
 pub struct Storage {
@@ -61,8 +63,31 @@ impl Storage {
     }
 
     pub async fn load(path: impl AsRef<Utf8Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let pool = Pool::<Sqlite>::connect(path.as_str()).await?;
+
+        // Bring databases created by older versions up to date.
+        sqlx::migrate!().run(&pool).await?;
+
+        Ok(Self {
+            pool,
+            uncommitted_height: Arc::new(Mutex::new(None)),
+            scanned_notes_tx: broadcast::channel(10).0,
+        })
+    }
+
+    /// Opens the database at `path` for inspection, without migrating or
+    /// otherwise writing to it.
+    ///
+    /// The database must already have been brought up to date by [`Self::load`].
+    pub async fn load_read_only(path: impl AsRef<Utf8Path>) -> anyhow::Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path.as_ref())
+            .read_only(true);
+        let pool = Pool::<Sqlite>::connect_with(options).await?;
+
         Ok(Self {
-            pool: Pool::<Sqlite>::connect(path.as_ref().as_str()).await?,
+            pool,
             uncommitted_height: Arc::new(Mutex::new(None)),
             scanned_notes_tx: broadcast::channel(10).0,
         })
@@ -90,20 +115,13 @@ impl Storage {
         // Run migrations
         sqlx::migrate!().run(&pool).await?;
 
-        // Initialize the database state with: empty NCT, chain params, FVK
+        // Initialize the database state with: chain params, FVK (the NCT starts
+        // out empty, with no updates)
         let mut tx = pool.begin().await?;
 
-        let nct_bytes = bincode::serialize(&tct::Tree::new())?;
         let chain_params_bytes = &ChainParams::encode_to_vec(&params)[..];
         let fvk_bytes = &FullViewingKey::encode_to_vec(&fvk)[..];
 
-        sqlx::query!(
-            "INSERT INTO note_commitment_tree (bytes) VALUES (?)",
-            nct_bytes
-        )
-        .execute(&mut tx)
-        .await?;
-
         sqlx::query!(
             "INSERT INTO chain_params (bytes) VALUES (?)",
             chain_params_bytes
@@ -227,17 +245,11 @@ impl Storage {
     }
 
     pub async fn note_commitment_tree(&self) -> anyhow::Result<tct::Tree> {
-        let result = query!(
-            r#"
-            SELECT bytes
-            FROM note_commitment_tree
-            LIMIT 1
-            "#
-        )
-        .fetch_one(&self.pool)
-        .await?;
+        let mut conn = self.pool.acquire().await?;
+        let (batches, size) = nct_batches(&mut conn).await?;
+        metrics::gauge!(metrics::NCT_SIZE_BYTES, size as f64);
 
-        Ok(bincode::deserialize(result.bytes.as_slice())?)
+        Ok(tct::Tree::from_batches(batches)?)
     }
 
     pub async fn assets(&self) -> anyhow::Result<Vec<Asset>> {
@@ -339,12 +351,22 @@ impl Storage {
         Ok(output)
     }
 
-    pub async fn quarantined_notes(&self) -> anyhow::Result<Vec<QuarantinedNoteRecord>> {
-        let result = sqlx::query_as::<_, QuarantinedNoteRecord>("SELECT * FROM quarantined_notes")
-            .fetch_all(&self.pool)
-            .await?;
-
-        Ok(result)
+    /// The note with the given nullifier, if it's one of ours.
+    pub async fn note_by_nullifier(
+        &self,
+        nullifier: Nullifier,
+    ) -> anyhow::Result<Option<NoteRecord>> {
+        Ok(sqlx::query_as::<_, NoteRecord>(
+            format!(
+                "SELECT *
+                FROM notes
+                WHERE nullifier = x'{}'",
+                hex::encode(nullifier.to_bytes())
+            )
+            .as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await?)
     }
 
     pub async fn record_asset(&self, asset: Asset) -> anyhow::Result<()> {
@@ -416,56 +438,6 @@ impl Storage {
         }
         let mut tx = self.pool.begin().await?;
 
-        // Insert all quarantined note commitments into storage
-        for quarantined_note_record in &scan_result.new_quarantined_notes {
-            let note_commitment = quarantined_note_record
-                .note_commitment
-                .0
-                .to_bytes()
-                .to_vec();
-            let height_created = scan_result.height as i64;
-            let diversifier = quarantined_note_record.note.diversifier().0.to_vec();
-            let amount = quarantined_note_record.note.amount() as i64;
-            let asset_id = quarantined_note_record.note.asset_id().to_bytes().to_vec();
-            let transmission_key = quarantined_note_record.note.transmission_key().0.to_vec();
-            let blinding_factor = quarantined_note_record
-                .note
-                .note_blinding()
-                .to_bytes()
-                .to_vec();
-            let diversifier_index = quarantined_note_record.diversifier_index.0.to_vec();
-            let unbonding_epoch = quarantined_note_record.unbonding_epoch as i64;
-            let identity_key = quarantined_note_record.identity_key.encode_to_vec();
-            sqlx::query!(
-                "INSERT INTO quarantined_notes
-                    (
-                        note_commitment,
-                        height_created,
-                        diversifier,
-                        amount,
-                        asset_id,
-                        transmission_key,
-                        blinding_factor,
-                        diversifier_index,
-                        unbonding_epoch,
-                        identity_key
-                    )
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                note_commitment,
-                height_created,
-                diversifier,
-                amount,
-                asset_id,
-                transmission_key,
-                blinding_factor,
-                diversifier_index,
-                unbonding_epoch,
-                identity_key,
-            )
-            .execute(&mut tx)
-            .await?;
-        }
-
         // Insert all new note records into storage
         for note_record in &scan_result.new_notes {
             // https://github.com/launchbadge/sqlx/issues/1430
@@ -526,48 +498,6 @@ impl Storage {
             )
             .execute(&mut tx)
             .await?;
-
-            // If this note corresponded to a previously quarantined note, delete it from quarantine
-            // also, because it is now applied
-            sqlx::query!(
-                "DELETE FROM quarantined_notes WHERE note_commitment = ?",
-                note_commitment,
-            )
-            .execute(&mut tx)
-            .await?;
-        }
-
-        // Add all quarantined nullifiers to storage and mark notes as spent, *without* forgetting
-        // them from the NCT (because they could be rolled back)
-        for (identity_key, quarantined_nullifiers) in scan_result.spent_quarantined_nullifiers {
-            let identity_key = identity_key.encode_to_vec();
-            for quarantined_nullifier in quarantined_nullifiers {
-                let height_spent = scan_result.height as i64;
-                let nullifier = quarantined_nullifier.to_bytes().to_vec();
-
-                // Track the quarantined nullifier
-                sqlx::query!(
-                    "INSERT INTO quarantined_nullifiers
-                        (
-                            identity_key,
-                            nullifier
-                        )
-                    VALUES (?, ?)",
-                    identity_key,
-                    nullifier,
-                )
-                .execute(&mut tx)
-                .await?;
-
-                // Mark the note as spent
-                sqlx::query!(
-                    "UPDATE notes SET height_spent = ? WHERE nullifier = ?",
-                    height_spent,
-                    nullifier,
-                )
-                .execute(&mut tx)
-                .await?;
-            }
         }
 
         // Update any rows of the table with matching nullifiers to have height_spent
@@ -590,62 +520,28 @@ impl Storage {
             if let Some(bytes) = spent_commitment_bytes {
                 // Forget spent note commitments from the NCT
                 let spent_commitment = Commitment::try_from(bytes.note_commitment.as_slice())?;
-                nct.forget(spent_commitment);
+                if nct.forget(spent_commitment) {
+                    metrics::increment_counter!(metrics::NCT_FORGOTTEN_TOTAL);
+                }
             }
-
-            // If the nullifier was previously quarantined, remove it from the list of quarantined
-            // nullifiers, because it has now been spent
-            sqlx::query!(
-                "DELETE FROM quarantined_nullifiers WHERE nullifier = ?",
-                nullifier,
-            )
-            .execute(&mut tx)
-            .await?;
         }
 
-        // For any slashed validator, remove all quarantined notes and nullifiers for that
-        // validator, and un-spend all spent notes that were referred to by all rolled back
-        // nullifiers
-        for identity_key in scan_result.slashed_validators {
-            let identity_key = identity_key.encode_to_vec();
+        // Append the updates made to the NCT since the last recorded block to its log, compacting
+        // it whenever an epoch ends, so that epochs in which every note we witnessed has been
+        // spent are collapsed into a single hash
 
-            // Delete all quarantined notes for this validator
-            sqlx::query!(
-                "DELETE FROM quarantined_notes WHERE identity_key = ?",
-                identity_key,
-            )
+        let nct_batch = tct::storage::Batch::take(nct);
+        let nct_bytes = nct_batch.encode();
+        metrics::increment_gauge!(metrics::NCT_SIZE_BYTES, nct_bytes.len() as f64);
+        let nct_index = sqlx::query("INSERT INTO nct_updates (bytes) VALUES (?)")
+            .bind(nct_bytes)
             .execute(&mut tx)
-            .await?;
-
-            // Collect all the currently quarantined nullifiers for this validator, deleting them in
-            // the process
-            let rolled_back_nullifiers = sqlx::query!(
-                "DELETE FROM quarantined_nullifiers WHERE identity_key = ? RETURNING nullifier",
-                identity_key,
-            )
-            .fetch_all(&mut tx)
-            .await?;
-
-            // For each such nullifier, roll back the spend of the note associated with it, marking
-            // that note as spendable again
-            for rolled_back_nullifier in rolled_back_nullifiers {
-                let rolled_back_nullifier = rolled_back_nullifier.nullifier.to_vec();
-                sqlx::query!(
-                    "UPDATE notes SET height_spent = NULL WHERE nullifier = ?",
-                    rolled_back_nullifier,
-                )
-                .execute(&mut tx)
-                .await?;
-            }
+            .await?
+            .last_insert_rowid();
+        if nct_index >= NCT_COMPACTION_INTERVAL || nct_batch.ends_epoch() {
+            compact_nct(&mut tx).await?;
         }
 
-        // Update NCT table with current NCT state
-
-        let nct_bytes = bincode::serialize(nct)?;
-        sqlx::query!("UPDATE note_commitment_tree SET bytes = ?", nct_bytes)
-            .execute(&mut tx)
-            .await?;
-
         // Record block height as latest synced height
 
         let latest_sync_height = scan_result.height as i64;
@@ -670,3 +566,137 @@ impl Storage {
         Ok(())
     }
 }
+
+/// How many blocks' worth of updates to the NCT are appended to its log before
+/// it is compacted.
+const NCT_COMPACTION_INTERVAL: i64 = 1000;
+
+/// Reads the NCT's log of updates, as batches in the order they were recorded,
+/// along with the total size of the log in bytes.
+async fn nct_batches(
+    conn: &mut SqliteConnection,
+) -> anyhow::Result<(Vec<tct::storage::Batch>, usize)> {
+    let rows = sqlx::query_as::<_, (Vec<u8>,)>("SELECT bytes FROM nct_updates ORDER BY id ASC")
+        .fetch_all(conn)
+        .await?;
+
+    let mut batches = Vec::new();
+    let mut size = 0;
+    for (bytes,) in rows {
+        size += bytes.len();
+        batches.push(tct::storage::Batch::decode(&bytes)?);
+    }
+    Ok((batches, size))
+}
+
+/// Replaces the NCT's log of updates with a single compacted entry.
+async fn compact_nct(conn: &mut SqliteConnection) -> anyhow::Result<()> {
+    let (batches, size) = nct_batches(conn).await?;
+    let updates = batches.into_iter().flat_map(|batch| batch.updates);
+    let compacted = tct::storage::compact(updates)?.encode();
+    tracing::debug!(
+        size,
+        compacted_size = compacted.len(),
+        "compacted NCT updates"
+    );
+    metrics::counter!(
+        metrics::NCT_COMPACTED_BYTES_TOTAL,
+        size.saturating_sub(compacted.len()) as u64
+    );
+    metrics::gauge!(metrics::NCT_SIZE_BYTES, compacted.len() as f64);
+
+    sqlx::query("DELETE FROM nct_updates")
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("INSERT INTO nct_updates (bytes) VALUES (?)")
+        .bind(compacted)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn load_upgrades_databases_created_by_the_initial_migration() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = Utf8Path::from_path(dir.path())
+            .ok_or_else(|| anyhow!("non-UTF-8 temporary directory"))?
+            .join("view.sqlite");
+        std::fs::File::create(&path)?;
+
+        // Create the database as the first release did, with only the initial
+        // migration applied, and put some scanned state in it.
+        {
+            let pool = Pool::<Sqlite>::connect(path.as_str()).await?;
+            let mut baseline = sqlx::migrate!();
+            baseline.migrations = Cow::Owned(baseline.migrations[..1].to_vec());
+            baseline.run(&pool).await?;
+
+            sqlx::query("INSERT INTO sync_height (height) VALUES (100)")
+                .execute(&pool)
+                .await?;
+            sqlx::query("INSERT INTO note_commitment_tree (bytes) VALUES (x'00')")
+                .execute(&pool)
+                .await?;
+            sqlx::query(
+                "INSERT INTO notes (note_commitment, height_created, diversifier, amount, asset_id,
+                    transmission_key, blinding_factor, diversifier_index, nullifier, position)
+                VALUES (x'01', 10, x'02', 5, x'03', x'04', x'05', x'06', x'07', 0)",
+            )
+            .execute(&pool)
+            .await?;
+            pool.close().await;
+        }
+
+        // Opening the database read-only, as tct-render does, leaves it as it was.
+        {
+            let storage = Storage::load_read_only(&path).await?;
+            assert!(storage.note_commitment_tree().await.is_err());
+            let (applied,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM _sqlx_migrations")
+                .fetch_one(&storage.pool)
+                .await?;
+            assert_eq!(applied, 1);
+            storage.pool.close().await;
+        }
+
+        let storage = Storage::load(&path).await?;
+
+        // The tree is now stored as a log of updates, and everything scanned
+        // against the old tree has been cleared so that it is rescanned.
+        let (updates,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM nct_updates")
+            .fetch_one(&storage.pool)
+            .await?;
+        assert_eq!(updates, 0);
+        let (notes,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM notes")
+            .fetch_one(&storage.pool)
+            .await?;
+        assert_eq!(notes, 0);
+        let (height,): (i64,) = sqlx::query_as("SELECT height FROM sync_height")
+            .fetch_one(&storage.pool)
+            .await?;
+        assert_eq!(height, -1);
+        assert_eq!(storage.last_sync_height().await?, None);
+
+        // The old tables are gone.
+        for table in [
+            "note_commitment_tree",
+            "quarantined_notes",
+            "quarantined_nullifiers",
+        ] {
+            let (count,): (i64,) = sqlx::query_as(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+            )
+            .bind(table)
+            .fetch_one(&storage.pool)
+            .await?;
+            assert_eq!(count, 0, "{} should have been dropped", table);
+        }
+
+        Ok(())
+    }
+}
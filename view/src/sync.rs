@@ -1,32 +1,25 @@
 use std::collections::BTreeMap;
 
 use penumbra_chain::{CompactBlock, Epoch};
-use penumbra_crypto::{note, IdentityKey, Nullifier};
+use penumbra_crypto::{note, Nullifier};
 use penumbra_crypto::{FullViewingKey, Note, NotePayload};
 use penumbra_tct as tct;
 
-use crate::{NoteRecord, QuarantinedNoteRecord};
+use crate::NoteRecord;
 
 /// Contains the results of scanning a single block.
 #[derive(Debug, Clone)]
 pub struct ScanResult {
     // write as new rows
     pub new_notes: Vec<NoteRecord>,
-    pub new_quarantined_notes: Vec<QuarantinedNoteRecord>,
     // use to update existing rows
     pub spent_nullifiers: Vec<Nullifier>,
-    pub spent_quarantined_nullifiers: BTreeMap<IdentityKey, Vec<Nullifier>>,
-    pub slashed_validators: Vec<IdentityKey>,
     pub height: u64,
 }
 
 impl ScanResult {
     pub fn is_empty(&self) -> bool {
-        self.new_notes.is_empty()
-            && self.new_quarantined_notes.is_empty()
-            && self.spent_nullifiers.is_empty()
-            && self.spent_quarantined_nullifiers.is_empty()
-            && self.slashed_validators.is_empty()
+        self.new_notes.is_empty() && self.spent_nullifiers.is_empty()
     }
 }
 
@@ -40,8 +33,6 @@ pub fn scan_block(
         nullifiers,
         block_root,
         epoch_root,
-        quarantined,
-        slashed,
     }: CompactBlock,
     epoch_duration: u64,
 ) -> ScanResult {
@@ -64,47 +55,9 @@ pub fn scan_block(
 
     // Notes we've found in this block that are meant for us
     let new_notes: Vec<NoteRecord>;
-    let mut new_quarantined_notes: Vec<QuarantinedNoteRecord> = Vec::new();
 
     // Nullifiers we've found in this block
     let spent_nullifiers: Vec<Nullifier> = nullifiers;
-    let mut spent_quarantined_nullifiers: BTreeMap<IdentityKey, Vec<Nullifier>> = BTreeMap::new();
-
-    // Collect quarantined nullifiers, and add all quarantined notes we can decrypt to the new
-    // quarantined notes set
-    for (unbonding_epoch, mut scheduled) in quarantined {
-        // For any validator slashed in this block, so any quarantined transactions in this block
-        // are immediately reverted; we don't even report them to the state, so that the state can
-        // avoid worrying about update ordering
-        for &identity_key in slashed.iter() {
-            scheduled.unschedule_validator(identity_key);
-        }
-
-        for (identity_key, unbonding) in scheduled {
-            // Remember these nullifiers (not all of them are ours, we have to check the database)
-            spent_quarantined_nullifiers
-                .entry(identity_key)
-                .or_default()
-                .extend(unbonding.nullifiers);
-            // Trial-decrypt the quarantined notes, keeping track of the ones that were meant for us
-            new_quarantined_notes.extend(
-                unbonding
-                    .note_payloads
-                    .into_iter()
-                    .filter_map(|note_payload| trial_decrypt(&note_payload))
-                    .map(|note| QuarantinedNoteRecord {
-                        note_commitment: note.commit(),
-                        height_created: height,
-                        diversifier_index: fvk
-                            .incoming()
-                            .index_for_diversifier(&note.diversifier()),
-                        note,
-                        unbonding_epoch,
-                        identity_key,
-                    }),
-            );
-        }
-    }
 
     // Trial-decrypt the notes in this block, keeping track of the ones that were meant for us
     let mut decrypted_applied_notes: BTreeMap<note::Commitment, Note> = note_payloads
@@ -179,18 +132,9 @@ pub fn scan_block(
     // Print the TCT root for debugging
     tracing::debug!(tct_root = %note_commitment_tree.root(), "tct root");
 
-    let result = ScanResult {
+    ScanResult {
         new_notes,
-        new_quarantined_notes,
         spent_nullifiers,
-        spent_quarantined_nullifiers,
-        slashed_validators: slashed,
         height,
-    };
-
-    if !result.spent_quarantined_nullifiers.is_empty() || !result.new_quarantined_notes.is_empty() {
-        tracing::debug!(?result, "scan result contained quarantined things");
     }
-
-    result
 }
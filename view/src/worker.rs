@@ -19,6 +19,8 @@ pub struct Worker {
     sync_height_tx: watch::Sender<u64>,
     #[cfg(feature = "nct-divergence-check")]
     specific_client: SpecificQueryClient<Channel>,
+    #[cfg(feature = "nct-divergence-check")]
+    tendermint_url: String,
 }
 
 impl Worker {
@@ -28,10 +30,12 @@ impl Worker {
     /// - a shared, in-memory NCT instance;
     /// - a shared error slot;
     /// - a channel for notifying the client of sync progress.
+    #[cfg_attr(not(feature = "nct-divergence-check"), allow(unused_variables))]
     pub async fn new(
         storage: Storage,
         node: String,
         pd_port: u16,
+        tendermint_port: u16,
     ) -> Result<
         (
             Self,
@@ -43,8 +47,11 @@ impl Worker {
     > {
         let fvk = storage.full_viewing_key().await?;
 
-        // Create a shared, in-memory NCT.
-        let nct = Arc::new(RwLock::new(storage.note_commitment_tree().await?));
+        // Create a shared, in-memory NCT, keeping enough past versions of it to witness
+        // commitments relative to any anchor that is still valid on chain.
+        let mut nct = storage.note_commitment_tree().await?;
+        nct.set_history_window(storage.chain_params().await?.max_anchor_age as usize);
+        let nct = Arc::new(RwLock::new(nct));
         // Create a shared error slot
         let error_slot = Arc::new(Mutex::new(None));
         // Create a channel for the worker to notify of sync height changes.
@@ -57,6 +64,8 @@ impl Worker {
         #[cfg(feature = "nct-divergence-check")]
         let specific_client =
             SpecificQueryClient::connect(format!("http://{}:{}", node, pd_port)).await?;
+        #[cfg(feature = "nct-divergence-check")]
+        let tendermint_url = format!("http://{}:{}", node, tendermint_port);
 
         Ok((
             Self {
@@ -68,6 +77,8 @@ impl Worker {
                 sync_height_tx,
                 #[cfg(feature = "nct-divergence-check")]
                 specific_client,
+                #[cfg(feature = "nct-divergence-check")]
+                tendermint_url,
             },
             nct,
             error_slot,
@@ -144,6 +155,10 @@ impl Worker {
             // Lock the NCT only while processing this block.
             let mut nct_guard = self.nct.write().await;
 
+            // If recording the block fails, the sync will be retried from the last recorded
+            // height, so the in-memory NCT must be rolled back to match it.
+            let checkpoint = nct_guard.checkpoint();
+
             if !block.requires_scanning() {
                 // Optimization: if the block is empty, seal the in-memory NCT,
                 // and skip touching the database:
@@ -155,7 +170,10 @@ impl Worker {
                         .end_epoch()
                         .expect("ending the epoch must succeed");
                 }
-                self.storage.record_empty_block(height).await?;
+                if let Err(e) = self.storage.record_empty_block(height).await {
+                    nct_guard.rollback(checkpoint);
+                    return Err(e);
+                }
                 // Notify all watchers of the new height we just recorded.
                 self.sync_height_tx.send(height)?;
             } else {
@@ -163,14 +181,21 @@ impl Worker {
                 let scan_result = scan_block(&self.fvk, &mut nct_guard, block, epoch_duration);
                 let height = scan_result.height;
 
-                self.storage
-                    .record_block(scan_result, &mut nct_guard)
-                    .await?;
+                if let Err(e) = self.storage.record_block(scan_result, &mut nct_guard).await {
+                    nct_guard.rollback(checkpoint);
+                    return Err(e);
+                }
                 // Notify all watchers of the new height we just recorded.
                 self.sync_height_tx.send(height)?;
             }
             #[cfg(feature = "nct-divergence-check")]
-            nct_divergence_check(&mut self.specific_client, height, nct_guard.root()).await?;
+            nct_divergence_check(
+                &mut self.specific_client,
+                &self.tendermint_url,
+                height,
+                nct_guard.root(),
+            )
+            .await?;
 
             // Release the NCT RwLock
             drop(nct_guard);
@@ -225,19 +250,47 @@ impl Worker {
 #[cfg(feature = "nct-divergence-check")]
 async fn nct_divergence_check(
     client: &mut SpecificQueryClient<Channel>,
+    tendermint_url: &str,
     height: u64,
     actual_root: penumbra_tct::Root,
 ) -> anyhow::Result<()> {
     use penumbra_proto::Protobuf;
 
-    let value = client
+    // The anchor for this height is first committed to by the header of the next block, which
+    // won't exist yet if we're caught up with the chain, so wait for it to be produced. Anchors are
+    // never overwritten, so any later version will do, and the latest one can't have been pruned.
+    let mut attempts = 0;
+    let (version, trusted_root) = loop {
+        let (version, root) = crate::committed_root(tendermint_url, None).await?;
+        if version >= height {
+            break (version, root);
+        }
+        attempts += 1;
+        if attempts >= 60 {
+            return Err(anyhow::anyhow!(
+                "no header has committed to the anchor at height {} yet",
+                height
+            ));
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    };
+
+    let key = format!("shielded_pool/anchor/{}", height).into_bytes();
+    let response = client
         .key_value(penumbra_proto::client::specific::KeyValueRequest {
-            key: format!("shielded_pool/anchor/{}", height).into_bytes(),
+            key: key.clone(),
+            proof: true,
+            height: Some(version),
             ..Default::default()
         })
         .await?
-        .into_inner()
-        .value;
+        .into_inner();
+
+    // Make sure we're comparing against the anchor committed to by the chain, not just the one
+    // the node tells us about.
+    let value = penumbra_storage::verify_key_value(key.as_slice().into(), trusted_root, response)?
+        .value
+        .ok_or_else(|| anyhow::anyhow!("no NCT anchor recorded at height {}", height))?;
 
     let expected_root = penumbra_tct::Root::decode(value.as_slice())?;
 
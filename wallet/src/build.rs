@@ -32,8 +32,10 @@ where
             fvk_hash: Some(fvk.hash().into()),
             note_commitments: plan
                 .spend_plans()
+                .filter(|spend| !spend.is_dummy())
                 .map(|spend| spend.note.commit().into())
                 .collect(),
+            anchor: None,
         })
         .await?;
 
@@ -4,11 +4,13 @@ use anyhow::Result;
 use penumbra_component::stake::rate::RateData;
 use penumbra_component::stake::validator;
 use penumbra_crypto::{
-    asset::Denom, keys::DiversifierIndex, memo::MemoPlaintext, Address, DelegationToken,
-    FullViewingKey, Value, STAKING_TOKEN_ASSET_ID, STAKING_TOKEN_DENOM,
+    asset::Denom, keys::DiversifierIndex, memo::MemoPlaintext, Address, DelegationToken, Fr,
+    FullViewingKey, IdentityKey, Note, Value, Zero, STAKING_TOKEN_ASSET_ID, STAKING_TOKEN_DENOM,
 };
 use penumbra_proto::view::NotesRequest;
+use penumbra_tct as tct;
 use penumbra_transaction::{
+    action::UndelegateClaim,
     plan::{ActionPlan, OutputPlan, SpendPlan, TransactionPlan},
     Fee,
 };
@@ -16,11 +18,34 @@ use penumbra_view::{NoteRecord, ViewClient};
 use rand_core::{CryptoRng, RngCore};
 use tracing::instrument;
 
+/// A [`SpendPlan`] whose spend authorization key is not randomized, so that the
+/// spend is visibly authorized by the validator whose identity key it is, as
+/// the chain requires for changes to a validator's self-bond.
+fn self_bond_spend<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    note: Note,
+    position: tct::Position,
+) -> SpendPlan {
+    SpendPlan {
+        randomizer: Fr::zero(),
+        ..SpendPlan::new(rng, note, position)
+    }
+}
+
+/// Generate a new transaction plan uploading a validator definition,
+/// optionally adding the given amount of unbonded stake to the validator's
+/// self-bond in the same transaction.
+///
+/// New validators are required to self-bond at least the chain's
+/// `min_self_delegation` in the transaction that defines them. The delegation
+/// tokens of the self-bond are held by the chain, and can only be withdrawn
+/// with [`undelegate_self_bond`].
 pub async fn validator_definition<V, R>(
     fvk: &FullViewingKey,
     view: &mut V,
     mut rng: R,
     new_validator: validator::Definition,
+    self_delegation: Option<(RateData, u64)>,
     fee: u64,
     source_address: Option<u64>,
 ) -> Result<TransactionPlan>
@@ -45,8 +70,18 @@ where
     plan.actions
         .push(ActionPlan::ValidatorDefinition(new_validator.into()));
 
+    let mut spend_amount = fee;
+    let self_bond = self_delegation.is_some();
+    if let Some((rate_data, unbonded_amount)) = self_delegation {
+        // Add the delegation action; the delegation tokens are held by the
+        // chain, so there's no output recording them.
+        let mut delegate = rate_data.build_delegate(unbonded_amount);
+        delegate.self_bond = true;
+        plan.actions.push(delegate.into());
+        spend_amount += unbonded_amount;
+    }
+
     // Add the required spends, and track change:
-    let spend_amount = fee;
     let mut spent_amount = 0;
     let source_index: Option<DiversifierIndex> = source_address.map(Into::into);
     let notes_to_spend = view
@@ -60,9 +95,22 @@ where
         .await?;
     for note_record in notes_to_spend {
         spent_amount += note_record.note.amount();
-        plan.actions
-            .push(SpendPlan::new(&mut rng, note_record.note, note_record.position).into());
+        let spend = if self_bond {
+            self_bond_spend(&mut rng, note_record.note, note_record.position)
+        } else {
+            SpendPlan::new(&mut rng, note_record.note, note_record.position)
+        };
+        plan.actions.push(spend.into());
     }
+
+    if spent_amount < spend_amount {
+        return Err(anyhow::anyhow!(
+            "not enough notes to upload validator definition: wanted to spend {}, have {}",
+            spend_amount,
+            spent_amount
+        ));
+    }
+
     // Add a change note if we have change left over:
     let change_amount = spent_amount - spend_amount;
     // TODO: support dummy notes, and produce a change output unconditionally.
@@ -184,7 +232,10 @@ where
     Ok(plan)
 }
 
-/// Generate a new transaction plan undelegating stake
+/// Generate a new transaction plan undelegating stake.
+///
+/// The undelegated stake is received as unbonding tokens, which can be claimed
+/// with [`undelegate_claim`] once the unbonding period has elapsed.
 pub async fn undelegate<V, R>(
     fvk: &FullViewingKey,
     view: &mut V,
@@ -211,19 +262,6 @@ where
 
     let spend_amount = delegation_amount;
 
-    // Because the outputs of an undelegation are quarantined, we want to
-    // avoid any unnecessary change outputs, so we pay fees out of the
-    // unbonded amount.
-    let unbonded_amount = rate_data.unbonded_amount(delegation_amount);
-    let output_amount = unbonded_amount.checked_sub(fee).ok_or_else(|| {
-        anyhow::anyhow!(
-            "unbonded amount {} from delegation amount {} is insufficient to pay fees {}",
-            unbonded_amount,
-            delegation_amount,
-            fee
-        )
-    })?;
-
     let mut plan = TransactionPlan {
         chain_id: chain_params.chain_id,
         fee: Fee(fee),
@@ -231,17 +269,18 @@ where
     };
 
     // add the undelegation action itself
-    plan.actions
-        .push(rate_data.build_undelegate(delegation_amount).into());
+    let undelegate = rate_data.build_undelegate(delegation_amount);
+    let unbonding_value = Value {
+        amount: undelegate.unbonded_amount,
+        asset_id: undelegate.unbonding_token().id(),
+    };
+    plan.actions.push(undelegate.into());
 
-    // add the outputs for the undelegation
+    // add the output recording the unbonding tokens
     plan.actions.push(
         OutputPlan::new(
             &mut rng,
-            Value {
-                amount: output_amount,
-                asset_id: *STAKING_TOKEN_ASSET_ID,
-            },
+            unbonding_value,
             self_address,
             MemoPlaintext::default(),
         )
@@ -264,6 +303,228 @@ where
         ))?;
     }
 
+    // Unbonding tokens can't be used to pay fees, so pay them out of
+    // staking tokens instead.
+    if fee > 0 {
+        let source_index: Option<DiversifierIndex> = source_address.map(Into::into);
+        let fee_notes = view
+            .notes(NotesRequest {
+                fvk_hash: Some(fvk.hash().into()),
+                asset_id: Some((*STAKING_TOKEN_ASSET_ID).into()),
+                diversifier_index: source_index.map(Into::into),
+                amount_to_spend: fee,
+                include_spent: false,
+            })
+            .await?;
+
+        let mut fee_spent_amount = 0;
+        for note_record in fee_notes {
+            fee_spent_amount += note_record.note.amount();
+            plan.actions
+                .push(SpendPlan::new(&mut rng, note_record.note, note_record.position).into());
+        }
+
+        if fee_spent_amount < fee {
+            return Err(anyhow::anyhow!(
+                "not enough notes to pay fees: wanted {}, have {}",
+                fee,
+                fee_spent_amount
+            ));
+        }
+
+        let change_amount = fee_spent_amount - fee;
+        if change_amount > 0 {
+            plan.actions.push(
+                OutputPlan::new(
+                    &mut rng,
+                    Value {
+                        amount: change_amount,
+                        asset_id: *STAKING_TOKEN_ASSET_ID,
+                    },
+                    self_address,
+                    MemoPlaintext::default(),
+                )
+                .into(),
+            );
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Generate a new transaction plan withdrawing the given amount of delegation
+/// tokens from the self-bond of the validator whose identity key is controlled
+/// by `fvk`.
+///
+/// The withdrawn stake is received as unbonding tokens, like any other
+/// undelegation. The fee is paid out of staking tokens, and at least one note
+/// is spent even if the fee is zero, because the spends are what authorize the
+/// withdrawal.
+pub async fn undelegate_self_bond<V, R>(
+    fvk: &FullViewingKey,
+    view: &mut V,
+    mut rng: R,
+    rate_data: RateData,
+    delegation_amount: u64,
+    fee: u64,
+    source_address: Option<u64>,
+) -> Result<TransactionPlan>
+where
+    V: ViewClient,
+    R: RngCore + CryptoRng,
+{
+    let (self_address, _dtk) = fvk
+        .incoming()
+        .payment_address(source_address.unwrap_or(0).into());
+
+    let chain_params = view.chain_params().await?;
+
+    let mut plan = TransactionPlan {
+        chain_id: chain_params.chain_id,
+        fee: Fee(fee),
+        ..Default::default()
+    };
+
+    // add the undelegation action itself
+    let mut undelegate = rate_data.build_undelegate(delegation_amount);
+    undelegate.self_bond = true;
+    let unbonding_value = Value {
+        amount: undelegate.unbonded_amount,
+        asset_id: undelegate.unbonding_token().id(),
+    };
+    plan.actions.push(undelegate.into());
+
+    // add the output recording the unbonding tokens
+    plan.actions.push(
+        OutputPlan::new(
+            &mut rng,
+            unbonding_value,
+            self_address,
+            MemoPlaintext::default(),
+        )
+        .into(),
+    );
+
+    // Spend enough staking tokens to pay the fee, and at least one note.
+    let source_index: Option<DiversifierIndex> = source_address.map(Into::into);
+    let fee_notes = view
+        .notes(NotesRequest {
+            fvk_hash: Some(fvk.hash().into()),
+            asset_id: Some((*STAKING_TOKEN_ASSET_ID).into()),
+            diversifier_index: source_index.map(Into::into),
+            amount_to_spend: fee.max(1),
+            include_spent: false,
+        })
+        .await?;
+
+    let mut fee_spent_amount = 0;
+    for note_record in fee_notes {
+        fee_spent_amount += note_record.note.amount();
+        plan.actions
+            .push(self_bond_spend(&mut rng, note_record.note, note_record.position).into());
+    }
+
+    if fee_spent_amount < fee.max(1) {
+        return Err(anyhow::anyhow!(
+            "not enough notes to authorize the withdrawal and pay fees: wanted {}, have {}",
+            fee.max(1),
+            fee_spent_amount
+        ));
+    }
+
+    let change_amount = fee_spent_amount - fee;
+    if change_amount > 0 {
+        plan.actions.push(
+            OutputPlan::new(
+                &mut rng,
+                Value {
+                    amount: change_amount,
+                    asset_id: *STAKING_TOKEN_ASSET_ID,
+                },
+                self_address,
+                MemoPlaintext::default(),
+            )
+            .into(),
+        );
+    }
+
+    Ok(plan)
+}
+
+/// Generate a new transaction plan claiming the unbonding tokens from an
+/// undelegation as staking tokens, after applying the given slashing penalty.
+///
+/// The fee is paid out of the claimed stake.
+pub async fn undelegate_claim<V, R>(
+    fvk: &FullViewingKey,
+    view: &mut V,
+    mut rng: R,
+    validator_identity: IdentityKey,
+    start_epoch_index: u64,
+    penalty: u64,
+    unbonding_notes: Vec<NoteRecord>,
+    fee: u64,
+    source_address: Option<u64>,
+) -> Result<TransactionPlan>
+where
+    V: ViewClient,
+    R: RngCore + CryptoRng,
+{
+    let (self_address, _dtk) = fvk
+        .incoming()
+        .payment_address(source_address.unwrap_or(0).into());
+
+    let chain_params = view.chain_params().await?;
+
+    let unbonding_amount = unbonding_notes
+        .iter()
+        .map(|record| record.note.amount())
+        .sum();
+
+    let claim = UndelegateClaim {
+        validator_identity,
+        start_epoch_index,
+        penalty,
+        unbonding_amount,
+    };
+    let unbonded_amount = claim.unbonded_amount();
+    let output_amount = unbonded_amount.checked_sub(fee).ok_or_else(|| {
+        anyhow::anyhow!(
+            "claimed amount {} from unbonding amount {} is insufficient to pay fees {}",
+            unbonded_amount,
+            unbonding_amount,
+            fee
+        )
+    })?;
+
+    let mut plan = TransactionPlan {
+        chain_id: chain_params.chain_id,
+        fee: Fee(fee),
+        ..Default::default()
+    };
+
+    // add the claim action itself
+    plan.actions.push(claim.into());
+
+    // add the output for the claimed stake
+    plan.actions.push(
+        OutputPlan::new(
+            &mut rng,
+            Value {
+                amount: output_amount,
+                asset_id: *STAKING_TOKEN_ASSET_ID,
+            },
+            self_address,
+            MemoPlaintext::default(),
+        )
+        .into(),
+    );
+
+    for note_record in unbonding_notes {
+        plan.actions
+            .push(SpendPlan::new(&mut rng, note_record.note, note_record.position).into());
+    }
+
     Ok(plan)
 }
 
@@ -277,6 +538,7 @@ pub async fn send<V, R>(
     dest_address: Address,
     source_address: Option<u64>,
     tx_memo: Option<String>,
+    pad: bool,
 ) -> Result<TransactionPlan, anyhow::Error>
 where
     V: ViewClient,
@@ -396,5 +658,13 @@ where
         }
     }
 
+    // Pad the number of spends and outputs to the next power of two, so that
+    // the shape of the transaction reveals less about the transfer.
+    if pad {
+        let spends = plan.spend_plans().count().next_power_of_two();
+        let outputs = plan.output_plans().count().next_power_of_two();
+        plan.pad(&mut rng, spends, outputs);
+    }
+
     Ok(plan)
 }